@@ -0,0 +1,349 @@
+//! Frames values on a byte stream with [`tokio_util::codec`].
+
+use crate::de::{Config, DuplicateKeys, Limits};
+use crate::error::{Error, ErrorKind, Result};
+use crate::push::decode;
+use crate::read::scan::{Scan, Scanner};
+use crate::ser::Serializer;
+use crate::value::Value;
+use crate::write::Write;
+use core::marker::PhantomData;
+use serde::{de, Serialize};
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The default maximum number of bytes in a single frame.
+const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+/// A [`Decoder`] and [`Encoder`] for a stream of values.
+///
+/// Bencoded values are self-delimiting, so each value is a frame on its own
+/// without any length prefix or separator. Decoded frames are deserialized
+/// into an instance of `T` which is a [`Value`] by default. Any type which
+/// implements [Serialize][serde::ser::Serialize] can be encoded.
+///
+/// Received bytes are checked as they arrive, so malformed input is reported
+/// as an error before the whole frame is received. Previously received bytes
+/// are not parsed again.
+///
+/// Byte offsets in errors and the limits are relative to the start of the
+/// frame. By default, a frame can be at most 8 MiB. After a malformed frame,
+/// the rest of the stream cannot be decoded.
+///
+/// # Example
+///
+/// ```
+/// use bt_bencode::{BencodeCodec, Value};
+/// use tokio_util::bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let mut codec = BencodeCodec::<Value>::new();
+/// let mut buf = BytesMut::new();
+///
+/// codec.encode(&vec!["spam", "eggs"], &mut buf)?;
+/// assert_eq!(&buf[..], b"l4:spam4:eggse");
+///
+/// let value = codec.decode(&mut buf)?.unwrap();
+/// assert_eq!(value[1].as_str(), Some("eggs"));
+/// assert!(buf.is_empty());
+///
+/// # Ok::<_, bt_bencode::Error>(())
+/// ```
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BencodeCodec<T = Value> {
+    scanner: Scanner,
+    /// The number of bytes of the next frame which were scanned
+    scanned: usize,
+    config: Config,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for BencodeCodec<T> {
+    fn default() -> Self {
+        BencodeCodec::new()
+    }
+}
+
+impl<T> BencodeCodec<T> {
+    /// Constructs a new codec.
+    #[must_use]
+    pub fn new() -> Self {
+        let config = Config {
+            limits: Limits::default().max_bytes(DEFAULT_MAX_FRAME_LEN),
+            ..Config::default()
+        };
+        BencodeCodec {
+            scanner: Scanner::new(config.limits, 0),
+            scanned: 0,
+            config,
+            item: PhantomData,
+        }
+    }
+
+    /// Sets if only the canonical encoding of values is accepted.
+    ///
+    /// See [`Deserializer::set_strict()`][crate::Deserializer::set_strict].
+    pub fn set_strict(&mut self, strict: bool) {
+        self.config.strict = strict;
+    }
+
+    /// Sets how a dictionary with a repeated key is decoded.
+    ///
+    /// See [`Deserializer::set_duplicate_keys()`][crate::Deserializer::set_duplicate_keys].
+    pub fn set_duplicate_keys(&mut self, duplicate_keys: DuplicateKeys) {
+        self.config.duplicate_keys = duplicate_keys;
+    }
+
+    /// Sets the limits for each frame.
+    ///
+    /// The byte limit is the maximum frame length. See
+    /// [`Deserializer::set_limits()`][crate::Deserializer::set_limits].
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.config.limits = limits;
+        self.scanner.set_limits(limits);
+    }
+
+    /// Sets the maximum number of bytes in a single frame.
+    ///
+    /// Exceeding the limit while decoding or encoding returns an
+    /// [`ErrorKind::ByteLimitExceeded`] error.
+    pub fn set_max_frame_len(&mut self, max_frame_len: usize) {
+        self.set_limits(self.config.limits.max_bytes(max_frame_len));
+    }
+
+    /// Returns the maximum number of bytes in a single frame.
+    #[must_use]
+    pub fn max_frame_len(&self) -> usize {
+        self.config.limits.max_bytes
+    }
+
+    /// Starts on the next frame.
+    fn reset(&mut self) {
+        self.scanned = 0;
+        self.scanner = Scanner::new(self.config.limits, 0);
+    }
+}
+
+impl<T> Decoder for BencodeCodec<T>
+where
+    T: de::DeserializeOwned,
+{
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        match self.scanner.scan(&src[self.scanned..]) {
+            Ok(Scan::NeedMore(_)) => {
+                self.scanned = src.len();
+                Ok(None)
+            }
+            Ok(Scan::Complete(len)) => {
+                let frame = src.split_to(self.scanned + len);
+                self.reset();
+                decode(&frame, self.config).map(Some)
+            }
+            Err(err) => {
+                self.reset();
+                Err(err)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        match self.decode(src)? {
+            Some(value) => Ok(Some(value)),
+            None if src.is_empty() => Ok(None),
+            None => {
+                let byte_offset = self.scanner.byte_offset();
+                self.reset();
+                Err(Error::new(ErrorKind::EofWhileParsingValue, byte_offset))
+            }
+        }
+    }
+}
+
+impl<T, I> Encoder<I> for BencodeCodec<T>
+where
+    I: Serialize,
+{
+    type Error = Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<()> {
+        let start = dst.len();
+        let mut ser = Serializer::new(FrameWrite {
+            dst: &mut *dst,
+            start,
+            max_frame_len: self.max_frame_len(),
+        });
+        let result = item.serialize(&mut ser);
+        if result.is_err() {
+            dst.truncate(start);
+        }
+        result
+    }
+}
+
+/// Writes a frame after the existing bytes of a [`BytesMut`].
+///
+/// Bytes beyond the maximum frame length are not written, so an item which is
+/// too large does not grow the buffer without bound.
+struct FrameWrite<'a> {
+    dst: &'a mut BytesMut,
+    /// The length of `dst` before the frame
+    start: usize,
+    max_frame_len: usize,
+}
+
+impl Write for FrameWrite<'_> {
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        let len = self.dst.len() - self.start;
+        if buf.len() > self.max_frame_len - len {
+            return Err(Error::new(ErrorKind::ByteLimitExceeded, self.max_frame_len));
+        }
+        self.dst.extend_from_slice(buf);
+        Ok(())
+    }
+
+    #[inline]
+    fn written(&self) -> Option<&[u8]> {
+        Some(&self.dst[self.start..])
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.dst.truncate(self.start + len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{string::String, vec, vec::Vec};
+
+    #[test]
+    fn test_decode_chunks() -> Result<()> {
+        let mut codec = BencodeCodec::<Value>::new();
+        let mut buf = BytesMut::new();
+        assert_eq!(codec.decode(&mut buf)?, None);
+
+        buf.extend_from_slice(b"d3:cow3:m");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_eq!(buf.len(), 9);
+
+        buf.extend_from_slice(b"ooe4:spami42");
+        let value = codec.decode(&mut buf)?.unwrap();
+        assert_eq!(value["cow"].as_str(), Some("moo"));
+        assert_eq!(&buf[..], b"4:spami42");
+
+        assert_eq!(codec.decode(&mut buf)?, Some(Value::from("spam")));
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_error_kind!(
+            codec.decode_eof(&mut buf),
+            ErrorKind::EofWhileParsingValue,
+            3
+        );
+
+        buf.extend_from_slice(b"e");
+        assert_eq!(
+            codec.decode_eof(&mut buf)?.and_then(|v| v.as_u64()),
+            Some(42)
+        );
+        assert_eq!(codec.decode_eof(&mut buf)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_typed() -> Result<()> {
+        let mut codec = BencodeCodec::<Vec<String>>::new();
+        let mut buf = BytesMut::from(&b"l4:spam4:eggsei1el4:spame"[..]);
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(vec![String::from("spam"), String::from("eggs")])
+        );
+        assert_error_kind!(codec.decode(&mut buf), ErrorKind::InvalidType { .. }, 3);
+        assert_eq!(codec.decode(&mut buf)?, Some(vec![String::from("spam")]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut codec = BencodeCodec::<Value>::new();
+        let mut buf = BytesMut::from(&b"li1e"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(b"x");
+        assert_error_kind!(codec.decode(&mut buf), ErrorKind::InvalidList, 4);
+
+        let mut codec = BencodeCodec::<Value>::new();
+        codec.set_strict(true);
+        let mut buf = BytesMut::from(&b"i-0e"[..]);
+        assert_error_kind!(codec.decode(&mut buf), ErrorKind::NegativeZero, 2);
+    }
+
+    #[test]
+    fn test_max_frame_len() -> Result<()> {
+        let mut codec = BencodeCodec::<Value>::new();
+        assert_eq!(codec.max_frame_len(), DEFAULT_MAX_FRAME_LEN);
+        codec.set_max_frame_len(6);
+        assert_eq!(codec.max_frame_len(), 6);
+
+        let mut buf = BytesMut::from(&b"4:spam4:eggs"[..]);
+        assert_eq!(codec.decode(&mut buf)?, Some(Value::from("spam")));
+        assert_eq!(codec.decode(&mut buf)?, Some(Value::from("eggs")));
+
+        buf.extend_from_slice(b"l4:sp");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b"am");
+        assert_error_kind!(codec.decode(&mut buf), ErrorKind::ByteLimitExceeded, 6);
+
+        let mut buf = BytesMut::new();
+        codec.encode("spam", &mut buf)?;
+        assert_error_kind!(
+            codec.encode("spams", &mut buf),
+            ErrorKind::ByteLimitExceeded,
+            6
+        );
+        assert_eq!(&buf[..], b"4:spam");
+
+        let mut buf = BytesMut::new();
+        assert_error_kind!(
+            codec.encode(Value::ByteStr(vec![0; 1 << 20].into()), &mut buf),
+            ErrorKind::ByteLimitExceeded,
+            6
+        );
+        assert_error_kind!(
+            codec.encode(vec![1; 1 << 20], &mut buf),
+            ErrorKind::ByteLimitExceeded,
+            6
+        );
+        assert!(buf.is_empty());
+        assert!(buf.capacity() < 1024);
+
+        #[derive(serde_derive::Serialize)]
+        struct Unsorted {
+            b: u8,
+            a: u8,
+        }
+
+        codec.set_max_frame_len(14);
+        codec.encode(Unsorted { b: 1, a: 2 }, &mut buf)?;
+        assert_eq!(&buf[..], b"d1:ai2e1:bi1ee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode() -> Result<()> {
+        let mut codec = BencodeCodec::<Value>::new();
+        let mut buf = BytesMut::new();
+        codec.encode(Value::from("spam"), &mut buf)?;
+        codec.encode(&vec![1, 2], &mut buf)?;
+        assert_eq!(&buf[..], b"4:spamli1ei2ee");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(true, 2);
+        assert_error_kind!(codec.encode(&map, &mut buf), ErrorKind::UnsupportedType, 0);
+        assert_eq!(&buf[..], b"4:spamli1ei2ee");
+        Ok(())
+    }
+}
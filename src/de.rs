@@ -1,13 +1,31 @@
 //! Deserializes Bencode data.
 
 use crate::error::{Error, ErrorKind, Result};
-use crate::read::{self, Read, Ref};
-use serde::de::{self, Expected, Unexpected};
+use crate::raw::RAW_VALUE_TOKEN;
+use crate::read::{self, Read, Ref, ReplayRead};
+use crate::spanned::{SPANNED_FIELDS, SPANNED_TOKEN, VALUE_FIELD};
+use crate::value::{EncodedValueDeserializer, NumberDeserializer, VALUE_TOKEN};
+use crate::ByteString;
+use core::{cmp::Ordering, marker::PhantomData, ops::Range};
+use serde::de::{
+    self,
+    value::{BorrowedStrDeserializer, UsizeDeserializer},
+    Expected, Unexpected,
+};
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::vec::Vec;
+use alloc::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    vec::{self, Vec},
+};
 #[cfg(feature = "std")]
-use std::{io, vec::Vec};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    io,
+    vec::{self, Vec},
+};
 
 /// Deserializes an instance of `T` from the bytes of an [`io::Read`] type.
 ///
@@ -16,6 +34,11 @@ use std::{io, vec::Vec};
 /// should be constructed directly. See [`Deserializer::byte_offset()`] for an
 /// example.
 ///
+/// The source is read through a new [`io::BufReader`], which allocates a
+/// buffer and copies every byte through it. A source which is already buffered,
+/// such as an [`io::BufReader`] or a byte slice, is buffered twice; use
+/// [`from_buf_reader()`] for such a source instead.
+///
 /// # Errors
 ///
 /// Deserialization can fail if the data is not valid, if the data cannot cannot be deserialized
@@ -26,12 +49,128 @@ where
     R: io::Read,
     T: de::DeserializeOwned,
 {
-    let mut de = Deserializer::new(read::IoRead::new(r));
+    from_buf_reader(io::BufReader::new(r))
+}
+
+/// Deserializes an instance of `T` from the bytes of an [`io::BufRead`] type.
+///
+/// Byte strings are copied from the source's buffer in bulk. The entire
+/// [`io::BufRead`] source is consumed, and it is an error if there is trailing
+/// data. If trailing data is expected, then the [`Deserializer`] should be
+/// constructed directly with [`Deserializer::from_buf_reader()`].
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid, if the data cannot cannot be deserialized
+/// into an instance of `T`, if there is trailing data, and other IO errors.
+#[cfg(feature = "std")]
+pub fn from_buf_reader<R, T>(r: R) -> Result<T>
+where
+    R: io::BufRead,
+    T: de::DeserializeOwned,
+{
+    let mut de = Deserializer::from_buf_reader(r);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserializes an instance of `T` from the bytes of an [`io::Read`] type in
+/// strict mode.
+///
+/// Only the canonical encoding of every value is accepted. See
+/// [`Deserializer::set_strict()`] for the rules which are enforced.
+///
+/// Like [`from_reader()`], the source is read through a new
+/// [`io::BufReader`]. Construct a [`Deserializer`] with
+/// [`Deserializer::from_buf_reader()`] for a source which is already buffered.
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid, if the data is not
+/// canonically encoded, if the data cannot be deserialized into an instance of
+/// `T`, if there is trailing data, and other IO errors.
+#[cfg(feature = "std")]
+pub fn from_reader_strict<R, T>(r: R) -> Result<T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    let mut de = Deserializer::from_buf_reader(io::BufReader::new(r));
+    de.set_strict(true);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserializes an instance of `T` from the bytes of an
+/// [`embedded_io::Read`] type.
+///
+/// The entire source is consumed, and it is an error if there is trailing
+/// data. If trailing data is expected, then the [`Deserializer`] should be
+/// constructed directly with [`Deserializer::from_embedded_reader()`].
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid, if the data cannot cannot be deserialized
+/// into an instance of `T`, if there is trailing data, and other IO errors.
+#[cfg(feature = "embedded-io")]
+pub fn from_embedded_reader<R, T>(r: R) -> Result<T>
+where
+    R: embedded_io::Read,
+    T: de::DeserializeOwned,
+{
+    let mut de = Deserializer::from_embedded_reader(r);
     let value = T::deserialize(&mut de)?;
     de.end()?;
     Ok(value)
 }
 
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+macro_rules! from_async_reader {
+    ($(#[$attr:meta])* $name:ident, $bound:path) => {
+        $(#[$attr])*
+        ///
+        /// The bytes are read without blocking. The entire source is consumed,
+        /// and it is an error if there is trailing data. To read multiple
+        /// values or to set the limits, the [`Deserializer`] should be
+        /// constructed directly.
+        ///
+        /// # Errors
+        ///
+        /// Deserialization can fail if the data is not valid, if the data cannot cannot be
+        /// deserialized into an instance of `T`, if there is trailing data, and other IO errors.
+        pub async fn $name<R, T>(r: R) -> Result<T>
+        where
+            R: $bound + Unpin,
+            T: de::DeserializeOwned,
+        {
+            let mut de = Deserializer::$name(r);
+            de.fill_value().await?;
+            let value = T::deserialize(&mut de)?;
+            de.read.fill_buf().await?;
+            de.end()?;
+            Ok(value)
+        }
+    };
+}
+
+#[cfg(all(feature = "std", feature = "tokio"))]
+from_async_reader!(
+    /// Deserializes an instance of `T` from the bytes of a
+    /// [`tokio::io::AsyncRead`] type.
+    from_tokio_reader,
+    tokio::io::AsyncRead
+);
+
+#[cfg(all(feature = "std", feature = "futures-io"))]
+from_async_reader!(
+    /// Deserializes an instance of `T` from the bytes of a
+    /// [`futures_io::AsyncRead`] type.
+    from_futures_reader,
+    futures_io::AsyncRead
+);
+
 /// Deserializes an instance of `T` from a slice of bytes.
 ///
 /// The entire slice of bytes is consumed, and it is an error if there is
@@ -53,12 +192,175 @@ where
     Ok(value)
 }
 
+/// Deserializes an instance of `T` from a slice of bytes in strict mode.
+///
+/// Only the canonical encoding of every value is accepted. See
+/// [`Deserializer::set_strict()`] for the rules which are enforced.
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid, if the data is not
+/// canonically encoded, if the data cannot be deserialized into an instance of
+/// `T`, and if there is trailing data.
+pub fn from_slice_strict<'a, T>(s: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(read::SliceRead::new(s));
+    de.set_strict(true);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// How a dictionary with a repeated key is deserialized.
+///
+/// See [`Deserializer::set_duplicate_keys()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Every entry is passed to the deserialized type.
+    ///
+    /// The type decides how to handle a repeated key. For instance, a
+    /// [`Value`][crate::Value] keeps the last entry while a derived struct
+    /// returns an error for a repeated field.
+    Allow,
+    /// An [`ErrorKind::DuplicateKey`] error is returned.
+    ///
+    /// Dictionaries in values which are captured as raw bytes or ignored are
+    /// checked as well.
+    Error,
+    /// Only the first entry for a key is used. Later entries are skipped.
+    KeepFirst,
+    /// Only the last entry for a key is used. Earlier entries are skipped.
+    ///
+    /// The entire dictionary is read before any of its entries are
+    /// deserialized.
+    KeepLast,
+}
+
+impl Default for DuplicateKeys {
+    fn default() -> Self {
+        DuplicateKeys::Allow
+    }
+}
+
+/// Limits on the resources used while deserializing.
+///
+/// Untrusted input may try to exhaust memory or the stack with huge byte
+/// string lengths or deeply nested lists and dictionaries. If a limit is
+/// exceeded, deserialization fails with a dedicated [`ErrorKind`] instead.
+///
+/// By default, lists and dictionaries can be nested up to a depth of 128, an
+/// integer can have up to 64 digits, and there are no other limits.
+///
+/// See [`Deserializer::set_limits()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    pub(crate) max_depth: usize,
+    pub(crate) max_byte_str_len: usize,
+    pub(crate) max_integer_len: usize,
+    pub(crate) max_len: usize,
+    pub(crate) max_bytes: usize,
+}
+
+impl Limits {
+    /// Constructs limits which do not restrict anything.
+    ///
+    /// Raw byte captures (such as `&[u8]` fields) and ignored values are
+    /// scanned without recursion, but deserializing into most types recurses
+    /// once per nested list or dictionary. Deeply nested input may overflow
+    /// the stack without a depth limit.
+    #[must_use]
+    pub fn unlimited() -> Self {
+        Limits {
+            max_depth: core::usize::MAX,
+            max_byte_str_len: core::usize::MAX,
+            max_integer_len: core::usize::MAX,
+            max_len: core::usize::MAX,
+            max_bytes: core::usize::MAX,
+        }
+    }
+
+    /// Sets the maximum depth of nested lists and dictionaries.
+    ///
+    /// A top-level list or dictionary has a depth of 1. Exceeding the limit
+    /// returns an [`ErrorKind::DepthLimitExceeded`] error.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the maximum length of a single byte string.
+    ///
+    /// The length is checked before any of the byte string is read. Exceeding
+    /// the limit returns an [`ErrorKind::ByteStrLenLimitExceeded`] error.
+    #[must_use]
+    pub fn max_byte_str_len(mut self, max_byte_str_len: usize) -> Self {
+        self.max_byte_str_len = max_byte_str_len;
+        self
+    }
+
+    /// Sets the maximum number of digits of a single integer.
+    ///
+    /// The digits are counted while they are read, so an integer of any size
+    /// is never held in memory in full. Leading zeros are counted, and a `-`
+    /// sign is not. Exceeding the limit returns an
+    /// [`ErrorKind::IntegerLenLimitExceeded`] error.
+    #[must_use]
+    pub fn max_integer_len(mut self, max_integer_len: usize) -> Self {
+        self.max_integer_len = max_integer_len;
+        self
+    }
+
+    /// Sets the maximum number of elements in a single list or the maximum
+    /// number of entries in a single dictionary.
+    ///
+    /// Exceeding the limit returns an [`ErrorKind::ElementLimitExceeded`]
+    /// error.
+    #[must_use]
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Sets the maximum total number of bytes which are read from the source.
+    ///
+    /// Exceeding the limit returns an [`ErrorKind::ByteLimitExceeded`] error.
+    #[must_use]
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::unlimited().max_depth(128).max_integer_len(64)
+    }
+}
+
+/// Options which are inherited by any nested deserializer.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Config {
+    /// If only canonical encodings are accepted
+    pub(crate) strict: bool,
+    /// How repeated dictionary keys are handled
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) limits: Limits,
+    /// If `bool` values are deserialized from the integers 0 and 1
+    pub(crate) bool_as_int: bool,
+}
+
 #[derive(Debug)]
 /// A `Bencode` Deserializer for types which implement [Deserialize][serde::de::Deserialize].
 pub struct Deserializer<R> {
     read: R,
     /// Temporary buffer used to reduce allocations made
     buf: Vec<u8>,
+    config: Config,
+    /// The number of lists and dictionaries which can still be nested
+    remaining_depth: usize,
 }
 
 impl<'a, R> Deserializer<R>
@@ -67,12 +369,159 @@ where
 {
     /// Constructs a Deserializer from a readable source.
     pub fn new(read: R) -> Self {
+        let config = Config::default();
         Deserializer {
             read,
             buf: Vec::default(),
+            config,
+            remaining_depth: config.limits.max_depth,
         }
     }
 
+    /// Constructs a Deserializer with the given configuration.
+    pub(crate) fn with_config(read: R, config: Config) -> Self {
+        let mut de = Deserializer::new(read);
+        de.config = config;
+        de.set_limits(config.limits);
+        de
+    }
+
+    /// Sets if only the canonical encoding of values is accepted.
+    ///
+    /// Bencode data is often hashed or signed (e.g. the `info` dictionary in a
+    /// metainfo file or a mutable item in the DHT), so there should be exactly
+    /// one valid encoding for any value. In strict mode, the following are
+    /// rejected:
+    ///
+    /// - integers with leading zeros (e.g. `i03e`)
+    /// - negative zero (e.g. `i-0e`)
+    /// - byte string lengths with leading zeros (e.g. `03:abc`)
+    /// - dictionary keys which are not sorted by their raw bytes
+    /// - repeated dictionary keys (regardless of the
+    ///   [`DuplicateKeys`] policy)
+    ///
+    /// The rules also apply to values captured as raw bytes (e.g. when a field
+    /// is deserialized as `&[u8]` or [`ByteString`][crate::ByteString]).
+    ///
+    /// By default, strict mode is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::Deserialize as _;
+    /// use bt_bencode::{Deserializer, ErrorKind};
+    ///
+    /// let mut de = Deserializer::from_slice(b"i03e");
+    /// de.set_strict(true);
+    /// let error = u64::deserialize(&mut de).unwrap_err();
+    /// match error.kind() {
+    ///     ErrorKind::LeadingZero => {}
+    ///     _ => panic!("unexpected error"),
+    /// }
+    /// assert_eq!(error.byte_offset(), 1);
+    /// ```
+    pub fn set_strict(&mut self, strict: bool) {
+        self.config.strict = strict;
+    }
+
+    /// Sets how a dictionary with a repeated key is deserialized.
+    ///
+    /// Peers may send dictionaries with repeated keys to confuse parsers
+    /// which disagree on which entry is used. The policy applies to every
+    /// dictionary, whether it is deserialized into a [`Value`][crate::Value]
+    /// or into a typed struct or map.
+    ///
+    /// By default, the policy is [`DuplicateKeys::Allow`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use serde::Deserialize as _;
+    /// use bt_bencode::{Deserializer, DuplicateKeys, ErrorKind};
+    ///
+    /// let bytes: &[u8] = b"d3:cow3:moo3:cow4:oinke";
+    ///
+    /// let mut de = Deserializer::from_slice(bytes);
+    /// de.set_duplicate_keys(DuplicateKeys::KeepFirst);
+    /// let dict = BTreeMap::<String, String>::deserialize(&mut de)?;
+    /// assert_eq!(dict["cow"], "moo");
+    ///
+    /// let mut de = Deserializer::from_slice(bytes);
+    /// de.set_duplicate_keys(DuplicateKeys::Error);
+    /// let error = BTreeMap::<String, String>::deserialize(&mut de).unwrap_err();
+    /// match error.kind() {
+    ///     ErrorKind::DuplicateKey(key) => assert_eq!(key.as_slice(), b"cow"),
+    ///     _ => panic!("unexpected error"),
+    /// }
+    /// assert_eq!(error.byte_offset(), 11);
+    /// # Ok::<_, bt_bencode::Error>(())
+    /// ```
+    pub fn set_duplicate_keys(&mut self, duplicate_keys: DuplicateKeys) {
+        self.config.duplicate_keys = duplicate_keys;
+    }
+
+    /// Sets the limits on the resources used while deserializing.
+    ///
+    /// The limits should be set before any value is deserialized. The byte
+    /// budget counts every byte read from the source by this deserializer.
+    ///
+    /// By default, the [`Limits::default()`] limits are used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::Deserialize as _;
+    /// use bt_bencode::{Deserializer, ErrorKind, Limits, Value};
+    ///
+    /// let mut de = Deserializer::from_slice(b"llllli1eeeeee");
+    /// de.set_limits(Limits::default().max_depth(4));
+    /// let error = Value::deserialize(&mut de).unwrap_err();
+    /// match error.kind() {
+    ///     ErrorKind::DepthLimitExceeded => {}
+    ///     _ => panic!("unexpected error"),
+    /// }
+    /// assert_eq!(error.byte_offset(), 4);
+    ///
+    /// let mut de = Deserializer::from_slice(b"9999999999:spam");
+    /// de.set_limits(Limits::default().max_byte_str_len(1024));
+    /// let error = Value::deserialize(&mut de).unwrap_err();
+    /// match error.kind() {
+    ///     ErrorKind::ByteStrLenLimitExceeded => {}
+    ///     _ => panic!("unexpected error"),
+    /// }
+    /// ```
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.config.limits = limits;
+        self.remaining_depth = limits.max_depth;
+        self.read.set_limits(limits);
+    }
+
+    /// Sets if `bool` values are deserialized from the integers `i0e` and
+    /// `i1e`.
+    ///
+    /// Any other value is an error. By default, a `bool` cannot be
+    /// deserialized.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::Deserialize as _;
+    /// use bt_bencode::Deserializer;
+    ///
+    /// let mut de = Deserializer::from_slice(b"li1ei0ee");
+    /// de.set_bool_as_int(true);
+    /// assert_eq!(Vec::<bool>::deserialize(&mut de)?, vec![true, false]);
+    ///
+    /// let mut de = Deserializer::from_slice(b"i2e");
+    /// de.set_bool_as_int(true);
+    /// assert!(bool::deserialize(&mut de).is_err());
+    /// # Ok::<_, bt_bencode::Error>(())
+    /// ```
+    pub fn set_bool_as_int(&mut self, bool_as_int: bool) {
+        self.config.bool_as_int = bool_as_int;
+    }
+
     /// Returns the byte offset in the underlying readable source.
     ///
     /// For most use cases, the entire source should be consumed with no
@@ -124,6 +573,43 @@ where
         }
     }
 
+    /// Turns the Deserializer into an iterator over the values in the source.
+    ///
+    /// The values must be encoded one after another without any other data
+    /// in between. Each call to [`Iterator::next()`] deserializes one
+    /// top-level value. The iterator ends after the last value or after an
+    /// error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bt_bencode::Deserializer;
+    ///
+    /// let mut iter = Deserializer::from_slice(b"4:spami42e").into_iter::<bt_bencode::Value>();
+    ///
+    /// assert_eq!(iter.next().unwrap()?.as_str(), Some("spam"));
+    /// assert_eq!(iter.value_offset(), 0);
+    /// assert_eq!(iter.next().unwrap()?.as_u64(), Some(42));
+    /// assert_eq!(iter.value_offset(), 6);
+    /// assert!(iter.next().is_none());
+    ///
+    /// # Ok::<_, bt_bencode::Error>(())
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T>(self) -> StreamDeserializer<'a, R, T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let value_offset = self.read.byte_offset();
+        StreamDeserializer {
+            de: self,
+            value_offset,
+            is_done: false,
+            output: PhantomData,
+            lifetime: PhantomData,
+        }
+    }
+
     fn on_end_seq(&mut self) -> Result<()> {
         match self.parse_peek()? {
             b'e' => {
@@ -147,25 +633,13 @@ where
     fn unexpected_type_err(&mut self, exp: &dyn Expected) -> Result<Error> {
         match self.parse_peek()? {
             b'0'..=b'9' => {
-                self.buf.clear();
-                let bytes = self.read.parse_byte_str(&mut self.buf)?;
+                let bytes = self.parse_byte_str()?;
                 Ok(de::Error::invalid_type(Unexpected::Bytes(&bytes), exp))
             }
             b'i' => {
                 self.parse_next()?;
-                let (is_positive, num) = self.parse_integer()?;
-                if is_positive {
-                    Ok(de::Error::invalid_type(Unexpected::Unsigned(num), exp))
-                } else {
-                    use core::convert::TryFrom;
-
-                    Ok(de::Error::invalid_type(
-                        Unexpected::Signed(-i64::try_from(num).map_err(|_| {
-                            Error::new(ErrorKind::InvalidInteger, self.read.byte_offset())
-                        })?),
-                        exp,
-                    ))
-                }
+                let integer = self.parse_integer(false)?;
+                Ok(de::Error::invalid_type(integer.unexpected(), exp))
             }
             b'l' => Ok(de::Error::invalid_type(Unexpected::Seq, exp)),
             b'd' => Ok(de::Error::invalid_type(Unexpected::Map, exp)),
@@ -176,6 +650,13 @@ where
         }
     }
 
+    /// Sets the current byte offset on an error which does not have a byte
+    /// offset, such as an error from a visitor.
+    #[inline]
+    fn fix_byte_offset<T>(&self, result: Result<T>) -> Result<T> {
+        result.map_err(|err| err.fix_byte_offset(self.read.byte_offset()))
+    }
+
     #[inline]
     fn parse_peek(&mut self) -> Result<u8> {
         self.read
@@ -190,7 +671,12 @@ where
             .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.read.byte_offset()))?
     }
 
-    fn parse_integer(&mut self) -> Result<(bool, u64)> {
+    /// Parses the rest of an integer after the `i`.
+    ///
+    /// The digits of an integer which does not fit into an [i128] or a [u128]
+    /// are only collected if `keep_big` is true. Otherwise,
+    /// [`Integer::Overflow`] is returned for such an integer.
+    fn parse_integer(&mut self, keep_big: bool) -> Result<Integer<'_>> {
         let peek = self
             .read
             .peek()
@@ -210,12 +696,16 @@ where
             .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??;
         match peek {
             b'0'..=b'9' => {}
-            _ => {
-                return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset()));
+            b => {
+                return Err(Error::new(ErrorKind::UnexpectedByte(b), self.byte_offset()));
             }
         }
 
-        let mut value: u64 = 0;
+        let digits_start = self.byte_offset();
+        let max_integer_len = self.config.limits.max_integer_len;
+        let mut value: u128 = 0;
+        // Once the value overflows, the digits are collected in the buffer.
+        let mut is_big = false;
 
         loop {
             match self
@@ -224,156 +714,619 @@ where
                 .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
             {
                 b'e' => {
-                    return Ok((is_positive, value));
+                    if self.config.strict && value == 0 && !is_positive {
+                        return Err(Error::new(ErrorKind::NegativeZero, digits_start));
+                    }
+                    if !is_big {
+                        if is_positive {
+                            return Ok(Integer::Unsigned(value));
+                        }
+                        if value <= i128::max_value() as u128 + 1 {
+                            return Ok(Integer::Signed((value as i128).wrapping_neg()));
+                        }
+                        if !keep_big {
+                            return Ok(Integer::Overflow);
+                        }
+                        self.buf.clear();
+                        self.buf.push(b'-');
+                        self.buf
+                            .extend_from_slice(itoa::Buffer::new().format(value).as_bytes());
+                    } else if !keep_big {
+                        return Ok(Integer::Overflow);
+                    }
+                    return core::str::from_utf8(&self.buf)
+                        .map(Integer::Big)
+                        .map_err(|_| Error::new(ErrorKind::InvalidInteger, digits_start));
                 }
                 n @ b'0'..=b'9' => {
-                    value = value
+                    if self.byte_offset() - digits_start > max_integer_len {
+                        return Err(Error::new(
+                            ErrorKind::IntegerLenLimitExceeded,
+                            self.byte_offset() - 1,
+                        ));
+                    }
+                    if self.config.strict && value == 0 && self.byte_offset() != digits_start + 1 {
+                        return Err(Error::new(ErrorKind::LeadingZero, digits_start));
+                    }
+                    if is_big {
+                        if keep_big {
+                            self.buf.push(n);
+                        }
+                        continue;
+                    }
+                    match value
                         .checked_mul(10)
-                        .ok_or_else(|| Error::new(ErrorKind::InvalidInteger, self.byte_offset()))?;
-                    value = value
-                        .checked_add(u64::from(n - b'0'))
-                        .ok_or_else(|| Error::new(ErrorKind::InvalidInteger, self.byte_offset()))?;
+                        .and_then(|value| value.checked_add(u128::from(n - b'0')))
+                    {
+                        Some(next) => value = next,
+                        None if !keep_big => is_big = true,
+                        None => {
+                            is_big = true;
+                            self.buf.clear();
+                            if !is_positive {
+                                self.buf.push(b'-');
+                            }
+                            self.buf
+                                .extend_from_slice(itoa::Buffer::new().format(value).as_bytes());
+                            self.buf.push(n);
+                        }
+                    }
+                }
+                b => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedByte(b),
+                        self.byte_offset() - 1,
+                    ))
                 }
-                _ => return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset())),
             }
         }
     }
-}
-
-#[cfg(feature = "std")]
-impl<R> Deserializer<read::IoRead<R>>
-where
-    R: io::Read,
-{
-    /// Constructs a Deserializer from an [`std::io::Read`][std::io::Read] source.
-    #[must_use]
-    pub fn from_reader(reader: R) -> Self {
-        Deserializer::new(read::IoRead::new(reader))
-    }
-}
 
-impl<'a> Deserializer<read::SliceRead<'a>> {
-    /// Constructs a Deserializer from a `&[u8]`.
-    #[must_use]
-    pub fn from_slice(bytes: &'a [u8]) -> Self {
-        Deserializer::new(read::SliceRead::new(bytes))
-    }
-}
+    /// Parses the next byte string and returns the contents.
+    ///
+    /// In strict mode, a length with a leading zero is rejected.
+    fn parse_byte_str(&mut self) -> Result<Ref<'a, '_, [u8]>> {
+        if self.config.strict && self.parse_peek()? == b'0' {
+            let start = self.read.byte_offset();
+            self.parse_next()?;
+            return match self.parse_next()? {
+                b':' => Ok(Ref::Source(&[])),
+                b'0'..=b'9' => Err(Error::new(ErrorKind::NonCanonicalByteStrLen, start)),
+                b => Err(Error::new(
+                    ErrorKind::UnexpectedByte(b),
+                    self.read.byte_offset() - 1,
+                )),
+            };
+        }
 
-macro_rules! forward_deserialize_signed_integer {
-    ($method:ident) => {
-        #[inline]
-        fn $method<V>(self, visitor: V) -> Result<V::Value>
-        where
-            V: de::Visitor<'de>,
-        {
-            self.deserialize_i64(visitor)
+        let start = self.read.byte_offset();
+        self.buf.clear();
+        let bytes = self.read.parse_byte_str(&mut self.buf)?;
+        // The reader may not enforce the limits.
+        if bytes.len() > self.config.limits.max_byte_str_len {
+            return Err(Error::new(ErrorKind::ByteStrLenLimitExceeded, start));
         }
-    };
-}
+        Ok(bytes)
+    }
 
-macro_rules! forward_deserialize_unsigned_integer {
-    ($method:ident) => {
-        #[inline]
-        fn $method<V>(self, visitor: V) -> Result<V::Value>
-        where
-            V: de::Visitor<'de>,
-        {
-            self.deserialize_u64(visitor)
+    /// Starts a nested list or dictionary.
+    fn enter_nested(&mut self) -> Result<()> {
+        if self.remaining_depth == 0 {
+            return Err(Error::new(
+                ErrorKind::DepthLimitExceeded,
+                self.read.byte_offset(),
+            ));
         }
-    };
-}
+        self.remaining_depth -= 1;
+        Ok(())
+    }
 
-impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
-    type Error = Error;
+    /// Ends a nested list or dictionary.
+    fn leave_nested(&mut self) {
+        self.remaining_depth += 1;
+    }
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        match self.parse_peek()? {
+    /// Parses the next value and returns its complete encoding.
+    ///
+    /// In strict mode, the encoding must be canonical.
+    fn parse_raw_value(&mut self) -> Result<Ref<'a, '_, [u8]>> {
+        let start = self.read.byte_offset();
+        self.buf.clear();
+        let raw = match self.parse_peek()? {
             b'0'..=b'9' => {
-                self.buf.clear();
-                match self.read.parse_byte_str(&mut self.buf)? {
-                    Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
-                    Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
-                }
+                self.set_raw_limits();
+                self.read.parse_raw_byte_str(&mut self.buf)?
             }
-            b'i' => {
-                self.parse_next()?;
+            b'i' => self.read.parse_raw_integer(&mut self.buf)?,
+            b'l' => {
+                self.set_raw_limits();
+                self.read.parse_raw_list(&mut self.buf)?
+            }
+            b'd' => {
+                self.set_raw_limits();
+                self.read.parse_raw_dict(&mut self.buf)?
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ExpectedSomeValue,
+                    self.read.byte_offset(),
+                ))
+            }
+        };
 
-                let (is_positive, num) = self.parse_integer()?;
-                if is_positive {
-                    visitor.visit_u64(num)
-                } else {
-                    use core::convert::TryFrom;
+        check_raw(&raw, start, self.config)?;
 
-                    visitor.visit_i64(-i64::try_from(num).map_err(|_| {
-                        Error::new(ErrorKind::InvalidInteger, self.read.byte_offset())
-                    })?)
-                }
+        Ok(raw)
+    }
+
+    /// Sets the limits for the next raw value read.
+    ///
+    /// The depth of the raw value is limited by the current depth.
+    fn set_raw_limits(&mut self) {
+        self.read.set_limits(Limits {
+            max_depth: self.remaining_depth,
+            ..self.config.limits
+        });
+    }
+
+    fn visit_list<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        self.enter_nested()?;
+        self.parse_next()?;
+        let ret = visitor.visit_seq(SeqAccess::new(self));
+        self.leave_nested();
+        match (ret, self.on_end_seq()) {
+            (Ok(ret), Ok(())) => Ok(ret),
+            (Err(err), _) | (_, Err(err)) => Err(err),
+        }
+    }
+
+    fn visit_dict<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        if self.config.duplicate_keys == DuplicateKeys::KeepLast {
+            return self.visit_dict_keep_last(visitor);
+        }
+
+        self.enter_nested()?;
+        self.parse_next()?;
+        let ret = visitor.visit_map(MapAccess::new(self));
+        self.leave_nested();
+        match (ret, self.on_end_map()) {
+            (Ok(ret), Ok(())) => Ok(ret),
+            (Err(err), _) | (_, Err(err)) => Err(err),
+        }
+    }
+
+    /// Visits a dictionary where only the last entry for a key is used.
+    ///
+    /// The entire dictionary is read first to find the last entry for every
+    /// key. The remaining entries are deserialized from the read bytes.
+    fn visit_dict_keep_last<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        let start = self.read.byte_offset();
+        let config = self.config;
+        let remaining_depth = self.remaining_depth;
+        if remaining_depth == 0 {
+            return Err(Error::new(ErrorKind::DepthLimitExceeded, start));
+        }
+        self.set_raw_limits();
+        self.buf.clear();
+        let raw = self.read.parse_raw_dict(&mut self.buf)?;
+
+        let entries = {
+            let entries = dict_entries(&raw, start, config)?;
+            let mut last_entries = BTreeMap::new();
+            for (idx, (key, _)) in entries.iter().enumerate() {
+                last_entries.insert(&raw[key.clone()], idx);
             }
-            b'l' => {
-                self.parse_next()?;
-                let ret = visitor.visit_seq(SeqAccess { de: self });
-                match (ret, self.on_end_seq()) {
-                    (Ok(ret), Ok(())) => Ok(ret),
-                    (Err(err), _) | (_, Err(err)) => Err(err),
-                }
+            let mut last_entries = last_entries
+                .into_iter()
+                .map(|(_, idx)| idx)
+                .collect::<Vec<_>>();
+            last_entries.sort_unstable();
+            last_entries
+                .into_iter()
+                .map(|idx| entries[idx].1.clone())
+                .collect::<Vec<_>>()
+        };
+
+        visitor.visit_map(ReplayMapAccess {
+            raw,
+            byte_offset: start,
+            entries: entries.into_iter(),
+            config,
+            remaining_depth: remaining_depth - 1,
+            de: None,
+        })
+    }
+}
+
+/// An iterator over the values which are encoded one after another in a
+/// source.
+///
+/// Constructed with [`Deserializer::into_iter()`].
+#[derive(Debug)]
+pub struct StreamDeserializer<'a, R, T> {
+    de: Deserializer<R>,
+    /// The byte offset where the last value started
+    value_offset: usize,
+    /// If the end of the source or an error was reached
+    is_done: bool,
+    output: PhantomData<T>,
+    lifetime: PhantomData<&'a ()>,
+}
+
+impl<'a, R, T> StreamDeserializer<'a, R, T>
+where
+    R: Read<'a>,
+    T: de::Deserialize<'a>,
+{
+    /// Returns the byte offset where the value (or the error) which was last
+    /// returned started.
+    ///
+    /// Before any value is returned, it is the byte offset of the source.
+    #[must_use]
+    pub fn value_offset(&self) -> usize {
+        self.value_offset
+    }
+
+    /// Returns the byte offset after the last value which was read.
+    ///
+    /// If the last returned item was an error, the byte offset is after the
+    /// bytes which were read before the error.
+    #[must_use]
+    pub fn byte_offset(&self) -> usize {
+        self.de.byte_offset()
+    }
+}
+
+impl<'a, R, T> Iterator for StreamDeserializer<'a, R, T>
+where
+    R: Read<'a>,
+    T: de::Deserialize<'a>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.is_done {
+            return None;
+        }
+
+        self.value_offset = self.de.byte_offset();
+        let result = match self.de.read.peek() {
+            None => {
+                self.is_done = true;
+                return None;
             }
-            b'd' => {
+            Some(Err(err)) => Err(err),
+            Some(Ok(_)) => T::deserialize(&mut self.de),
+        };
+        self.is_done = result.is_err();
+        Some(result)
+    }
+}
+
+impl<'a, R, T> core::iter::FusedIterator for StreamDeserializer<'a, R, T>
+where
+    R: Read<'a>,
+    T: de::Deserialize<'a>,
+{
+}
+
+#[cfg(feature = "std")]
+impl<R> Deserializer<read::IoRead<R>>
+where
+    R: io::Read,
+{
+    /// Constructs a Deserializer from an [`std::io::Read`][std::io::Read] source.
+    #[must_use]
+    pub fn from_reader(reader: R) -> Self {
+        Deserializer::new(read::IoRead::new(reader))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> Deserializer<read::BufIoRead<R>>
+where
+    R: io::BufRead,
+{
+    /// Constructs a Deserializer from an [`std::io::BufRead`][std::io::BufRead] source.
+    ///
+    /// Unlike [`Deserializer::from_reader()`], byte strings are copied from
+    /// the source's buffer in bulk.
+    #[must_use]
+    pub fn from_buf_reader(reader: R) -> Self {
+        Deserializer::new(read::BufIoRead::new(reader))
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<R> Deserializer<read::EmbeddedIoRead<R>>
+where
+    R: embedded_io::Read,
+{
+    /// Constructs a Deserializer from an [`embedded_io::Read`] source.
+    #[must_use]
+    pub fn from_embedded_reader(reader: R) -> Self {
+        Deserializer::new(read::EmbeddedIoRead::new(reader))
+    }
+}
+
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+macro_rules! impl_async_deserializer {
+    ($read:ident, $bound:path, $from_reader:ident, $reader_doc:expr) => {
+        impl<R> Deserializer<read::$read<R>>
+        where
+            R: $bound + Unpin,
+        {
+            #[doc = $reader_doc]
+            ///
+            /// Before a value is deserialized, the value must be read with
+            /// [`Deserializer::fill_value()`].
+            #[must_use]
+            pub fn $from_reader(reader: R) -> Self {
+                Deserializer::new(read::$read::new(reader))
+            }
+
+            /// Reads bytes from the source until a complete value is buffered.
+            ///
+            /// The bytes are checked for syntax errors and the limits while
+            /// they are read, so the value can be deserialized afterwards
+            /// without blocking. It can be called again after a value is
+            /// deserialized to read the next value from the source.
+            ///
+            /// The future is cancel safe. If it is dropped before it
+            /// completes, the bytes which were read are kept, and a new call
+            /// continues where the previous one stopped.
+            ///
+            /// # Errors
+            ///
+            /// Errors include malformatted input, exceeded limits, the end of
+            /// the source, and other IO errors.
+            pub async fn fill_value(&mut self) -> Result<()> {
+                self.read.set_limits(self.config.limits);
+                self.read.fill_value().await
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "std", feature = "tokio"))]
+impl_async_deserializer!(
+    TokioIoRead,
+    tokio::io::AsyncRead,
+    from_tokio_reader,
+    "Constructs a Deserializer from a [`tokio::io::AsyncRead`] source."
+);
+
+#[cfg(all(feature = "std", feature = "futures-io"))]
+impl_async_deserializer!(
+    FuturesIoRead,
+    futures_io::AsyncRead,
+    from_futures_reader,
+    "Constructs a Deserializer from a [`futures_io::AsyncRead`] source."
+);
+
+impl<'a> Deserializer<read::SliceRead<'a>> {
+    /// Constructs a Deserializer from a `&[u8]`.
+    #[must_use]
+    pub fn from_slice(bytes: &'a [u8]) -> Self {
+        Deserializer::new(read::SliceRead::new(bytes))
+    }
+}
+
+/// A parsed integer.
+enum Integer<'a> {
+    /// A non-negative integer.
+    Unsigned(u128),
+    /// A negative integer.
+    Signed(i128),
+    /// The decimal digits of an integer which does not fit into an [i128] or a
+    /// [u128].
+    Big(&'a str),
+    /// An integer which does not fit into an [i128] or a [u128] and whose
+    /// digits were not kept.
+    Overflow,
+}
+
+impl<'a> Integer<'a> {
+    /// Visits the integer with the smallest type which can represent it.
+    fn visit<'de, V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        use core::convert::TryFrom;
+
+        match self {
+            Integer::Unsigned(n) => match u64::try_from(n) {
+                Ok(n) => visitor.visit_u64(n),
+                Err(_) => visitor.visit_u128(n),
+            },
+            Integer::Signed(n) => match i64::try_from(n) {
+                Ok(n) => visitor.visit_i64(n),
+                Err(_) => visitor.visit_i128(n),
+            },
+            Integer::Big(digits) => visitor.visit_map(NumberDeserializer::new(digits)),
+            Integer::Overflow => Err(Error::with_kind(ErrorKind::IntegerOverflow)),
+        }
+    }
+
+    fn unexpected(&self) -> Unexpected<'static> {
+        use core::convert::TryFrom;
+
+        match *self {
+            Integer::Unsigned(n) => u64::try_from(n)
+                .map(Unexpected::Unsigned)
+                .unwrap_or(Unexpected::Other("integer")),
+            Integer::Signed(n) => i64::try_from(n)
+                .map(Unexpected::Signed)
+                .unwrap_or(Unexpected::Other("integer")),
+            Integer::Big(_) | Integer::Overflow => Unexpected::Other("integer"),
+        }
+    }
+}
+
+macro_rules! forward_deserialize_signed_integer {
+    ($method:ident) => {
+        #[inline]
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            self.deserialize_i64(visitor)
+        }
+    };
+}
+
+macro_rules! forward_deserialize_unsigned_integer {
+    ($method:ident) => {
+        #[inline]
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            self.deserialize_u64(visitor)
+        }
+    };
+}
+
+impl<'de, R: Read<'de>> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let value = match self.parse_peek()? {
+            b'0'..=b'9' => match self.parse_byte_str()? {
+                Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+            },
+            b'i' => {
                 self.parse_next()?;
-                let ret = visitor.visit_map(MapAccess { de: self });
-                match (ret, self.on_end_map()) {
-                    (Ok(ret), Ok(())) => Ok(ret),
-                    (Err(err), _) | (_, Err(err)) => Err(err),
-                }
+                self.parse_integer(true)?.visit(visitor)
             }
+            b'l' => self.visit_list(visitor),
+            b'd' => self.visit_dict(visitor),
             _ => Err(Error::new(
                 ErrorKind::ExpectedSomeValue,
                 self.read.byte_offset(),
             )),
-        }
+        };
+        self.fix_byte_offset(value)
     }
 
     forward_to_deserialize_any! {
-        bool f32 f64 unit unit_struct
+        f32 f64 unit unit_struct
+
+        char
+
+        identifier
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.parse_peek()? {
+            b'0'..=b'9' => {}
+            _ => return self.deserialize_any(visitor),
+        }
+
+        // On invalid UTF-8, the length of the byte string and the number of
+        // valid bytes are returned.
+        let value = match self.parse_byte_str()? {
+            Ref::Source(bytes) => core::str::from_utf8(bytes)
+                .map(|s| visitor.visit_borrowed_str(s))
+                .map_err(|err| (bytes.len(), err.valid_up_to())),
+            Ref::Buffer(bytes) => core::str::from_utf8(bytes)
+                .map(|s| visitor.visit_str(s))
+                .map_err(|err| (bytes.len(), err.valid_up_to())),
+        };
+        let value = match value {
+            Ok(value) => value,
+            Err((len, valid_up_to)) => Err(Error::new(
+                ErrorKind::InvalidUtf8,
+                self.read.byte_offset() - len + valid_up_to,
+            )),
+        };
+        self.fix_byte_offset(value)
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if !self.config.bool_as_int {
+            return self.deserialize_any(visitor);
+        }
 
-        char str string
+        let value = match self.parse_peek()? {
+            b'i' => {
+                self.parse_next()?;
 
-        struct enum identifier ignored_any
+                match self.parse_integer(false)? {
+                    Integer::Unsigned(0) | Integer::Signed(0) => visitor.visit_bool(false),
+                    Integer::Unsigned(1) => visitor.visit_bool(true),
+                    integer => Err(de::Error::invalid_value(
+                        integer.unexpected(),
+                        &"the integer 0 or 1",
+                    )),
+                }
+            }
+            _ => Err(self.unexpected_type_err(&visitor)?),
+        };
+        self.fix_byte_offset(value)
     }
 
     forward_deserialize_signed_integer!(deserialize_i8);
     forward_deserialize_signed_integer!(deserialize_i16);
     forward_deserialize_signed_integer!(deserialize_i32);
+    forward_deserialize_signed_integer!(deserialize_i128);
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        match self.parse_peek()? {
+        let value = match self.parse_peek()? {
             b'i' => {
                 self.parse_next()?;
 
-                let (is_positive, num) = self.parse_integer()?;
-                if is_positive {
-                    visitor.visit_u64(num)
+                let digits_start = if self.parse_peek()? == b'-' {
+                    self.read.byte_offset() + 1
                 } else {
-                    use core::convert::TryFrom;
-
-                    visitor.visit_i64(-i64::try_from(num).map_err(|_| {
-                        Error::new(ErrorKind::InvalidInteger, self.read.byte_offset())
-                    })?)
+                    self.read.byte_offset()
+                };
+                match self.parse_integer(false)? {
+                    Integer::Big(_) | Integer::Overflow => {
+                        Err(Error::new(ErrorKind::IntegerOverflow, digits_start))
+                    }
+                    integer => integer.visit(visitor),
                 }
             }
             _ => Err(self.unexpected_type_err(&visitor)?),
-        }
+        };
+        self.fix_byte_offset(value)
     }
 
     forward_deserialize_unsigned_integer!(deserialize_u8);
     forward_deserialize_unsigned_integer!(deserialize_u16);
     forward_deserialize_unsigned_integer!(deserialize_u32);
+    forward_deserialize_unsigned_integer!(deserialize_u128);
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -401,37 +1354,20 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         // as-is. The primary use case is to capture the `info` value in a
         // BitTorrent metainfo. The `info` value would be captured as-is without
         // parsing which allows the infohash to be generated according to the specification.
-        match self.parse_peek()? {
-            b'0'..=b'9' => {
-                self.buf.clear();
-                match self.read.parse_byte_str(&mut self.buf)? {
-                    Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
-                    Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
-                }
-            }
-            b'i' => {
-                self.buf.clear();
-                match self.read.parse_raw_integer(&mut self.buf)? {
-                    Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
-                    Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
-                }
-            }
-            b'l' => {
-                self.buf.clear();
-                match self.read.parse_raw_list(&mut self.buf)? {
-                    Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
-                    Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
-                }
-            }
-            b'd' => {
-                self.buf.clear();
-                match self.read.parse_raw_dict(&mut self.buf)? {
-                    Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
-                    Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
-                }
-            }
+        //
+        // A `RawValue` captures the raw bytes of a byte string as well.
+        let value = match self.parse_peek()? {
+            b'0'..=b'9' => match self.parse_byte_str()? {
+                Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+            },
+            b'i' | b'l' | b'd' => match self.parse_raw_value()? {
+                Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+            },
             _ => Err(self.unexpected_type_err(&visitor)?),
-        }
+        };
+        self.fix_byte_offset(value)
     }
 
     #[inline]
@@ -442,37 +1378,81 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         self.deserialize_bytes(visitor)
     }
 
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // The value is skipped without recursing into any nested values. It
+        // is still checked against the limits and in strict mode.
+        let start = self.read.byte_offset();
+        self.buf.clear();
+        let raw = match self.parse_peek()? {
+            b'0'..=b'9' => {
+                self.parse_byte_str()?;
+                let value = visitor.visit_unit();
+                return self.fix_byte_offset(value);
+            }
+            b'i' => self.read.parse_raw_integer(&mut self.buf)?,
+            b'l' => {
+                self.set_raw_limits();
+                self.read.parse_raw_list(&mut self.buf)?
+            }
+            b'd' => {
+                self.set_raw_limits();
+                self.read.parse_raw_dict(&mut self.buf)?
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ExpectedSomeValue,
+                    self.read.byte_offset(),
+                ))
+            }
+        };
+
+        check_raw(&raw, start, self.config)?;
+
+        let value = visitor.visit_unit();
+        self.fix_byte_offset(value)
+    }
+
     #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_some(self)
+        let value = visitor.visit_some(&mut *self);
+        self.fix_byte_offset(value)
     }
 
     #[inline]
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        let value = if name == RAW_VALUE_TOKEN {
+            match self.parse_raw_value()? {
+                Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+            }
+        } else if name == VALUE_TOKEN {
+            let keep_first = self.config.duplicate_keys == DuplicateKeys::KeepFirst;
+            let raw = self.parse_raw_value()?;
+            visitor.visit_map(EncodedValueDeserializer::new(&raw, keep_first))
+        } else {
+            visitor.visit_newtype_struct(&mut *self)
+        };
+        self.fix_byte_offset(value)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        match self.parse_peek()? {
-            b'l' => {
-                self.parse_next()?;
-                let ret = visitor.visit_seq(SeqAccess { de: self });
-                match (ret, self.on_end_seq()) {
-                    (Ok(ret), Ok(())) => Ok(ret),
-                    (Err(err), _) | (_, Err(err)) => Err(err),
-                }
-            }
+        let value = match self.parse_peek()? {
+            b'l' => self.visit_list(visitor),
             _ => Err(self.unexpected_type_err(&visitor)?),
-        }
+        };
+        self.fix_byte_offset(value)
     }
 
     #[inline]
@@ -500,17 +1480,62 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
     where
         V: de::Visitor<'de>,
     {
-        match self.parse_peek()? {
-            b'd' => {
-                self.parse_next()?;
-                let ret = visitor.visit_map(MapAccess { de: self });
-                match (ret, self.on_end_map()) {
-                    (Ok(ret), Ok(())) => Ok(ret),
-                    (Err(err), _) | (_, Err(err)) => Err(err),
-                }
-            }
+        let value = match self.parse_peek()? {
+            b'd' => self.visit_dict(visitor),
             _ => Err(self.unexpected_type_err(&visitor)?),
+        };
+        self.fix_byte_offset(value)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == SPANNED_TOKEN && fields == SPANNED_FIELDS {
+            let value = visitor.visit_map(SpannedAccess {
+                de: &mut *self,
+                next_field: 0,
+            });
+            return self.fix_byte_offset(value);
         }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let value = match self.parse_peek()? {
+            b'0'..=b'9' => {
+                let key = self.parse_byte_str()?;
+                let key_len = key.len();
+                let value = visitor.visit_enum(MapKey { key });
+                let key_start = self.read.byte_offset() - key_len;
+                value.map_err(|err| err.offset_by(key_start))
+            }
+            b'd' => {
+                self.enter_nested()?;
+                self.parse_next()?;
+                let ret = visitor.visit_enum(VariantAccess { de: &mut *self });
+                self.leave_nested();
+                match (ret, self.on_end_map()) {
+                    (Ok(ret), Ok(())) => Ok(ret),
+                    (Err(err), _) | (_, Err(err)) => Err(err),
+                }
+            }
+            _ => Err(self.unexpected_type_err(&visitor)?),
+        };
+        self.fix_byte_offset(value)
     }
 
     #[inline]
@@ -521,6 +1546,14 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
 
 struct SeqAccess<'a, R> {
     de: &'a mut Deserializer<R>,
+    /// The number of elements read
+    len: usize,
+}
+
+impl<'a, R> SeqAccess<'a, R> {
+    fn new(de: &'a mut Deserializer<R>) -> Self {
+        SeqAccess { de, len: 0 }
+    }
 }
 
 impl<'de, 'a, R: Read<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
@@ -532,57 +1565,320 @@ impl<'de, 'a, R: Read<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
     {
         match self.de.parse_peek()? {
             b'e' => Ok(None),
-            _ => Ok(Some(seed.deserialize(&mut *self.de)?)),
+            _ => {
+                if self.len == self.de.config.limits.max_len {
+                    return Err(Error::new(
+                        ErrorKind::ElementLimitExceeded,
+                        self.de.read.byte_offset(),
+                    ));
+                }
+                let index = self.len;
+                self.len += 1;
+                seed.deserialize(&mut *self.de)
+                    .map(Some)
+                    .map_err(|err| err.with_path_index(index))
+            }
         }
     }
 }
 
-struct MapAccess<'a, R> {
+struct MapAccess<'de, 'a, R> {
     de: &'a mut Deserializer<R>,
+    /// The previous key which is borrowed from the source if possible
+    last_key: Option<Cow<'de, [u8]>>,
+    /// The keys which have been read which are only tracked if repeated keys
+    /// are rejected or skipped
+    keys: BTreeSet<Vec<u8>>,
+    /// The number of entries read
+    len: usize,
+}
+
+impl<'de, 'a, R> MapAccess<'de, 'a, R> {
+    fn new(de: &'a mut Deserializer<R>) -> Self {
+        MapAccess {
+            de,
+            last_key: None,
+            keys: BTreeSet::new(),
+            len: 0,
+        }
+    }
 }
 
-impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
+impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'de, 'a, R> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
     where
         K: de::DeserializeSeed<'de>,
     {
-        match self.de.parse_peek()? {
-            b'0'..=b'9' => seed.deserialize(MapKey { de: &mut *self.de }).map(Some),
-            b'e' => Ok(None),
-            _ => Err(Error::new(
-                ErrorKind::KeyMustBeAByteStr,
-                self.de.read.byte_offset(),
-            )),
+        let config = self.de.config;
+
+        loop {
+            match self.de.parse_peek()? {
+                b'0'..=b'9' => {}
+                b'e' => return Ok(None),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::KeyMustBeAByteStr,
+                        self.de.read.byte_offset(),
+                    ))
+                }
+            }
+
+            let start = self.de.read.byte_offset();
+            if self.len == config.limits.max_len {
+                return Err(Error::new(ErrorKind::ElementLimitExceeded, start));
+            }
+            self.len += 1;
+
+            let key = self.de.parse_byte_str()?;
+
+            if config.strict {
+                if let Some(last_key) = &self.last_key {
+                    match key[..].cmp(&last_key[..]) {
+                        Ordering::Less => return Err(Error::new(ErrorKind::UnsortedKeys, start)),
+                        Ordering::Equal => {
+                            return Err(Error::new(
+                                ErrorKind::DuplicateKey(ByteString::from(key.to_vec())),
+                                start,
+                            ))
+                        }
+                        Ordering::Greater => {}
+                    }
+                }
+            }
+            self.last_key = Some(match key {
+                Ref::Source(key) => Cow::Borrowed(key),
+                Ref::Buffer(key) => {
+                    // The buffered key is overwritten when the value is read.
+                    let mut buf = match self.last_key.take() {
+                        Some(Cow::Owned(buf)) => buf,
+                        _ => Vec::new(),
+                    };
+                    buf.clear();
+                    buf.extend_from_slice(key);
+                    Cow::Owned(buf)
+                }
+            });
+
+            match config.duplicate_keys {
+                DuplicateKeys::Allow | DuplicateKeys::KeepLast => {}
+                DuplicateKeys::Error | DuplicateKeys::KeepFirst => {
+                    if !self.keys.contains(&key[..]) {
+                        self.keys.insert(key.to_vec());
+                    } else if config.duplicate_keys == DuplicateKeys::Error {
+                        return Err(Error::new(
+                            ErrorKind::DuplicateKey(ByteString::from(key.to_vec())),
+                            start,
+                        ));
+                    } else {
+                        let last_key = &self.last_key;
+                        let _: de::IgnoredAny = de::Deserialize::deserialize(&mut *self.de)
+                            .map_err(|err| with_last_key(err, last_key))?;
+                        continue;
+                    }
+                }
+            }
+
+            let key_len = key.len();
+            let key = seed.deserialize(MapKey { key });
+            let key_start = self.de.read.byte_offset() - key_len;
+            let key = key.map_err(|err| err.offset_by(key_start));
+            return self.de.fix_byte_offset(key).map(Some);
         }
     }
 
-    #[inline]
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
     where
         V: de::DeserializeSeed<'de>,
     {
+        let last_key = &self.last_key;
         seed.deserialize(&mut *self.de)
+            .map_err(|err| with_last_key(err, last_key))
+    }
+}
+
+/// Adds the key of the current dictionary entry to the error's path.
+fn with_last_key(err: Error, last_key: &Option<Cow<'_, [u8]>>) -> Error {
+    match last_key {
+        Some(key) => err.with_path_key(key),
+        None => err,
+    }
+}
+
+/// Deserializes the entries of a dictionary which was already read.
+struct ReplayMapAccess<'a, 'b> {
+    /// The raw encoded dictionary
+    raw: Ref<'a, 'b, [u8]>,
+    /// The byte offset of the dictionary in the original source
+    byte_offset: usize,
+    /// The ranges of the remaining entries to deserialize
+    entries: vec::IntoIter<Range<usize>>,
+    config: Config,
+    /// The remaining depth for the values
+    remaining_depth: usize,
+    /// The deserializer and the range of the key in `raw` for the current
+    /// entry
+    de: Option<(Deserializer<ReplayRead<'a, 'b>>, Range<usize>)>,
+}
+
+impl<'de, 'b> de::MapAccess<'de> for ReplayMapAccess<'de, 'b> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        let entry = match self.entries.next() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let bytes = match self.raw {
+            Ref::Source(raw) => Ref::Source(&raw[entry.clone()]),
+            Ref::Buffer(raw) => Ref::Buffer(&raw[entry.clone()]),
+        };
+
+        let mut de = Deserializer {
+            read: ReplayRead::new(bytes, self.byte_offset + entry.start),
+            buf: Vec::new(),
+            config: self.config,
+            remaining_depth: self.remaining_depth,
+        };
+        de.read.set_limits(self.config.limits);
+        let key = de.parse_byte_str()?;
+        let key_len = key.len();
+        let key = seed.deserialize(MapKey { key });
+        let key_start = de.read.byte_offset() - key_len;
+        let key = de.fix_byte_offset(key.map_err(|err| err.offset_by(key_start)))?;
+        let key_start = key_start - self.byte_offset;
+        self.de = Some((de, key_start..key_start + key_len));
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.de.take() {
+            Some((mut de, key)) => seed
+                .deserialize(&mut de)
+                .map_err(|err| err.with_path_key(&self.raw[key])),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
     }
 }
 
-struct MapKey<'a, R> {
+/// Presents a value with the byte offsets before and after it as a struct with
+/// the [`Spanned`][crate::Spanned] fields.
+struct SpannedAccess<'a, R> {
     de: &'a mut Deserializer<R>,
+    /// The index of the next field in `SPANNED_FIELDS`
+    next_field: usize,
+}
+
+impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for SpannedAccess<'a, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match SPANNED_FIELDS.get(self.next_field) {
+            Some(field) => {
+                self.next_field += 1;
+                seed.deserialize(BorrowedStrDeserializer::new(field))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match SPANNED_FIELDS[..self.next_field].last() {
+            Some(&VALUE_FIELD) => seed.deserialize(&mut *self.de),
+            Some(_) => seed.deserialize(UsizeDeserializer::new(self.de.byte_offset())),
+            None => Err(de::Error::custom(
+                "next_value_seed called before next_key_seed",
+            )),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(SPANNED_FIELDS.len() - self.next_field)
+    }
 }
 
-impl<'de, 'a, R> de::Deserializer<'de> for MapKey<'a, R>
+/// Parses a dictionary key which is an integer encoded as a decimal byte
+/// string.
+///
+/// Only the canonical representation is accepted, the same as the one which
+/// the serializer writes. Keys with a `+` sign, leading zeros, or a negative
+/// zero are rejected.
+pub(crate) fn parse_integer_key<T>(key: &[u8]) -> Option<T>
 where
-    R: Read<'de>,
+    T: core::str::FromStr,
 {
+    let digits = if key.first() == Some(&b'-') {
+        &key[1..]
+    } else {
+        key
+    };
+    match digits.first() {
+        None => return None,
+        Some(b'0') if digits.len() > 1 || digits.len() != key.len() => return None,
+        _ => {}
+    }
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    core::str::from_utf8(key).ok()?.parse().ok()
+}
+
+macro_rules! deserialize_integer_key {
+    ($method:ident => $visit:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            match parse_integer_key(&self.key) {
+                Some(num) => visitor.$visit(num),
+                None => Err(de::Error::invalid_value(
+                    Unexpected::Bytes(&self.key),
+                    &visitor,
+                )),
+            }
+        }
+    };
+}
+
+/// Deserializes a dictionary key or an enum variant.
+///
+/// Errors which are detected in the key have a byte offset relative to the
+/// start of the key.
+struct MapKey<'a, 'b> {
+    key: Ref<'a, 'b, [u8]>,
+}
+
+impl<'de, 'b> de::Deserializer<'de> for MapKey<'de, 'b> {
     type Error = Error;
 
-    #[inline]
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_any(visitor)
+        match self.key {
+            Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+        }
     }
 
     #[inline]
@@ -601,286 +1897,2460 @@ where
         visitor.visit_newtype_struct(self)
     }
 
-    forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 unit unit_struct seq tuple tuple_struct map
-        char str string bytes byte_buf enum struct identifier ignored_any
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match core::str::from_utf8(&self.key) {
+            Ok(s) => visitor.visit_str(s),
+            Err(_) => Err(de::Error::invalid_value(
+                Unexpected::Bytes(&self.key),
+                &visitor,
+            )),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::ByteString;
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.key {
+            Ref::Source(bytes) => match core::str::from_utf8(bytes) {
+                Ok(s) => visitor.visit_borrowed_str(s),
+                Err(err) => Err(Error::new(ErrorKind::InvalidUtf8, err.valid_up_to())),
+            },
+            Ref::Buffer(bytes) => match core::str::from_utf8(bytes) {
+                Ok(s) => visitor.visit_str(s),
+                Err(err) => Err(Error::new(ErrorKind::InvalidUtf8, err.valid_up_to())),
+            },
+        }
+    }
 
-    use super::*;
-    use serde_derive::Deserialize;
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
 
-    #[cfg(all(feature = "alloc", not(feature = "std")))]
-    use alloc::{collections::BTreeMap, string::String, vec};
-    #[cfg(feature = "std")]
-    use std::{collections::BTreeMap, string::String, vec};
+    deserialize_integer_key!(deserialize_i8 => visit_i8);
+    deserialize_integer_key!(deserialize_i16 => visit_i16);
+    deserialize_integer_key!(deserialize_i32 => visit_i32);
+    deserialize_integer_key!(deserialize_i64 => visit_i64);
+    deserialize_integer_key!(deserialize_i128 => visit_i128);
+    deserialize_integer_key!(deserialize_u8 => visit_u8);
+    deserialize_integer_key!(deserialize_u16 => visit_u16);
+    deserialize_integer_key!(deserialize_u32 => visit_u32);
+    deserialize_integer_key!(deserialize_u64 => visit_u64);
+    deserialize_integer_key!(deserialize_u128 => visit_u128);
 
-    #[test]
-    fn test_deserialize_str() -> Result<()> {
-        let s: &str = from_slice("4:spam".as_bytes())?;
-        assert_eq!(s, "spam");
-        Ok(())
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_enum(self)
     }
 
-    #[test]
-    fn test_deserialize_string() -> Result<()> {
-        let s: String = from_slice("4:spam".as_bytes())?;
-        assert_eq!(s, "spam");
-        Ok(())
+    forward_to_deserialize_any! {
+        bool f32 f64 unit unit_struct seq tuple tuple_struct map
+        bytes byte_buf struct identifier ignored_any
     }
+}
 
-    #[test]
-    fn test_deserialize_integer_1() -> Result<()> {
-        let input = "i3e";
-        let i: u64 = from_slice(input.as_bytes())?;
-        assert_eq!(i, 3);
-        Ok(())
-    }
+impl<'de, 'b> de::EnumAccess<'de> for MapKey<'de, 'b> {
+    type Error = Error;
+    type Variant = UnitVariant;
 
-    #[test]
-    fn test_deserialize_integer_2() -> Result<()> {
-        let input = "i-3e";
-        let i: i64 = from_slice(input.as_bytes())?;
-        assert_eq!(i, -3);
-        Ok(())
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, UnitVariant)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        Ok((seed.deserialize(self)?, UnitVariant))
     }
+}
 
-    #[test]
-    fn test_deserialize_integer_3() -> Result<()> {
-        let input = "i0e";
-        let i: u64 = from_slice(input.as_bytes())?;
-        assert_eq!(i, 0);
-        Ok(())
-    }
+/// The variant of an enum which is encoded as a byte string.
+struct UnitVariant;
 
-    #[test]
-    fn test_deserialize_integer_4() -> Result<()> {
-        let input = "i0e";
-        let i: i64 = from_slice(input.as_bytes())?;
-        assert_eq!(i, 0);
+impl<'de> de::VariantAccess<'de> for UnitVariant {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
         Ok(())
     }
 
-    #[test]
-    fn test_deserialize_illegal_unsigned_int() {
-        let input = "ie";
-        let result: Result<u64> = from_slice(input.as_bytes());
-        let error = result.unwrap_err();
-        match error.kind() {
-            ErrorKind::InvalidInteger => {}
-            _ => panic!(),
-        }
-        assert_eq!(error.byte_offset(), 1);
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
     }
 
-    #[test]
-    fn test_deserialize_illegal_signed_int() {
-        let input = "i-e";
-        let result: Result<i64> = from_slice(input.as_bytes());
-        let error = result.unwrap_err();
-        match error.kind() {
-            ErrorKind::InvalidInteger => {}
-            _ => panic!(),
-        }
-        assert_eq!(error.byte_offset(), 2);
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
     }
 
-    #[test]
-    fn test_deserialize_list() -> Result<()> {
-        let input = "l4:spam4:eggse";
-        let v: Vec<String> = from_slice(input.as_bytes())?;
-        assert_eq!(v, vec!["spam", "eggs"]);
-        Ok(())
+    fn struct_variant<V>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"struct variant",
+        ))
     }
+}
 
-    #[test]
-    fn test_deserialize_list_str() -> Result<()> {
-        let input = "l4:spam4:eggse";
-        let v: Vec<&str> = from_slice(input.as_bytes())?;
-        assert_eq!(v, vec!["spam", "eggs"]);
-        Ok(())
-    }
+/// Deserializes a variant which is encoded as a dictionary with the variant
+/// name as the single key.
+struct VariantAccess<'a, R> {
+    de: &'a mut Deserializer<R>,
+}
 
-    #[test]
-    fn test_deserialize_list_as_tuple() -> Result<()> {
-        let input = "li123e4:eggse";
-        let v: (i64, &str) = from_slice(input.as_bytes())?;
-        assert_eq!(v, (123, "eggs"));
-        Ok(())
+impl<'de, 'a, R: Read<'de> + 'a> de::EnumAccess<'de> for VariantAccess<'a, R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.de.parse_peek()? {
+            b'0'..=b'9' => {}
+            b'e' => {
+                return Err(de::Error::invalid_length(
+                    0,
+                    &"a dictionary with a single key",
+                ))
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::KeyMustBeAByteStr,
+                    self.de.read.byte_offset(),
+                ))
+            }
+        }
+
+        let start = self.de.read.byte_offset();
+        if self.de.config.limits.max_len == 0 {
+            return Err(Error::new(ErrorKind::ElementLimitExceeded, start));
+        }
+
+        let key = self.de.parse_byte_str()?;
+        let key_len = key.len();
+        let variant = seed.deserialize(MapKey { key });
+        let key_start = self.de.read.byte_offset() - key_len;
+        let variant = variant.map_err(|err| err.offset_by(key_start));
+        let variant = self.de.fix_byte_offset(variant)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a, R: Read<'de> + 'a> de::VariantAccess<'de> for VariantAccess<'a, R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+/// Returns the ranges of the keys and the entries in a raw encoded dictionary.
+///
+/// The key range is the contents of the key byte string. The entry range
+/// includes both the encoded key and the encoded value. In strict mode, the
+/// keys must be sorted and unique.
+#[allow(clippy::type_complexity)]
+fn dict_entries(
+    bytes: &[u8],
+    start: usize,
+    config: Config,
+) -> Result<Vec<(Range<usize>, Range<usize>)>> {
+    let mut entries: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+    let mut idx = 1;
+
+    while bytes.get(idx) != Some(&b'e') {
+        // The reader may not enforce the limits.
+        if entries.len() == config.limits.max_len {
+            return Err(Error::new(ErrorKind::ElementLimitExceeded, start + idx));
+        }
+
+        let (contents_start, key_end) = byte_str_bounds(bytes, idx, start)?;
+        if config.strict {
+            if let Some((last_key, _)) = entries.last() {
+                match bytes[contents_start..key_end].cmp(&bytes[last_key.clone()]) {
+                    Ordering::Less => {
+                        return Err(Error::new(ErrorKind::UnsortedKeys, start + idx));
+                    }
+                    Ordering::Equal => {
+                        return Err(Error::new(
+                            ErrorKind::DuplicateKey(ByteString::from(
+                                bytes[contents_start..key_end].to_vec(),
+                            )),
+                            start + idx,
+                        ));
+                    }
+                    Ordering::Greater => {}
+                }
+            }
+        }
+
+        let end = raw_value_end(bytes, key_end, start)?;
+        entries.push((contents_start..key_end, idx..end));
+        idx = end;
+    }
+
+    Ok(entries)
+}
+
+/// Returns the index after the value which starts at `idx` in a raw encoding.
+///
+/// The bytes are expected to be returned by one of the `parse_raw_*` methods in
+/// [`Read`], so only the structure of the value is walked.
+fn raw_value_end(bytes: &[u8], mut idx: usize, start: usize) -> Result<usize> {
+    let mut depth: usize = 0;
+
+    loop {
+        match bytes.get(idx) {
+            Some(b'0'..=b'9') => {
+                let (_, end) = byte_str_bounds(bytes, idx, start)?;
+                idx = end;
+            }
+            Some(b'i') => match bytes[idx..].iter().position(|b| *b == b'e') {
+                Some(len) => idx += len + 1,
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::EofWhileParsingValue,
+                        start + bytes.len(),
+                    ))
+                }
+            },
+            Some(b'l') | Some(b'd') => {
+                depth += 1;
+                idx += 1;
+                continue;
+            }
+            Some(b'e') if depth > 0 => {
+                depth -= 1;
+                idx += 1;
+            }
+            Some(_) => return Err(Error::new(ErrorKind::ExpectedSomeValue, start + idx)),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::EofWhileParsingValue,
+                    start + bytes.len(),
+                ))
+            }
+        }
+
+        if depth == 0 {
+            return Ok(idx);
+        }
+    }
+}
+
+/// Verifies that the raw encoding of a value is canonical.
+///
+/// The bytes are expected to be a single value as returned by one of the
+/// `parse_raw_*` methods in [`Read`]. The `start` parameter is the byte offset
+/// of the value in the original source and is used for error reporting.
+fn check_canonical(bytes: &[u8], start: usize) -> Result<()> {
+    enum Container<'a> {
+        List,
+        Dict {
+            last_key: Option<&'a [u8]>,
+            is_key_next: bool,
+        },
+    }
+
+    let mut stack: Vec<Container<'_>> = Vec::new();
+    let mut idx = 0;
+
+    loop {
+        let is_key = match stack.last() {
+            Some(Container::Dict { is_key_next, .. }) => *is_key_next,
+            _ => false,
+        };
+
+        match bytes.get(idx) {
+            Some(b'e') if !stack.is_empty() => {
+                if let Some(Container::Dict {
+                    is_key_next: false, ..
+                }) = stack.last()
+                {
+                    return Err(Error::new(ErrorKind::InvalidDict, start + idx));
+                }
+                stack.pop();
+                idx += 1;
+            }
+            Some(b'0'..=b'9') => {
+                let (contents_start, end) = check_canonical_byte_str(bytes, idx, start)?;
+                if is_key {
+                    let key = &bytes[contents_start..end];
+                    if let Some(Container::Dict { last_key, .. }) = stack.last_mut() {
+                        if let Some(last_key) = last_key {
+                            match key.cmp(last_key) {
+                                Ordering::Less => {
+                                    return Err(Error::new(ErrorKind::UnsortedKeys, start + idx));
+                                }
+                                Ordering::Equal => {
+                                    return Err(Error::new(
+                                        ErrorKind::DuplicateKey(ByteString::from(key.to_vec())),
+                                        start + idx,
+                                    ));
+                                }
+                                Ordering::Greater => {}
+                            }
+                        }
+                        *last_key = Some(key);
+                    }
+                }
+                idx = end;
+            }
+            Some(_) if is_key => {
+                return Err(Error::new(ErrorKind::KeyMustBeAByteStr, start + idx));
+            }
+            Some(b'i') => {
+                idx = check_canonical_integer(bytes, idx, start)?;
+            }
+            Some(b'l') => {
+                stack.push(Container::List);
+                idx += 1;
+                continue;
+            }
+            Some(b'd') => {
+                stack.push(Container::Dict {
+                    last_key: None,
+                    is_key_next: true,
+                });
+                idx += 1;
+                continue;
+            }
+            Some(_) => return Err(Error::new(ErrorKind::ExpectedSomeValue, start + idx)),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::EofWhileParsingValue,
+                    start + bytes.len(),
+                ))
+            }
+        }
+
+        match stack.last_mut() {
+            None => return Ok(()),
+            Some(Container::List) => {}
+            Some(Container::Dict { is_key_next, .. }) => *is_key_next = !*is_key_next,
+        }
+    }
+}
+
+/// Verifies a raw encoding with the checks which apply to values read by a
+/// [`Deserializer`].
+fn check_raw(bytes: &[u8], start: usize, config: Config) -> Result<()> {
+    if config.strict {
+        check_canonical(bytes, start)?;
+    }
+    if config.duplicate_keys == DuplicateKeys::Error {
+        check_duplicate_keys(bytes, start)?;
+    }
+    Ok(())
+}
+
+/// Returns an error if a dictionary in a raw encoding has a repeated key.
+///
+/// The bytes are expected to be returned by one of the `parse_raw_*` methods in
+/// [`Read`], so only the structure of the value is walked.
+fn check_duplicate_keys(bytes: &[u8], start: usize) -> Result<()> {
+    enum Container<'a> {
+        List,
+        Dict {
+            keys: BTreeSet<&'a [u8]>,
+            is_key_next: bool,
+        },
+    }
+
+    let mut stack: Vec<Container<'_>> = Vec::new();
+    let mut idx = 0;
+
+    loop {
+        match bytes.get(idx) {
+            Some(b'e') if !stack.is_empty() => {
+                stack.pop();
+                idx += 1;
+            }
+            Some(b'0'..=b'9') => {
+                let (contents_start, end) = byte_str_bounds(bytes, idx, start)?;
+                if let Some(Container::Dict {
+                    keys,
+                    is_key_next: true,
+                }) = stack.last_mut()
+                {
+                    let key = &bytes[contents_start..end];
+                    if !keys.insert(key) {
+                        return Err(Error::new(
+                            ErrorKind::DuplicateKey(ByteString::from(key.to_vec())),
+                            start + idx,
+                        ));
+                    }
+                }
+                idx = end;
+            }
+            Some(b'i') => idx = raw_value_end(bytes, idx, start)?,
+            Some(b'l') => {
+                stack.push(Container::List);
+                idx += 1;
+                continue;
+            }
+            Some(b'd') => {
+                stack.push(Container::Dict {
+                    keys: BTreeSet::new(),
+                    is_key_next: true,
+                });
+                idx += 1;
+                continue;
+            }
+            Some(_) => return Err(Error::new(ErrorKind::ExpectedSomeValue, start + idx)),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::EofWhileParsingValue,
+                    start + bytes.len(),
+                ))
+            }
+        }
+
+        match stack.last_mut() {
+            None => return Ok(()),
+            Some(Container::List) => {}
+            Some(Container::Dict { is_key_next, .. }) => *is_key_next = !*is_key_next,
+        }
+    }
+}
+
+/// Verifies the canonical encoding of the integer at `idx` and returns the
+/// index after the integer.
+fn check_canonical_integer(bytes: &[u8], idx: usize, start: usize) -> Result<usize> {
+    let mut pos = idx + 1;
+    let is_negative = bytes.get(pos) == Some(&b'-');
+    if is_negative {
+        pos += 1;
+    }
+
+    let digits_start = pos;
+    while let Some(b'0'..=b'9') = bytes.get(pos) {
+        pos += 1;
+    }
+
+    match bytes.get(pos) {
+        Some(b'e') if pos != digits_start => {}
+        Some(&b) => return Err(Error::new(ErrorKind::UnexpectedByte(b), start + pos)),
+        None => {
+            return Err(Error::new(
+                ErrorKind::EofWhileParsingValue,
+                start + bytes.len(),
+            ))
+        }
+    }
+
+    if bytes[digits_start] == b'0' {
+        if pos - digits_start > 1 {
+            return Err(Error::new(ErrorKind::LeadingZero, start + digits_start));
+        }
+        if is_negative {
+            return Err(Error::new(ErrorKind::NegativeZero, start + digits_start));
+        }
+    }
+
+    Ok(pos + 1)
+}
+
+/// Verifies the canonical encoding of the byte string at `idx` and returns the
+/// index of the contents and the index after the byte string.
+fn check_canonical_byte_str(bytes: &[u8], idx: usize, start: usize) -> Result<(usize, usize)> {
+    let (contents_start, end) = byte_str_bounds(bytes, idx, start)?;
+    if bytes[idx] == b'0' && contents_start - idx > 2 {
+        return Err(Error::new(ErrorKind::NonCanonicalByteStrLen, start + idx));
+    }
+    Ok((contents_start, end))
+}
+
+/// Returns the index of the contents and the index after the byte string at
+/// `idx`.
+fn byte_str_bounds(bytes: &[u8], idx: usize, start: usize) -> Result<(usize, usize)> {
+    let mut pos = idx;
+    let mut len: usize = 0;
+    loop {
+        match bytes.get(pos) {
+            Some(b':') if pos != idx => break,
+            Some(n @ b'0'..=b'9') => {
+                len = len
+                    .checked_mul(10)
+                    .and_then(|len| len.checked_add(usize::from(n - b'0')))
+                    .ok_or_else(|| Error::new(ErrorKind::IntegerOverflow, start + pos))?;
+            }
+            Some(&b) => return Err(Error::new(ErrorKind::UnexpectedByte(b), start + pos)),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::EofWhileParsingValue,
+                    start + bytes.len(),
+                ))
+            }
+        }
+        pos += 1;
+    }
+
+    let contents_start = pos + 1;
+    let end = contents_start + len;
+    if end > bytes.len() {
+        return Err(Error::new(
+            ErrorKind::EofWhileParsingValue,
+            start + bytes.len(),
+        ));
+    }
+
+    Ok((contents_start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ByteString;
+
+    use super::*;
+    use crate::test_util::PathInfo;
+    use crate::{PathSegment, Value};
+    use serde::Deserialize as _;
+    use serde_derive::Deserialize;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{collections::BTreeMap, format, string::String, vec};
+    #[cfg(feature = "std")]
+    use std::{collections::BTreeMap, string::String, vec};
+
+    #[cfg(all(feature = "std", feature = "futures-io"))]
+    use crate::test_util::noop_waker;
+    #[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+    use crate::test_util::{block_on, ChunkedReader};
+
+    #[test]
+    fn test_deserialize_str() -> Result<()> {
+        let s: &str = from_slice("4:spam".as_bytes())?;
+        assert_eq!(s, "spam");
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_string() -> Result<()> {
+        let s: String = from_slice("4:spam".as_bytes())?;
+        assert_eq!(s, "spam");
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_1() -> Result<()> {
+        let input = "i3e";
+        let i: u64 = from_slice(input.as_bytes())?;
+        assert_eq!(i, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_2() -> Result<()> {
+        let input = "i-3e";
+        let i: i64 = from_slice(input.as_bytes())?;
+        assert_eq!(i, -3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_3() -> Result<()> {
+        let input = "i0e";
+        let i: u64 = from_slice(input.as_bytes())?;
+        assert_eq!(i, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_4() -> Result<()> {
+        let input = "i0e";
+        let i: i64 = from_slice(input.as_bytes())?;
+        assert_eq!(i, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_128() -> Result<()> {
+        let i: u128 = from_slice(b"i340282366920938463463374607431768211455e")?;
+        assert_eq!(i, u128::max_value());
+        let i: i128 = from_slice(b"i-170141183460469231731687303715884105728e")?;
+        assert_eq!(i, i128::min_value());
+        let i: i64 = from_slice(b"i-9223372036854775808e")?;
+        assert_eq!(i, i64::min_value());
+        let i: i128 = from_slice(b"i3e")?;
+        assert_eq!(i, 3);
+
+        assert!(from_slice::<u64>(b"i18446744073709551616e").is_err());
+        assert!(from_slice::<i128>(b"i170141183460469231731687303715884105728e").is_err());
+        let error = from_slice::<u128>(b"i340282366920938463463374607431768211456e").unwrap_err();
+        match error.kind() {
+            ErrorKind::IntegerOverflow => {}
+            _ => panic!(),
+        }
+        assert_eq!(error.byte_offset(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_big_integer() -> Result<()> {
+        use crate::value::Number;
+
+        for input in [
+            "18446744073709551616",
+            "-9223372036854775809",
+            "340282366920938463463374607431768211456",
+            "-170141183460469231731687303715884105729",
+            "100000000000000000000000000000000000000000000000000",
+        ]
+        .iter()
+        {
+            let encoded = format!("i{}e", input);
+            let v: Value = from_slice(encoded.as_bytes())?;
+            assert_eq!(v, Value::Int(Number::Big(String::from(*input))));
+            assert_eq!(crate::to_vec(&v)?, encoded.as_bytes());
+
+            let n: Number = from_slice(encoded.as_bytes())?;
+            assert_eq!(n, Number::Big(String::from(*input)));
+        }
+
+        let v: Value = from_slice(b"li-3ei00000000000000000000000000000000000000000001ee")?;
+        assert_eq!(
+            v,
+            Value::List(vec![
+                Value::Int(Number::Signed(-3)),
+                Value::Int(Number::Unsigned(1))
+            ])
+        );
+
+        let mut de = Deserializer::from_slice(b"i0340282366920938463463374607431768211456e");
+        de.set_strict(true);
+        match Value::deserialize(&mut de).unwrap_err().kind() {
+            ErrorKind::LeadingZero => {}
+            _ => panic!(),
+        }
+
+        // A dictionary with the same key as the internal representation is
+        // still a dictionary.
+        let v: Value = from_slice(b"d28:$bt_bencode::private::Number2:12e")?;
+        assert_eq!(v["$bt_bencode::private::Number"].as_str(), Some("12"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_bool() {
+        assert!(from_slice::<bool>(b"i1e").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_bool_as_int() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Info {
+            private: bool,
+            public: Option<bool>,
+        }
+
+        let mut de = Deserializer::from_slice(b"d7:privatei1e6:publici0ee");
+        de.set_bool_as_int(true);
+        assert_eq!(
+            Info::deserialize(&mut de)?,
+            Info {
+                private: true,
+                public: Some(false),
+            }
+        );
+
+        for input in [&b"i2e"[..], b"i-1e", b"1:1", b"le"].iter() {
+            let mut de = Deserializer::from_slice(input);
+            de.set_bool_as_int(true);
+            assert!(bool::deserialize(&mut de).is_err());
+        }
+
+        let mut de = Deserializer::from_slice(b"i-0e");
+        de.set_strict(true);
+        de.set_bool_as_int(true);
+        assert!(bool::deserialize(&mut de).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_illegal_unsigned_int() {
+        let input = "ie";
+        let result: Result<u64> = from_slice(input.as_bytes());
+        let error = result.unwrap_err();
+        match error.kind() {
+            ErrorKind::UnexpectedByte(b'e') => {}
+            _ => panic!(),
+        }
+        assert_eq!(error.byte_offset(), 1);
+    }
+
+    #[test]
+    fn test_deserialize_illegal_signed_int() {
+        let input = "i-e";
+        let result: Result<i64> = from_slice(input.as_bytes());
+        let error = result.unwrap_err();
+        match error.kind() {
+            ErrorKind::UnexpectedByte(b'e') => {}
+            _ => panic!(),
+        }
+        assert_eq!(error.byte_offset(), 2);
+    }
+
+    #[test]
+    fn test_deserialize_list() -> Result<()> {
+        let input = "l4:spam4:eggse";
+        let v: Vec<String> = from_slice(input.as_bytes())?;
+        assert_eq!(v, vec!["spam", "eggs"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_list_str() -> Result<()> {
+        let input = "l4:spam4:eggse";
+        let v: Vec<&str> = from_slice(input.as_bytes())?;
+        assert_eq!(v, vec!["spam", "eggs"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_list_as_tuple() -> Result<()> {
+        let input = "li123e4:eggse";
+        let v: (i64, &str) = from_slice(input.as_bytes())?;
+        assert_eq!(v, (123, "eggs"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_list_as_struct_tuple() -> Result<()> {
+        #[derive(Debug, serde_derive::Deserialize, PartialEq, Eq)]
+        struct S<'a>(i64, &'a str);
+
+        let input = "li123e4:eggse";
+        let v: S<'_> = from_slice(input.as_bytes())?;
+        assert_eq!(v, S(123, "eggs"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_dict_1() -> Result<()> {
+        let input = "d3:cow3:moo4:spam4:eggse";
+        let m: BTreeMap<String, String> = from_slice(input.as_bytes())?;
+        let mut expected = BTreeMap::new();
+        expected.insert(String::from("cow"), String::from("moo"));
+        expected.insert(String::from("spam"), String::from("eggs"));
+        assert_eq!(m, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_dict_1_str() -> Result<()> {
+        let input = "d3:cow3:moo4:spam4:eggse";
+        let m: BTreeMap<&str, &str> = from_slice(input.as_bytes())?;
+        let mut expected = BTreeMap::new();
+        expected.insert("cow", "moo");
+        expected.insert("spam", "eggs");
+        assert_eq!(m, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_dict_2() -> Result<()> {
+        let input = "d4:spaml1:a1:bee";
+        let m: BTreeMap<String, Vec<String>> = from_slice(input.as_bytes())?;
+        let mut expected = BTreeMap::new();
+        expected.insert(String::from("spam"), vec!["a".into(), "b".into()]);
+        assert_eq!(m, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_dict_2_str() -> Result<()> {
+        let input = "d4:spaml1:a1:bee";
+        let m: BTreeMap<&str, Vec<&str>> = from_slice(input.as_bytes())?;
+        let mut expected = BTreeMap::new();
+        expected.insert("spam", vec!["a", "b"]);
+        assert_eq!(m, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_dict_non_string_keys() -> Result<()> {
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+        enum Key {
+            Spam,
+            Eggs,
+        }
+
+        let m: BTreeMap<u32, &str> = from_slice(b"d1:11:a2:101:c1:91:be")?;
+        assert_eq!(
+            m.into_iter().collect::<Vec<_>>(),
+            vec![(1, "a"), (9, "b"), (10, "c")]
+        );
+
+        let m: BTreeMap<i8, u32> = from_slice(b"d2:-1i1e1:0i3e3:127i2ee")?;
+        assert_eq!(
+            m.into_iter().collect::<Vec<_>>(),
+            vec![(-1, 1), (0, 3), (127, 2)]
+        );
+
+        let m: BTreeMap<char, u32> = from_slice(b"d1:ai2e1:bi1ee")?;
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![('a', 2), ('b', 1)]);
+
+        let m: BTreeMap<Key, u32> = from_slice(b"d4:Eggsi2e4:Spami1ee")?;
+        assert_eq!(
+            m.into_iter().collect::<Vec<_>>(),
+            vec![(Key::Spam, 1), (Key::Eggs, 2)]
+        );
+
+        assert!(from_slice::<BTreeMap<u32, u32>>(b"d3:abci1ee").is_err());
+        assert!(from_slice::<BTreeMap<i8, u32>>(b"d3:128i1ee").is_err());
+        assert!(from_slice::<BTreeMap<u32, u32>>(b"d2:+1i1ee").is_err());
+        assert!(from_slice::<BTreeMap<u32, u32>>(b"d2:01i1ee").is_err());
+        assert!(from_slice::<BTreeMap<i32, u32>>(b"d2:-0i1ee").is_err());
+        assert!(from_slice::<BTreeMap<i32, u32>>(b"d3:-01i1ee").is_err());
+        assert!(from_slice::<BTreeMap<i32, u32>>(b"d1:-i1ee").is_err());
+        assert!(from_slice::<BTreeMap<u32, u32>>(b"d0:i1ee").is_err());
+        assert!(from_slice::<BTreeMap<char, u32>>(b"d2:abi1ee").is_err());
+        assert!(from_slice::<BTreeMap<Key, u32>>(b"d3:Hami1ee").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_struct() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S {
+            spam: Vec<String>,
+        }
+
+        let input = "d4:spaml1:a1:bee";
+        let s: S = from_slice(input.as_bytes())?;
+        let expected = S {
+            spam: vec!["a".into(), "b".into()],
+        };
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, serde_derive::Serialize)]
+    enum E {
+        Unit,
+        Newtype(u32),
+        Tuple(u32, String),
+        Struct { a: u32, b: String },
+    }
+
+    #[test]
+    fn test_deserialize_enum() -> Result<()> {
+        let values = [
+            (&b"4:Unit"[..], E::Unit),
+            (&b"d7:Newtypei1ee"[..], E::Newtype(1)),
+            (
+                &b"d5:Tupleli2e4:spamee"[..],
+                E::Tuple(2, String::from("spam")),
+            ),
+            (
+                &b"d6:Structd1:ai3e1:b4:eggsee"[..],
+                E::Struct {
+                    a: 3,
+                    b: String::from("eggs"),
+                },
+            ),
+        ];
+
+        for (input, expected) in values.iter() {
+            assert_eq!(from_slice::<E>(input)?, *expected);
+            assert_eq!(crate::to_vec(expected)?, *input);
+
+            let value: Value = from_slice(input)?;
+            assert_eq!(E::deserialize(&value)?, *expected);
+            assert_eq!(crate::from_value::<E>(value)?, *expected);
+            assert_eq!(crate::to_value(expected)?, from_slice::<Value>(input)?);
+        }
+
+        let list: Vec<E> = from_slice(b"l4:Unitd7:Newtypei1eee")?;
+        assert_eq!(list, vec![E::Unit, E::Newtype(1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_as_raw_slice() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S<'a>(&'a [u8]);
+
+        let input = "i-1234e";
+        let s: S<'_> = from_slice(input.as_bytes())?;
+        let expected = S(input.as_bytes());
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_list_as_raw_slice() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S<'a>(&'a [u8]);
+
+        let input = "l4:spam4:eggse";
+        let s: S<'_> = from_slice(input.as_bytes())?;
+        let expected = S(input.as_bytes());
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_map_value_as_raw_slice() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S<'a> {
+            spam: &'a [u8],
+        }
+
+        let input = "d4:spamd1:a1:bee";
+        let s: S<'_> = from_slice(input.as_bytes())?;
+        let expected = S { spam: b"d1:a1:be" };
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_map_as_raw_slice() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S<'a>(&'a [u8]);
+
+        let input = "d4:spamd1:a1:bee";
+        let s: S<'_> = from_slice(input.as_bytes())?;
+        let expected = S(input.as_bytes());
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_as_raw_bytes() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S(ByteString);
+
+        let input = "i-1234e";
+        let s: S = from_slice(input.as_bytes())?;
+        let expected = S(ByteString::from(input.as_bytes().to_vec()));
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_list_as_raw_bytes() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S(ByteString);
+
+        let input = "l4:spam4:eggse";
+        let s: S = from_slice(input.as_bytes())?;
+        let expected = S(ByteString::from(input.as_bytes().to_vec()));
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_map_value_as_raw_bytes() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S {
+            spam: ByteString,
+        }
+
+        let input = "d4:spamd1:a1:bee";
+        let s: S = from_slice(input.as_bytes())?;
+        let expected = S {
+            spam: ByteString::from(b"d1:a1:be".to_vec()),
+        };
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_map_as_raw_bytes() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S(ByteString);
+
+        let input = "d4:spamd1:a1:bee";
+        let s: S = from_slice(input.as_bytes())?;
+        let expected = S(ByteString::from(input.as_bytes().to_vec()));
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_enum_errors() {
+        assert_error_kind!(from_slice::<E>(b"i1e"), ErrorKind::InvalidType { .. }, 3);
+        assert_error_kind!(from_slice::<E>(b"4:Spam"), ErrorKind::Deserialize(_), 6);
+        assert_error_kind!(
+            from_slice::<E>(b"7:Newtype"),
+            ErrorKind::InvalidType { .. },
+            9
+        );
+        assert_error_kind!(
+            from_slice::<E>(b"d4:Uniti1ee"),
+            ErrorKind::InvalidType { .. },
+            10
+        );
+        assert_error_kind!(from_slice::<E>(b"de"), ErrorKind::Deserialize(_), 2);
+        assert_error_kind!(
+            from_slice::<E>(b"di1ei1ee"),
+            ErrorKind::KeyMustBeAByteStr,
+            1
+        );
+        assert_error_kind!(
+            from_slice::<E>(b"d7:Newtypei1e4:Unit"),
+            ErrorKind::InvalidDict,
+            13
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert(ByteString::from("Unit"), Value::from("spam"));
+        map.insert(ByteString::from("Newtype"), Value::from(1));
+        assert_error_kind!(
+            crate::from_value::<E>(Value::Dict(map)),
+            ErrorKind::InvalidValue { .. },
+            0
+        );
+    }
+
+    #[test]
+    fn test_deserialize_non_canonical_integer() -> Result<()> {
+        let i: u64 = from_slice(b"i03e")?;
+        assert_eq!(i, 3);
+        let i: i64 = from_slice(b"i-0e")?;
+        assert_eq!(i, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_strict_integer() -> Result<()> {
+        let i: u64 = from_slice_strict(b"i0e")?;
+        assert_eq!(i, 0);
+        let i: u64 = from_slice_strict(b"i30e")?;
+        assert_eq!(i, 30);
+        let i: i64 = from_slice_strict(b"i-3e")?;
+        assert_eq!(i, -3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_strict_integer_leading_zero() {
+        assert_error_kind!(from_slice_strict::<u64>(b"i03e"), ErrorKind::LeadingZero, 1);
+        assert_error_kind!(from_slice_strict::<u64>(b"i00e"), ErrorKind::LeadingZero, 1);
+        assert_error_kind!(
+            from_slice_strict::<i64>(b"i-03e"),
+            ErrorKind::LeadingZero,
+            2
+        );
+    }
+
+    #[test]
+    fn test_deserialize_strict_integer_negative_zero() {
+        assert_error_kind!(
+            from_slice_strict::<i64>(b"i-0e"),
+            ErrorKind::NegativeZero,
+            2
+        );
+    }
+
+    #[test]
+    fn test_deserialize_strict_byte_str() -> Result<()> {
+        let s: &str = from_slice_strict(b"0:")?;
+        assert_eq!(s, "");
+        let s: &str = from_slice_strict(b"10:0123456789")?;
+        assert_eq!(s, "0123456789");
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_strict_byte_str_leading_zero() {
+        assert_error_kind!(
+            from_slice_strict::<&str>(b"03:abc"),
+            ErrorKind::NonCanonicalByteStrLen,
+            0
+        );
+        assert_error_kind!(
+            from_slice_strict::<&str>(b"00:"),
+            ErrorKind::NonCanonicalByteStrLen,
+            0
+        );
+        assert_error_kind!(
+            from_slice_strict::<Vec<&str>>(b"l4:spam03:abce"),
+            ErrorKind::NonCanonicalByteStrLen,
+            7
+        );
+    }
+
+    #[test]
+    fn test_deserialize_strict_unsorted_keys() {
+        let input = b"d4:spam4:eggs3:cow3:mooe";
+        let m: BTreeMap<&str, &str> = from_slice(input).unwrap();
+        assert_eq!(m.len(), 2);
+
+        assert_error_kind!(
+            from_slice_strict::<BTreeMap<&str, &str>>(input),
+            ErrorKind::UnsortedKeys,
+            13
+        );
+    }
+
+    #[test]
+    fn test_deserialize_strict_repeated_keys() {
+        assert_error_kind!(
+            from_slice_strict::<BTreeMap<&str, &str>>(b"d3:cow3:moo3:cow3:mooe"),
+            ErrorKind::DuplicateKey(_),
+            11
+        );
+    }
+
+    #[test]
+    fn test_deserialize_strict_nested_unsorted_keys() {
+        #[derive(Debug, Deserialize)]
+        struct S {
+            #[allow(dead_code)]
+            spam: BTreeMap<String, String>,
+        }
+
+        assert_error_kind!(
+            from_slice_strict::<S>(b"d4:spamd1:b1:a1:a1:bee"),
+            ErrorKind::UnsortedKeys,
+            14
+        );
+    }
+
+    #[test]
+    fn test_deserialize_strict_raw_value() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S<'a> {
+            spam: &'a [u8],
+        }
+
+        let s: S<'_> = from_slice_strict(b"d4:spamd1:a1:b1:bli-1ei0e0:eee")?;
+        assert_eq!(s.spam, b"d1:a1:b1:bli-1ei0e0:ee");
+
+        assert_error_kind!(
+            from_slice_strict::<S<'_>>(b"d4:spamd1:b1:a1:a1:bee"),
+            ErrorKind::UnsortedKeys,
+            14
+        );
+        assert_error_kind!(
+            from_slice_strict::<S<'_>>(b"d4:spamli03eee"),
+            ErrorKind::LeadingZero,
+            9
+        );
+        assert_error_kind!(
+            from_slice_strict::<S<'_>>(b"d4:spami-0ee"),
+            ErrorKind::NegativeZero,
+            9
+        );
+        assert_error_kind!(
+            from_slice_strict::<S<'_>>(b"d4:spamd01:a1:bee"),
+            ErrorKind::NonCanonicalByteStrLen,
+            8
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_strict_raw_value_from_reader() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S {
+            spam: ByteString,
+        }
+
+        let s: S = from_reader_strict(&b"d4:spamd1:a1:b1:bi0eee"[..]).unwrap();
+        assert_eq!(s.spam, ByteString::from("d1:a1:b1:bi0ee"));
+
+        assert_error_kind!(
+            from_reader_strict::<_, S>(&b"d4:spamd1:b1:a1:a1:bee"[..]),
+            ErrorKind::UnsortedKeys,
+            14
+        );
+    }
+
+    fn from_slice_with_duplicate_keys<'a, T>(
+        s: &'a [u8],
+        duplicate_keys: DuplicateKeys,
+    ) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let mut de = Deserializer::from_slice(s);
+        de.set_duplicate_keys(duplicate_keys);
+        let value = T::deserialize(&mut de)?;
+        de.end()?;
+        Ok(value)
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Animal<'a> {
+        cow: &'a str,
+        spam: Option<BTreeMap<String, i64>>,
+    }
+
+    #[test]
+    fn test_deserialize_duplicate_keys_allow() -> Result<()> {
+        let input = b"d3:cow3:moo3:cow4:oinke";
+        let m: BTreeMap<&str, &str> = from_slice(input)?;
+        assert_eq!(m["cow"], "oink");
+
+        assert!(from_slice::<Animal<'_>>(input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_duplicate_keys_error() {
+        let input = b"d3:cow3:moo4:spamde3:cow4:oinke";
+        match from_slice_with_duplicate_keys::<BTreeMap<&str, Value>>(input, DuplicateKeys::Error) {
+            Ok(_) => panic!("expected error"),
+            Err(error) => {
+                match error.kind() {
+                    ErrorKind::DuplicateKey(key) => assert_eq!(key.as_slice(), b"cow"),
+                    _ => panic!("wrong error type: {:?}", error),
+                }
+                assert_eq!(error.byte_offset(), 19);
+            }
+        }
+
+        assert_error_kind!(
+            from_slice_with_duplicate_keys::<Animal<'_>>(input, DuplicateKeys::Error),
+            ErrorKind::DuplicateKey(_),
+            19
+        );
+        assert_error_kind!(
+            from_slice_with_duplicate_keys::<Animal<'_>>(
+                b"d3:cow3:moo4:spamd1:ai1e1:bi2e1:ai3eee",
+                DuplicateKeys::Error
+            ),
+            ErrorKind::DuplicateKey(_),
+            30
+        );
+    }
+
+    #[test]
+    fn test_deserialize_duplicate_keys_error_raw_value() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct S<'a> {
+            spam: &'a [u8],
+            #[serde(borrow)]
+            raw: Option<&'a crate::RawValue>,
+        }
+
+        let s: S<'_> = from_slice_with_duplicate_keys(
+            b"d4:spamd1:ad1:bi1ee1:bi2ee3:rawd1:ai1e1:bi2eee",
+            DuplicateKeys::Error,
+        )?;
+        assert_eq!(s.spam, b"d1:ad1:bi1ee1:bi2ee");
+        assert_eq!(
+            s.raw.map(crate::RawValue::as_bytes),
+            Some(&b"d1:ai1e1:bi2ee"[..])
+        );
+
+        assert_error_kind!(
+            from_slice_with_duplicate_keys::<S<'_>>(
+                b"d4:spamld1:ai1e1:ai2eee",
+                DuplicateKeys::Error
+            ),
+            ErrorKind::DuplicateKey(_),
+            15
+        );
+        assert_error_kind!(
+            from_slice_with_duplicate_keys::<S<'_>>(
+                b"d3:rawd1:ai1e1:bd1:ai1e1:ai2eee4:spam0:e",
+                DuplicateKeys::Error
+            ),
+            ErrorKind::DuplicateKey(_),
+            23
+        );
+        assert_error_kind!(
+            from_slice_with_duplicate_keys::<S<'_>>(
+                b"d4:spam0:5:extrad1:ai1e1:ai2eee",
+                DuplicateKeys::Error
+            ),
+            ErrorKind::DuplicateKey(_),
+            23
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_duplicate_keys_keep_first() -> Result<()> {
+        let input = b"d3:cow3:moo4:spamd1:ai1e1:bi2e1:ai3ee3:cow4:oink4:spamdee";
+        let a: Animal<'_> = from_slice_with_duplicate_keys(input, DuplicateKeys::KeepFirst)?;
+        let mut spam = BTreeMap::new();
+        spam.insert(String::from("a"), 1);
+        spam.insert(String::from("b"), 2);
+        assert_eq!(
+            a,
+            Animal {
+                cow: "moo",
+                spam: Some(spam),
+            }
+        );
+
+        let v: Value = from_slice_with_duplicate_keys(input, DuplicateKeys::KeepFirst)?;
+        assert_eq!(v["cow"].as_str(), Some("moo"));
+        assert_eq!(v["spam"]["a"].as_u64(), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_duplicate_keys_keep_last() -> Result<()> {
+        let input = b"d3:cow3:moo4:spamd1:ai1e1:bi2e1:ai3ee3:cow4:oinke";
+        let a: Animal<'_> = from_slice_with_duplicate_keys(input, DuplicateKeys::KeepLast)?;
+        let mut spam = BTreeMap::new();
+        spam.insert(String::from("a"), 3);
+        spam.insert(String::from("b"), 2);
+        assert_eq!(
+            a,
+            Animal {
+                cow: "oink",
+                spam: Some(spam),
+            }
+        );
+
+        let v: Value = from_slice_with_duplicate_keys(input, DuplicateKeys::KeepLast)?;
+        assert_eq!(v["cow"].as_str(), Some("oink"));
+        assert_eq!(v["spam"]["a"].as_u64(), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_duplicate_keys_keep_last_byte_offset() {
+        assert_error_kind!(
+            from_slice_with_duplicate_keys::<Animal<'_>>(
+                b"d3:cow3:moo4:spamd1:ai1e1:b1:xee",
+                DuplicateKeys::KeepLast
+            ),
+            ErrorKind::InvalidType { .. },
+            30
+        );
+        assert_error_kind!(
+            from_slice_with_duplicate_keys::<BTreeMap<&str, Value>>(
+                b"d3:cow3:moo4:spamd1:ai1e1:bi2xee",
+                DuplicateKeys::KeepLast
+            ),
+            ErrorKind::UnexpectedByte(b'x'),
+            29
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_duplicate_keys_keep_last_from_reader() -> Result<()> {
+        let input = &b"d3:cow3:moo4:spamd1:ai1e1:bi2e1:ai3ee3:cow4:oinke"[..];
+        let mut de = Deserializer::from_reader(input);
+        de.set_duplicate_keys(DuplicateKeys::KeepLast);
+        let v = Value::deserialize(&mut de)?;
+        de.end()?;
+        assert_eq!(v["cow"].as_str(), Some("oink"));
+        assert_eq!(v["spam"]["a"].as_u64(), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_duplicate_keys_strict() {
+        for duplicate_keys in &[
+            DuplicateKeys::Allow,
+            DuplicateKeys::Error,
+            DuplicateKeys::KeepFirst,
+            DuplicateKeys::KeepLast,
+        ] {
+            let mut de = Deserializer::from_slice(b"d3:cow3:moo3:cow4:oinke");
+            de.set_strict(true);
+            de.set_duplicate_keys(*duplicate_keys);
+            assert_error_kind!(
+                BTreeMap::<&str, &str>::deserialize(&mut de),
+                ErrorKind::DuplicateKey(_),
+                11
+            );
+        }
+    }
+
+    fn from_slice_with_limits<'a, T>(s: &'a [u8], limits: Limits) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let mut de = Deserializer::from_slice(s);
+        de.set_limits(limits);
+        let value = T::deserialize(&mut de)?;
+        de.end()?;
+        Ok(value)
+    }
+
+    #[cfg(feature = "std")]
+    fn from_reader_with_limits<T>(s: &[u8], limits: Limits) -> Result<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        let mut de = Deserializer::from_reader(s);
+        de.set_limits(limits);
+        let value = T::deserialize(&mut de)?;
+        de.end()?;
+        Ok(value)
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Raw<'a> {
+        #[allow(dead_code)]
+        spam: &'a [u8],
+    }
+
+    #[test]
+    fn test_deserialize_depth_limit() -> Result<()> {
+        let limits = Limits::default().max_depth(2);
+        let _: Value = from_slice_with_limits(b"ld1:ai1eee", limits)?;
+
+        assert_error_kind!(
+            from_slice_with_limits::<Value>(b"ld1:alee", limits),
+            ErrorKind::DepthLimitExceeded,
+            5
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Vec<Vec<Vec<u64>>>>(b"llli1eeee", limits),
+            ErrorKind::DepthLimitExceeded,
+            2
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Raw<'_>>(b"d4:spamldeee", limits),
+            ErrorKind::DepthLimitExceeded,
+            8
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Raw<'_>>(b"d4:spamllleee", limits),
+            ErrorKind::DepthLimitExceeded,
+            8
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_default_depth_limit() {
+        let mut input = vec![b'l'; 100_000];
+        input.extend(vec![b'e'; 100_000]);
+        assert_error_kind!(
+            from_slice::<Value>(&input),
+            ErrorKind::DepthLimitExceeded,
+            128
+        );
+
+        let mut input = b"d4:spam".to_vec();
+        input.extend(vec![b'l'; 100_000]);
+        input.extend(vec![b'e'; 100_001]);
+        assert_error_kind!(
+            from_slice::<Raw<'_>>(&input),
+            ErrorKind::DepthLimitExceeded,
+            134
+        );
+    }
+
+    #[test]
+    fn test_deserialize_value_deeply_nested() -> Result<()> {
+        let mut input = b"d4:spam".to_vec();
+        input.extend(vec![b'l'; 100_000]);
+        input.extend(b"i-00ei042e");
+        input.extend(vec![b'e'; 100_001]);
+        let value: Value = from_slice_with_limits(&input, Limits::default().max_depth(100_001))?;
+
+        let mut depth = 0;
+        let mut item = &value["spam"];
+        while let Some(list) = item.as_array() {
+            depth += 1;
+            assert_eq!(list.len(), if depth == 100_000 { 2 } else { 1 });
+            item = &list[list.len() - 1];
+        }
+        assert_eq!(depth, 100_000);
+        assert_eq!(item.as_u64(), Some(42));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_depth_limit_keep_last() {
+        let mut de = Deserializer::from_slice(b"d1:ad1:ad1:aleeee");
+        de.set_limits(Limits::default().max_depth(2));
+        de.set_duplicate_keys(DuplicateKeys::KeepLast);
+        assert_error_kind!(
+            Value::deserialize(&mut de),
+            ErrorKind::DepthLimitExceeded,
+            8
+        );
+    }
+
+    #[test]
+    fn test_deserialize_byte_str_len_limit() -> Result<()> {
+        let limits = Limits::default().max_byte_str_len(4);
+        let s: &str = from_slice_with_limits(b"4:spam", limits)?;
+        assert_eq!(s, "spam");
+
+        assert_error_kind!(
+            from_slice_with_limits::<&str>(b"5:spams", limits),
+            ErrorKind::ByteStrLenLimitExceeded,
+            0
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Value>(b"d4:spam9999999999:e", limits),
+            ErrorKind::ByteStrLenLimitExceeded,
+            7
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Raw<'_>>(b"d4:spaml5:spamsee", limits),
+            ErrorKind::ByteStrLenLimitExceeded,
+            8
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_integer_len_limit() -> Result<()> {
+        let limits = Limits::default().max_integer_len(3);
+        let i: i64 = from_slice_with_limits(b"i-123e", limits)?;
+        assert_eq!(i, -123);
+
+        assert_error_kind!(
+            from_slice_with_limits::<u64>(b"i1234e", limits),
+            ErrorKind::IntegerLenLimitExceeded,
+            4
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Value>(b"li0001ee", limits),
+            ErrorKind::IntegerLenLimitExceeded,
+            5
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Raw<'_>>(b"d4:spamli1234eee", limits),
+            ErrorKind::IntegerLenLimitExceeded,
+            12
+        );
+
+        #[cfg(feature = "std")]
+        {
+            let mut de = Deserializer::from_reader(&b"d4:spami1234ee"[..]);
+            de.set_limits(limits);
+            assert_error_kind!(
+                Raw::deserialize(&mut de),
+                ErrorKind::IntegerLenLimitExceeded,
+                11
+            );
+
+            let mut de = Deserializer::from_buf_reader(&b"d4:spami-1234ee"[..]);
+            de.set_limits(limits);
+            assert_error_kind!(
+                Raw::deserialize(&mut de),
+                ErrorKind::IntegerLenLimitExceeded,
+                12
+            );
+        }
+
+        let mut decoder = crate::PushDecoder::new();
+        decoder.set_limits(limits);
+        assert_error_kind!(
+            decoder.feed::<Value>(b"i-1234e"),
+            ErrorKind::IntegerLenLimitExceeded,
+            5
+        );
+
+        let mut input = b"i".to_vec();
+        input.extend(vec![b'9'; 64]);
+        input.push(b'e');
+        let v: Value = from_slice(&input)?;
+        assert_eq!(crate::to_vec(&v)?, input);
+
+        input.insert(1, b'9');
+        assert_error_kind!(
+            from_slice::<Value>(&input),
+            ErrorKind::IntegerLenLimitExceeded,
+            65
+        );
+        Ok(())
     }
 
     #[test]
-    fn test_deserialize_list_as_struct_tuple() -> Result<()> {
-        #[derive(Debug, serde_derive::Deserialize, PartialEq, Eq)]
-        struct S<'a>(i64, &'a str);
+    fn test_deserialize_integer_overflow_is_not_buffered() {
+        let mut input = b"i".to_vec();
+        input.extend(vec![b'9'; 10_000]);
+        input.push(b'e');
 
-        let input = "li123e4:eggse";
-        let v: S<'_> = from_slice(input.as_bytes())?;
-        assert_eq!(v, S(123, "eggs"));
-        Ok(())
+        let mut de = Deserializer::from_slice(&input);
+        de.set_limits(Limits::unlimited());
+        assert_error_kind!(u64::deserialize(&mut de), ErrorKind::IntegerOverflow, 1);
+        assert!(de.buf.capacity() < 64);
     }
 
     #[test]
-    fn test_deserialize_dict_1() -> Result<()> {
-        let input = "d3:cow3:moo4:spam4:eggse";
-        let m: BTreeMap<String, String> = from_slice(input.as_bytes())?;
-        let mut expected = BTreeMap::new();
-        expected.insert(String::from("cow"), String::from("moo"));
-        expected.insert(String::from("spam"), String::from("eggs"));
-        assert_eq!(m, expected);
+    fn test_deserialize_element_limit() -> Result<()> {
+        let limits = Limits::default().max_len(2);
+        let _: Value = from_slice_with_limits(b"li1ei2ee", limits)?;
+        let _: Value = from_slice_with_limits(b"d1:ai1e1:bi2ee", limits)?;
+
+        assert_error_kind!(
+            from_slice_with_limits::<Value>(b"li1ei2ei3ee", limits),
+            ErrorKind::ElementLimitExceeded,
+            7
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Value>(b"d1:ai1e1:bi2e1:ci3ee", limits),
+            ErrorKind::ElementLimitExceeded,
+            13
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Raw<'_>>(b"d4:spamli1ei2ei3eee", limits),
+            ErrorKind::ElementLimitExceeded,
+            14
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Raw<'_>>(b"d4:spamd1:ai1e1:bi2e1:ci3eee", limits),
+            ErrorKind::ElementLimitExceeded,
+            20
+        );
         Ok(())
     }
 
     #[test]
-    fn test_deserialize_dict_1_str() -> Result<()> {
-        let input = "d3:cow3:moo4:spam4:eggse";
-        let m: BTreeMap<&str, &str> = from_slice(input.as_bytes())?;
-        let mut expected = BTreeMap::new();
-        expected.insert("cow", "moo");
-        expected.insert("spam", "eggs");
-        assert_eq!(m, expected);
+    fn test_deserialize_byte_limit() -> Result<()> {
+        let limits = Limits::default().max_bytes(6);
+        let s: &str = from_slice_with_limits(b"4:spam", limits)?;
+        assert_eq!(s, "spam");
+
+        assert_error_kind!(
+            from_slice_with_limits::<&str>(b"5:spams", limits),
+            ErrorKind::ByteLimitExceeded,
+            6
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<Value>(b"li1ei2ee", limits),
+            ErrorKind::ByteLimitExceeded,
+            6
+        );
+        assert_error_kind!(
+            from_slice_with_limits::<&str>(b"4:spameggs", limits),
+            ErrorKind::ByteLimitExceeded,
+            6
+        );
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_deserialize_dict_2() -> Result<()> {
-        let input = "d4:spaml1:a1:bee";
-        let m: BTreeMap<String, Vec<String>> = from_slice(input.as_bytes())?;
-        let mut expected = BTreeMap::new();
-        expected.insert(String::from("spam"), vec!["a".into(), "b".into()]);
-        assert_eq!(m, expected);
+    fn test_deserialize_limits_from_reader() -> Result<()> {
+        let limits = Limits::default()
+            .max_depth(2)
+            .max_byte_str_len(4)
+            .max_len(2)
+            .max_bytes(32);
+        let v: Value = from_reader_with_limits(b"d4:spamli1ei2eee", limits)?;
+        assert_eq!(v["spam"][1].as_u64(), Some(2));
+
+        assert_error_kind!(
+            from_reader_with_limits::<Value>(b"d4:spamllleee", limits),
+            ErrorKind::DepthLimitExceeded,
+            8
+        );
+        assert_error_kind!(
+            from_reader_with_limits::<Value>(b"99999999999999:", limits),
+            ErrorKind::ByteStrLenLimitExceeded,
+            0
+        );
+        assert_error_kind!(
+            from_reader_with_limits::<Value>(b"li1ei2ei3ee", limits),
+            ErrorKind::ElementLimitExceeded,
+            7
+        );
+        assert_error_kind!(
+            from_reader_with_limits::<Value>(&[b'i'; 64], limits),
+            ErrorKind::UnexpectedByte(b'i'),
+            1
+        );
+        assert_error_kind!(
+            from_reader_with_limits::<Value>(b"l4:spam4:spam4:spam4:spam4:spame", limits),
+            ErrorKind::ElementLimitExceeded,
+            13
+        );
+        let v: Value = from_reader_with_limits(b"4:spam", Limits::default().max_bytes(6))?;
+        assert_eq!(v.as_str(), Some("spam"));
+        assert_error_kind!(
+            from_reader_with_limits::<Value>(b"4:spam", Limits::default().max_bytes(4)),
+            ErrorKind::ByteLimitExceeded,
+            4
+        );
+
+        #[derive(Debug, Deserialize)]
+        struct RawOwned {
+            #[allow(dead_code)]
+            spam: ByteString,
+        }
+
+        assert_error_kind!(
+            from_reader_with_limits::<RawOwned>(b"d4:spamli1ei2ei3eee", limits),
+            ErrorKind::ElementLimitExceeded,
+            14
+        );
+        assert_error_kind!(
+            from_reader_with_limits::<RawOwned>(b"d4:spamlllleeeee", limits),
+            ErrorKind::DepthLimitExceeded,
+            8
+        );
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_deserialize_dict_2_str() -> Result<()> {
-        let input = "d4:spaml1:a1:bee";
-        let m: BTreeMap<&str, Vec<&str>> = from_slice(input.as_bytes())?;
-        let mut expected = BTreeMap::new();
-        expected.insert("spam", vec!["a", "b"]);
-        assert_eq!(m, expected);
-        Ok(())
+    fn test_deserialize_huge_byte_str_len_from_reader() {
+        assert_error_kind!(
+            from_reader::<_, Value>(&b"99999999999999:spam"[..]),
+            ErrorKind::EofWhileParsingValue,
+            19
+        );
+    }
+
+    fn deeply_nested_dict(depth: usize) -> Vec<u8> {
+        let mut input = Vec::from(&b"d3:cow3:moo4:spam"[..]);
+        input.extend(core::iter::repeat(b'l').take(depth));
+        input.extend(core::iter::repeat(b'e').take(depth));
+        input.push(b'e');
+        input
     }
 
     #[test]
-    fn test_deserialize_struct() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S {
-            spam: Vec<String>,
+    fn test_deserialize_deeply_nested_raw() -> Result<()> {
+        let input = deeply_nested_dict(1_000_000);
+        let raw: Raw<'_> = from_slice_with_limits(&input, Limits::unlimited())?;
+        assert_eq!(raw.spam.len(), 2_000_000);
+
+        #[derive(Debug, Deserialize)]
+        struct Cow<'a> {
+            cow: &'a str,
         }
 
-        let input = "d4:spaml1:a1:bee";
-        let s: S = from_slice(input.as_bytes())?;
-        let expected = S {
-            spam: vec!["a".into(), "b".into()],
-        };
-        assert_eq!(s, expected);
+        let cow: Cow<'_> = from_slice_with_limits(&input, Limits::unlimited())?;
+        assert_eq!(cow.cow, "moo");
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_deserialize_integer_as_raw_slice() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S<'a>(&'a [u8]);
+    fn test_deserialize_deeply_nested_raw_from_reader() -> Result<()> {
+        let input = deeply_nested_dict(1_000_000);
 
-        let input = "i-1234e";
-        let s: S<'_> = from_slice(input.as_bytes())?;
-        let expected = S(input.as_bytes());
-        assert_eq!(s, expected);
+        #[derive(Debug, Deserialize)]
+        struct RawOwned {
+            spam: ByteString,
+        }
+
+        let raw: RawOwned = from_reader_with_limits(&input, Limits::unlimited())?;
+        assert_eq!(raw.spam.len(), 2_000_000);
+
+        #[derive(Debug, Deserialize)]
+        struct Cow {
+            cow: String,
+        }
+
+        let cow: Cow = from_reader_with_limits(&input, Limits::unlimited())?;
+        assert_eq!(cow.cow, "moo");
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_deserialize_list_as_raw_slice() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S<'a>(&'a [u8]);
+    fn test_deserialize_from_buf_reader() -> Result<()> {
+        let input: &[u8] = b"d3:cow3:moo4:spamd1:ai-12e1:bl4:eggs5:bacon0:eee";
+        let expected: Value = from_slice(input)?;
 
-        let input = "l4:spam4:eggse";
-        let s: S<'_> = from_slice(input.as_bytes())?;
-        let expected = S(input.as_bytes());
-        assert_eq!(s, expected);
+        for capacity in 1..=input.len() {
+            let v: Value = from_buf_reader(io::BufReader::with_capacity(capacity, input))?;
+            assert_eq!(v, expected);
+
+            #[derive(Debug, Deserialize)]
+            struct RawOwned {
+                spam: ByteString,
+            }
+
+            let raw: RawOwned = from_buf_reader(io::BufReader::with_capacity(capacity, input))?;
+            assert_eq!(raw.spam.as_slice(), &b"d1:ai-12e1:bl4:eggs5:bacon0:ee"[..]);
+        }
+
+        let v: Value = from_reader(input)?;
+        assert_eq!(v, expected);
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_deserialize_map_value_as_raw_slice() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S<'a> {
-            spam: &'a [u8],
+    fn test_deserialize_from_buf_reader_errors() -> Result<()> {
+        let reader = |input: &'static [u8]| io::BufReader::with_capacity(2, input);
+
+        assert_error_kind!(
+            from_buf_reader::<_, Value>(reader(b"l4:spa")),
+            ErrorKind::EofWhileParsingValue,
+            6
+        );
+        assert_error_kind!(
+            from_buf_reader::<_, Value>(reader(b"99999999999999:spam")),
+            ErrorKind::EofWhileParsingValue,
+            19
+        );
+        assert_error_kind!(
+            from_buf_reader::<_, Value>(reader(b"4:spameggs")),
+            ErrorKind::TrailingData,
+            6
+        );
+
+        let limits = Limits::default().max_byte_str_len(4).max_bytes(8);
+        let mut de = Deserializer::from_buf_reader(reader(b"l4:spame"));
+        de.set_limits(limits);
+        let v = Value::deserialize(&mut de)?;
+        de.end()?;
+        assert_eq!(v[0].as_str(), Some("spam"));
+
+        let mut de = Deserializer::from_buf_reader(reader(b"l5:bacone"));
+        de.set_limits(limits);
+        assert_error_kind!(
+            Value::deserialize(&mut de),
+            ErrorKind::ByteStrLenLimitExceeded,
+            1
+        );
+
+        let mut de = Deserializer::from_buf_reader(reader(b"l4:spam4:eggse"));
+        de.set_limits(limits);
+        assert_error_kind!(Value::deserialize(&mut de), ErrorKind::ByteLimitExceeded, 8);
+        Ok(())
+    }
+
+    /// An [`io::BufRead`] source which is interrupted before every call which
+    /// returns bytes.
+    #[cfg(feature = "std")]
+    struct InterruptedBufReader<'a> {
+        input: &'a [u8],
+        is_interrupted: bool,
+    }
+
+    #[cfg(feature = "std")]
+    impl io::Read for InterruptedBufReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = io::Read::read(&mut io::BufRead::fill_buf(self)?, buf)?;
+            io::BufRead::consume(self, len);
+            Ok(len)
         }
+    }
 
-        let input = "d4:spamd1:a1:bee";
-        let s: S<'_> = from_slice(input.as_bytes())?;
-        let expected = S { spam: b"d1:a1:be" };
-        assert_eq!(s, expected);
+    #[cfg(feature = "std")]
+    impl io::BufRead for InterruptedBufReader<'_> {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            self.is_interrupted = !self.is_interrupted;
+            if self.is_interrupted {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            Ok(&self.input[..self.input.len().min(1)])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.input = &self.input[amt..];
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_from_buf_reader_interrupted() -> Result<()> {
+        let input: &[u8] = b"d3:cow3:moo4:spamd1:ai-12e1:bl4:eggs5:bacon0:eee";
+        let reader = || InterruptedBufReader {
+            input,
+            is_interrupted: false,
+        };
+
+        let v: Value = from_buf_reader(reader())?;
+        assert_eq!(v, from_slice::<Value>(input)?);
+
+        let mut de = Deserializer::from_buf_reader(reader());
+        de.set_limits(Limits::default().max_bytes(12));
+        assert_error_kind!(
+            Value::deserialize(&mut de),
+            ErrorKind::ByteLimitExceeded,
+            12
+        );
         Ok(())
     }
 
+    /// An [`embedded_io::Read`] source which is interrupted before every byte
+    /// and which returns `error` instead of the end of the input.
+    #[cfg(feature = "embedded-io")]
+    struct EmbeddedReader<'a> {
+        input: &'a [u8],
+        is_interrupted: bool,
+        error: Option<embedded_io::ErrorKind>,
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl<'a> EmbeddedReader<'a> {
+        fn new(input: &'a [u8]) -> Self {
+            EmbeddedReader {
+                input,
+                is_interrupted: false,
+                error: None,
+            }
+        }
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl embedded_io::ErrorType for EmbeddedReader<'_> {
+        type Error = embedded_io::ErrorKind;
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl embedded_io::Read for EmbeddedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> core::result::Result<usize, Self::Error> {
+            self.is_interrupted = !self.is_interrupted;
+            if self.is_interrupted {
+                return Err(embedded_io::ErrorKind::Interrupted);
+            }
+            match self.input.split_first() {
+                Some((b, rest)) => {
+                    buf[0] = *b;
+                    self.input = rest;
+                    Ok(1)
+                }
+                None => self.error.map_or(Ok(0), Err),
+            }
+        }
+    }
+
+    #[cfg(feature = "embedded-io")]
     #[test]
-    fn test_deserialize_map_as_raw_slice() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S<'a>(&'a [u8]);
+    fn test_deserialize_from_embedded_reader() -> Result<()> {
+        let input: &[u8] = b"d3:cow3:moo4:spamd1:ai-12e1:bl4:eggs5:bacon0:eee";
+        let expected: Value = from_slice(input)?;
 
-        let input = "d4:spamd1:a1:bee";
-        let s: S<'_> = from_slice(input.as_bytes())?;
-        let expected = S(input.as_bytes());
-        assert_eq!(s, expected);
+        let v: Value = from_embedded_reader(EmbeddedReader::new(input))?;
+        assert_eq!(v, expected);
+        let v: Value = from_embedded_reader(input)?;
+        assert_eq!(v, expected);
+
+        #[derive(Debug, Deserialize)]
+        struct RawOwned {
+            spam: ByteString,
+        }
+
+        let raw: RawOwned = from_embedded_reader(EmbeddedReader::new(input))?;
+        assert_eq!(raw.spam.as_slice(), &b"d1:ai-12e1:bl4:eggs5:bacon0:ee"[..]);
         Ok(())
     }
 
+    #[cfg(feature = "embedded-io")]
     #[test]
-    fn test_deserialize_integer_as_raw_bytes() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S(ByteString);
+    fn test_deserialize_from_embedded_reader_errors() -> Result<()> {
+        assert_error_kind!(
+            from_embedded_reader::<_, Value>(EmbeddedReader::new(b"l4:spa")),
+            ErrorKind::EofWhileParsingValue,
+            6
+        );
+        assert_error_kind!(
+            from_embedded_reader::<_, Value>(EmbeddedReader::new(b"4:spameggs")),
+            ErrorKind::TrailingData,
+            6
+        );
 
-        let input = "i-1234e";
-        let s: S = from_slice(input.as_bytes())?;
-        let expected = S(ByteString::from(input.as_bytes().to_vec()));
-        assert_eq!(s, expected);
+        let mut reader = EmbeddedReader::new(b"l4:spa");
+        reader.error = Some(embedded_io::ErrorKind::BrokenPipe);
+        assert_error_kind!(
+            from_embedded_reader::<_, Value>(reader),
+            ErrorKind::EmbeddedIo(embedded_io::ErrorKind::BrokenPipe),
+            6
+        );
+
+        let limits = Limits::default().max_byte_str_len(4).max_bytes(8);
+        let mut de = Deserializer::from_embedded_reader(EmbeddedReader::new(b"l4:spame"));
+        de.set_limits(limits);
+        let v = Value::deserialize(&mut de)?;
+        de.end()?;
+        assert_eq!(v[0].as_str(), Some("spam"));
+
+        let mut de = Deserializer::from_embedded_reader(EmbeddedReader::new(b"l4:spam4:eggse"));
+        de.set_limits(limits);
+        assert_error_kind!(Value::deserialize(&mut de), ErrorKind::ByteLimitExceeded, 8);
         Ok(())
     }
 
+    #[cfg(all(feature = "std", feature = "tokio"))]
     #[test]
-    fn test_deserialize_list_as_raw_bytes() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S(ByteString);
+    fn test_deserialize_from_tokio_reader() -> Result<()> {
+        let input: &[u8] = b"d3:cow3:moo4:spamd1:ai-12e1:bl4:eggs5:bacon0:eee";
+        let expected: Value = from_slice(input)?;
 
-        let input = "l4:spam4:eggse";
-        let s: S = from_slice(input.as_bytes())?;
-        let expected = S(ByteString::from(input.as_bytes().to_vec()));
-        assert_eq!(s, expected);
+        for chunk_len in 1..=input.len() {
+            let v: Value = block_on(from_tokio_reader(ChunkedReader::new(input, chunk_len)))?;
+            assert_eq!(v, expected);
+        }
+
+        let v: Value = block_on(from_tokio_reader(input))?;
+        assert_eq!(v, expected);
+
+        assert_error_kind!(
+            block_on(from_tokio_reader::<_, Value>(&b"4:spameggs"[..])),
+            ErrorKind::TrailingData,
+            6
+        );
         Ok(())
     }
 
+    #[cfg(all(feature = "std", feature = "futures-io"))]
     #[test]
-    fn test_deserialize_map_value_as_raw_bytes() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S {
+    fn test_deserialize_from_futures_reader() -> Result<()> {
+        let input: &[u8] = b"d3:cow3:moo4:spamd1:ai-12e1:bl4:eggs5:bacon0:eee";
+        let expected: Value = from_slice(input)?;
+
+        #[derive(Debug, Deserialize)]
+        struct RawOwned {
             spam: ByteString,
         }
 
-        let input = "d4:spamd1:a1:bee";
-        let s: S = from_slice(input.as_bytes())?;
-        let expected = S {
-            spam: ByteString::from(b"d1:a1:be".to_vec()),
+        for chunk_len in 1..=input.len() {
+            let v: Value = block_on(from_futures_reader(ChunkedReader::new(input, chunk_len)))?;
+            assert_eq!(v, expected);
+
+            let raw: RawOwned =
+                block_on(from_futures_reader(ChunkedReader::new(input, chunk_len)))?;
+            assert_eq!(raw.spam.as_slice(), &b"d1:ai-12e1:bl4:eggs5:bacon0:ee"[..]);
+        }
+
+        let mut input = b"20000:".to_vec();
+        input.extend(core::iter::repeat(b'a').take(20_000));
+        let s: ByteString = block_on(from_futures_reader(&input[..]))?;
+        assert_eq!(s.len(), 20_000);
+
+        assert_error_kind!(
+            block_on(from_futures_reader::<_, Value>(&b""[..])),
+            ErrorKind::EofWhileParsingValue,
+            0
+        );
+        assert_error_kind!(
+            block_on(from_futures_reader::<_, Value>(&b"l4:spa"[..])),
+            ErrorKind::EofWhileParsingValue,
+            6
+        );
+        assert_error_kind!(
+            block_on(from_futures_reader::<_, Value>(&b"di1e4:spame"[..])),
+            ErrorKind::InvalidDict,
+            1
+        );
+        assert_error_kind!(
+            block_on(from_futures_reader::<_, Value>(ChunkedReader::new(
+                b"4:spameggs",
+                1
+            ))),
+            ErrorKind::TrailingData,
+            6
+        );
+        assert_error_kind!(
+            block_on(from_futures_reader::<_, Value>(&b"i-e"[..])),
+            ErrorKind::UnexpectedByte(b'e'),
+            2
+        );
+        Ok(())
+    }
+
+    #[cfg(all(feature = "std", feature = "futures-io"))]
+    #[test]
+    fn test_deserialize_from_futures_reader_cancelled() -> Result<()> {
+        use core::{future::Future as _, task::Context};
+
+        let input: &[u8] = b"d3:cow3:moo4:spamli1ei2eee";
+        let mut de = Deserializer::from_futures_reader(ChunkedReader::new(input, 4));
+
+        // The reader is pending before every read, so the future is dropped
+        // while a read is in progress.
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(Box::pin(de.fill_value())
+            .as_mut()
+            .poll(&mut cx)
+            .is_pending());
+
+        block_on(de.fill_value())?;
+        let v = Value::deserialize(&mut de)?;
+        de.end()?;
+        assert_eq!(v, from_slice::<Value>(input)?);
+        Ok(())
+    }
+
+    #[cfg(all(feature = "std", feature = "futures-io"))]
+    #[test]
+    fn test_deserialize_from_futures_reader_limits() -> Result<()> {
+        let limits = Limits::default()
+            .max_depth(2)
+            .max_byte_str_len(4)
+            .max_len(2)
+            .max_bytes(16);
+        let deserialize = |input: &'static [u8]| -> Result<Value> {
+            let mut de = Deserializer::from_futures_reader(ChunkedReader::new(input, 3));
+            de.set_limits(limits);
+            block_on(de.fill_value())?;
+            Value::deserialize(&mut de)
         };
-        assert_eq!(s, expected);
+
+        let v = deserialize(b"d4:spamli1ei2eee")?;
+        assert_eq!(v["spam"][1].as_u64(), Some(2));
+
+        assert_error_kind!(
+            deserialize(b"d4:spamllleee"),
+            ErrorKind::DepthLimitExceeded,
+            8
+        );
+        assert_error_kind!(
+            deserialize(b"99999999999999:"),
+            ErrorKind::ByteStrLenLimitExceeded,
+            0
+        );
+        assert_error_kind!(
+            deserialize(b"li1ei2ei3ee"),
+            ErrorKind::ElementLimitExceeded,
+            7
+        );
+        assert_error_kind!(
+            deserialize(b"l4:spam4:spam4:spame"),
+            ErrorKind::ElementLimitExceeded,
+            13
+        );
+        assert_error_kind!(
+            deserialize(b"l1:a1:b1:c1:d1:ee"),
+            ErrorKind::ElementLimitExceeded,
+            7
+        );
+        assert_error_kind!(
+            deserialize(b"li1234567890123ee"),
+            ErrorKind::ByteLimitExceeded,
+            16
+        );
         Ok(())
     }
 
+    #[cfg(all(feature = "std", feature = "futures-io"))]
     #[test]
-    fn test_deserialize_map_as_raw_bytes() -> Result<()> {
-        #[derive(Debug, PartialEq, Deserialize)]
-        struct S(ByteString);
+    fn test_deserialize_multiple_from_futures_reader() -> Result<()> {
+        let mut de = Deserializer::from_futures_reader(ChunkedReader::new(b"4:spami42el1:ae", 4));
 
-        let input = "d4:spamd1:a1:bee";
-        let s: S = from_slice(input.as_bytes())?;
-        let expected = S(ByteString::from(input.as_bytes().to_vec()));
-        assert_eq!(s, expected);
+        block_on(de.fill_value())?;
+        assert_eq!(String::deserialize(&mut de)?, "spam");
+        assert_eq!(de.byte_offset(), 6);
+
+        block_on(de.fill_value())?;
+        assert_eq!(u64::deserialize(&mut de)?, 42);
+        assert_eq!(de.byte_offset(), 10);
+
+        block_on(de.fill_value())?;
+        assert_eq!(
+            Vec::<String>::deserialize(&mut de)?,
+            vec![String::from("a")]
+        );
+        assert_eq!(de.byte_offset(), 15);
+
+        assert_error_kind!(
+            block_on(de.fill_value()),
+            ErrorKind::EofWhileParsingValue,
+            15
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_deserializer() -> Result<()> {
+        let input = b"4:spami42eli1ei2eed3:cow3:mooe";
+        let mut iter = Deserializer::from_slice(input).into_iter::<Value>();
+
+        assert_eq!(iter.next().unwrap()?.as_str(), Some("spam"));
+        assert_eq!(iter.value_offset(), 0);
+        assert_eq!(iter.byte_offset(), 6);
+        assert_eq!(iter.next().unwrap()?.as_u64(), Some(42));
+        assert_eq!(iter.value_offset(), 6);
+        assert_eq!(iter.next().unwrap()?.as_array().map(Vec::len), Some(2));
+        assert_eq!(iter.value_offset(), 10);
+        assert_eq!(iter.next().unwrap()?["cow"].as_str(), Some("moo"));
+        assert_eq!(iter.value_offset(), 18);
+        assert!(iter.next().is_none());
+        assert_eq!(iter.value_offset(), 30);
+        assert!(iter.next().is_none());
+
+        let values = Deserializer::from_slice(b"")
+            .into_iter::<Value>()
+            .collect::<Result<Vec<_>>>()?;
+        assert!(values.is_empty());
+
+        let values = Deserializer::from_slice(b"4:spam4:eggs")
+            .into_iter::<&str>()
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(values, ["spam", "eggs"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_deserializer_error() {
+        let mut iter = Deserializer::from_slice(b"i1ei-ei3e").into_iter::<i64>();
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert_error_kind!(iter.next().unwrap(), ErrorKind::UnexpectedByte(b'e'), 5);
+        assert_eq!(iter.value_offset(), 3);
+        assert!(iter.next().is_none());
+
+        let mut iter = Deserializer::from_slice(b"4:spam4:egg").into_iter::<&str>();
+        assert_eq!(iter.next().unwrap().unwrap(), "spam");
+        assert_error_kind!(iter.next().unwrap(), ErrorKind::EofWhileParsingValue, 11);
+        assert_eq!(iter.value_offset(), 6);
+        assert!(iter.next().is_none());
+
+        let mut de = Deserializer::from_slice(b"d1:ai1eed1:ai1e1:ai2ee");
+        de.set_duplicate_keys(DuplicateKeys::Error);
+        let mut iter = de.into_iter::<Value>();
+        assert!(iter.next().unwrap().is_ok());
+        assert_error_kind!(iter.next().unwrap(), ErrorKind::DuplicateKey(_), 15);
+        assert_eq!(iter.value_offset(), 8);
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct PathMetainfo {
+        #[allow(dead_code)]
+        info: PathInfo,
+    }
+
+    #[test]
+    fn test_deserialize_error_path() {
+        let input = b"d4:infod5:filesld6:lengthi1eed6:length4:spameeee";
+        let err = from_slice::<PathMetainfo>(input).unwrap_err();
+        assert_eq!(
+            err.path().segments().cloned().collect::<Vec<_>>(),
+            vec![
+                PathSegment::Key(ByteString::from(String::from("info"))),
+                PathSegment::Key(ByteString::from(String::from("files"))),
+                PathSegment::Index(1),
+                PathSegment::Key(ByteString::from(String::from("length"))),
+            ]
+        );
+        assert_eq!(format!("{}", err.path()), "info.files[1].length");
+
+        let mut de = Deserializer::from_slice(input);
+        de.set_duplicate_keys(DuplicateKeys::KeepLast);
+        let err = PathMetainfo::deserialize(&mut de).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "info.files[1].length");
+
+        let err =
+            from_slice::<PathMetainfo>(b"d4:infod5:filesld12:piece lengthi-1eeeee").unwrap_err();
+        assert_eq!(format!("{}", err.path()), "info.files[0][\"piece length\"]");
+
+        let err = from_slice::<PathMetainfo>(b"d4:infod5:filesli1eeee").unwrap_err();
+        assert_eq!(format!("{}", err.path()), "info.files[0]");
+
+        let err = from_slice::<Vec<u64>>(b"li1ei-2ee").unwrap_err();
+        assert_eq!(format!("{}", err.path()), "[1]");
+
+        let err = from_slice::<u64>(b"i-2e").unwrap_err();
+        assert!(err.path().is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_error_kinds() {
+        assert_error_kind!(
+            from_slice::<u64>(b"i12ae"),
+            ErrorKind::UnexpectedByte(b'a'),
+            3
+        );
+        assert_error_kind!(
+            from_slice::<Value>(b"i12ae"),
+            ErrorKind::UnexpectedByte(b'a'),
+            3
+        );
+        assert_error_kind!(
+            from_slice::<Value>(b"ie"),
+            ErrorKind::UnexpectedByte(b'e'),
+            1
+        );
+        assert_error_kind!(
+            from_slice::<&[u8]>(b"i1 e"),
+            ErrorKind::UnexpectedByte(b' '),
+            2
+        );
+        assert_error_kind!(
+            from_slice::<&[u8]>(b"li1e4x:spame"),
+            ErrorKind::UnexpectedByte(b'x'),
+            5
+        );
+        assert_error_kind!(
+            from_slice::<Value>(b"4x:spam"),
+            ErrorKind::UnexpectedByte(b'x'),
+            1
+        );
+        assert_error_kind!(
+            from_slice::<Value>(b"99999999999999999999999:"),
+            ErrorKind::IntegerOverflow,
+            20
+        );
+        assert_error_kind!(
+            from_slice::<i64>(b"i9223372036854775808e"),
+            ErrorKind::InvalidValue { .. },
+            21
+        );
+        assert_error_kind!(
+            from_slice::<i64>(b"i-170141183460469231731687303715884105729e"),
+            ErrorKind::IntegerOverflow,
+            2
+        );
+        assert_error_kind!(
+            from_slice_strict::<Value>(b"0x:"),
+            ErrorKind::UnexpectedByte(b'x'),
+            1
+        );
+        assert_error_kind!(
+            from_slice_strict::<Value>(b"i01e"),
+            ErrorKind::LeadingZero,
+            1
+        );
+
+        assert_error_kind!(from_slice::<&str>(b"4:sp\xffm"), ErrorKind::InvalidUtf8, 4);
+        assert_error_kind!(
+            from_slice::<Vec<String>>(b"l4:spam3:\xe2\x82e"),
+            ErrorKind::InvalidUtf8,
+            9
+        );
+        assert_error_kind!(
+            from_slice::<BTreeMap<String, u64>>(b"d2:\xffai1ee"),
+            ErrorKind::InvalidUtf8,
+            3
+        );
+        assert_error_kind!(
+            from_slice::<BTreeMap<String, u64>>(b"d2:a\xffi1ee"),
+            ErrorKind::InvalidUtf8,
+            4
+        );
+        assert_eq!(from_slice::<String>(b"4:spam").unwrap(), "spam");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_error_kinds_from_reader() {
+        assert_error_kind!(
+            from_reader::<_, Value>(&b"li1ei12ae"[..]),
+            ErrorKind::UnexpectedByte(b'a'),
+            7
+        );
+        assert_error_kind!(
+            from_reader::<_, Value>(&b"99999999999999999999999:"[..]),
+            ErrorKind::IntegerOverflow,
+            20
+        );
+        assert_error_kind!(
+            from_reader::<_, String>(&b"4:sp\xffm"[..]),
+            ErrorKind::InvalidUtf8,
+            4
+        );
+        assert_error_kind!(
+            from_reader::<_, BTreeMap<String, u64>>(&b"d2:a\xffi1ee"[..]),
+            ErrorKind::InvalidUtf8,
+            4
+        );
+    }
+
+    #[test]
+    fn test_deserialize_visitor_error_byte_offset() {
+        let input = b"d4:infod5:filesld6:length4:spameeee";
+        let err = from_slice::<PathMetainfo>(input).unwrap_err();
+        match err.kind() {
+            ErrorKind::InvalidType {
+                unexpected,
+                expected,
+            } => {
+                assert_eq!(unexpected, "byte array");
+                assert_eq!(expected, "u64");
+            }
+            _ => panic!("wrong error type: {:?}", err),
+        }
+        assert_eq!(err.byte_offset(), 31);
+        assert_eq!(
+            format!("{}", err),
+            "invalid type: byte array, expected u64 in info.files[0].length at byte offset 31"
+        );
+
+        let err = from_slice::<Vec<u8>>(b"li1ei300ee").unwrap_err();
+        match err.kind() {
+            ErrorKind::InvalidValue {
+                unexpected,
+                expected,
+            } => {
+                assert_eq!(unexpected, "integer `300`");
+                assert_eq!(expected, "u8");
+            }
+            _ => panic!("wrong error type: {:?}", err),
+        }
+        assert_eq!(err.byte_offset(), 9);
+
+        assert_error_kind!(
+            from_slice::<PathMetainfo>(b"d4:infod5:filesldeeee"),
+            ErrorKind::Deserialize(_),
+            18
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_error_path_from_reader() {
+        let input = &b"d4:infod5:filesld6:lengthi1eed6:lengthi-1eeeee"[..];
+        let err = from_reader::<_, PathMetainfo>(input).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "info.files[1].length");
+        assert!(err.to_string().contains(" in info.files[1].length"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_stream_deserializer_from_reader() -> Result<()> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Cow {
+            cow: String,
+        }
+
+        let input: &[u8] = b"d3:cow3:mooed3:cow4:moooe";
+        let mut iter = Deserializer::from_reader(input).into_iter::<Cow>();
+        assert_eq!(iter.next().unwrap()?.cow, "moo");
+        assert_eq!(iter.next().unwrap()?.cow, "mooo");
+        assert_eq!(iter.value_offset(), 12);
+        assert!(iter.next().is_none());
+
+        let values = Deserializer::from_buf_reader(io::BufReader::with_capacity(3, input))
+            .into_iter::<Cow>()
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(values.len(), 2);
         Ok(())
     }
 }
@@ -5,18 +5,20 @@ use serde::{de, ser};
 #[cfg(all(feature = "alloc", not(feature = "std")))]
 use alloc::{
     boxed::Box,
-    format,
     string::{String, ToString},
+    vec::Vec,
 };
 #[cfg(feature = "std")]
 use std::{
     boxed::Box,
-    error, format,
+    error,
     string::{String, ToString},
+    vec::Vec,
 };
 
+use crate::ByteString;
 use core::{
-    fmt::{self, Display},
+    fmt::{self, Display, Write as _},
     result,
 };
 
@@ -31,21 +33,78 @@ pub struct Error {
 impl Error {
     /// Constructs an error with the kind and the byte offset where the error
     /// was detected.
-    ///
-    /// A byte offset value of `0` indicates that the byte offset is either
-    /// unknown or not relevant.
     #[must_use]
     #[inline]
     pub fn new(kind: ErrorKind, byte_offset: usize) -> Self {
+        Self::with_byte_offset(kind, Some(byte_offset))
+    }
+
+    /// Constructs an error with the kind and an unknown byte offset.
+    #[must_use]
+    #[inline]
+    pub(crate) fn with_kind(kind: ErrorKind) -> Self {
+        Self::with_byte_offset(kind, None)
+    }
+
+    #[must_use]
+    #[inline]
+    fn with_byte_offset(kind: ErrorKind, byte_offset: Option<usize>) -> Self {
         Self {
-            inner: Box::new(ErrorImpl { kind, byte_offset }),
+            inner: Box::new(ErrorImpl {
+                kind,
+                byte_offset,
+                path: Path::default(),
+            }),
         }
     }
 
+    /// Moves the byte offset forward by `offset`.
+    ///
+    /// Used when the error was detected while reading a subslice of the
+    /// original source.
     #[must_use]
     #[inline]
-    pub(crate) fn with_kind(kind: ErrorKind) -> Self {
-        Self::new(kind, 0)
+    pub(crate) fn offset_by(mut self, offset: usize) -> Self {
+        if let Some(byte_offset) = &mut self.inner.byte_offset {
+            *byte_offset += offset;
+        }
+        self
+    }
+
+    /// Sets the byte offset if the byte offset is unknown.
+    ///
+    /// Used for errors which are constructed without access to the source,
+    /// such as errors from a [`Visitor`][serde::de::Visitor].
+    #[must_use]
+    #[inline]
+    pub(crate) fn fix_byte_offset(mut self, byte_offset: usize) -> Self {
+        if self.inner.byte_offset.is_none() {
+            self.inner.byte_offset = Some(byte_offset);
+        }
+        self
+    }
+
+    /// Adds the index of the list element which contained the error.
+    ///
+    /// Segments are added from the innermost value outwards.
+    #[must_use]
+    #[inline]
+    pub(crate) fn with_path_index(mut self, index: usize) -> Self {
+        self.inner.path.segments.push(PathSegment::Index(index));
+        self
+    }
+
+    /// Adds the key of the dictionary entry which contained the error.
+    ///
+    /// Segments are added from the innermost value outwards.
+    #[must_use]
+    #[inline]
+    pub(crate) fn with_path_key(mut self, key: &[u8]) -> Self {
+        self.inner
+            .path
+            .segments
+            .push(PathSegment::Key(ByteString::from(key.to_vec())));
+        self
     }
 
     /// The kind of error encountered
@@ -60,10 +119,64 @@ impl Error {
     /// Usually, the byte offset is after the problem has been detected. For
     /// instance, if an integer is not encoded correctly like `i12ae`, the byte
     /// offset may be after the `a` byte is read.
+    ///
+    /// The byte offset is `0` if it is unknown or not relevant.
     #[must_use]
     #[inline]
     pub fn byte_offset(&self) -> usize {
-        self.inner.byte_offset
+        self.inner.byte_offset.unwrap_or(0)
+    }
+
+    /// The location of the value which failed to serialize or deserialize.
+    ///
+    /// The path is made of the dictionary keys (or struct field names) and
+    /// list indexes from the outermost value to the value where the error was
+    /// detected (e.g. `info.files[12].length`). The path is empty if the error
+    /// was detected in the outermost value.
+    #[must_use]
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Returns an excerpt of the source around the error's byte offset.
+    ///
+    /// The excerpt is displayed as hex and ASCII lines of 16 bytes with a
+    /// marker below the byte at the byte offset. It is intended for logs and
+    /// command line output.
+    ///
+    /// ```
+    /// let input = b"d6:lengthi12ae4:name4:spame";
+    /// let err = bt_bencode::from_slice::<bt_bencode::Value>(input).unwrap_err();
+    /// assert_eq!(err.byte_offset(), 12);
+    ///
+    /// let snippet = err.snippet(input).to_string();
+    /// let lines = snippet.lines().collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     lines[0],
+    ///     "00000000  64 36 3a 6c 65 6e 67 74  68 69 31 32 61 65 34 3a  |d6:lengthi12ae4:|"
+    /// );
+    /// assert_eq!(lines[1].trim(), "^^                        ^");
+    /// ```
+    ///
+    /// The complete excerpt is:
+    ///
+    /// ```text
+    /// 00000000  64 36 3a 6c 65 6e 67 74  68 69 31 32 61 65 34 3a  |d6:lengthi12ae4:|
+    ///                                                ^^                        ^
+    /// 00000010  6e 61 6d 65 34 3a 73 70  61 6d 65                 |name4:spame|
+    /// ```
+    ///
+    /// The source must be the same bytes which were deserialized. If a value
+    /// was deserialized from a subslice, byte offsets are relative to the
+    /// start of the subslice.
+    #[must_use]
+    #[inline]
+    pub fn snippet<'a>(&self, source: &'a [u8]) -> Snippet<'a> {
+        Snippet {
+            source,
+            byte_offset: self.byte_offset(),
+        }
     }
 }
 
@@ -92,10 +205,17 @@ impl de::Error for Error {
     }
 
     fn invalid_type(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
-        Error::with_kind(ErrorKind::Deserialize(format!(
-            "unexpected type error. invalid_type={}, expected_type={}",
-            unexp, exp
-        )))
+        Error::with_kind(ErrorKind::InvalidType {
+            unexpected: unexp.to_string(),
+            expected: exp.to_string(),
+        })
+    }
+
+    fn invalid_value(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        Error::with_kind(ErrorKind::InvalidValue {
+            unexpected: unexp.to_string(),
+            expected: exp.to_string(),
+        })
     }
 }
 
@@ -105,6 +225,13 @@ impl ser::Error for Error {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::with_kind(ErrorKind::Io(error))
+    }
+}
+
 #[cfg(feature = "std")]
 impl From<Error> for std::io::Error {
     fn from(error: Error) -> Self {
@@ -117,16 +244,21 @@ impl From<Error> for std::io::Error {
 
 struct ErrorImpl {
     kind: ErrorKind,
-    byte_offset: usize,
+    /// The byte offset which is `None` if it is unknown or not relevant
+    byte_offset: Option<usize>,
+    path: Path,
 }
 
 impl Display for ErrorImpl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.byte_offset == 0 {
-            Display::fmt(&self.kind, f)
-        } else {
-            write!(f, "{} at byte offset {}", self.kind, self.byte_offset)
+        Display::fmt(&self.kind, f)?;
+        if !self.path.is_empty() {
+            write!(f, " in {}", self.path)?;
+        }
+        if let Some(byte_offset) = self.byte_offset {
+            write!(f, " at byte offset {}", byte_offset)?;
         }
+        Ok(())
     }
 }
 
@@ -135,26 +267,236 @@ impl fmt::Debug for ErrorImpl {
         f.debug_struct("Error")
             .field("kind", &self.kind)
             .field("byte_offset", &self.byte_offset)
+            .field("path", &self.path)
             .finish()
     }
 }
 
+/// The location of a value within nested lists and dictionaries.
+///
+/// A path is displayed with dictionary keys separated by `.` and list indexes
+/// in brackets like `info.files[12].length`. Keys which are empty or contain
+/// bytes other than ASCII letters, digits, `_`, or `-` are quoted in brackets
+/// like `info["piece length"]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Path {
+    /// The segments from the innermost value outwards
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Returns an iterator over the segments from the outermost value to the
+    /// innermost value.
+    #[inline]
+    pub fn segments(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &PathSegment> + ExactSizeIterator + '_ {
+        self.segments.iter().rev()
+    }
+
+    /// The number of segments.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns true if there are no segments.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, segment) in self.segments().enumerate() {
+            match segment {
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(key) if is_plain_key(key) => {
+                    if idx != 0 {
+                        f.write_str(".")?;
+                    }
+                    f.write_str(&String::from_utf8_lossy(key))?;
+                }
+                PathSegment::Key(key) => write!(f, "[{:?}]", String::from_utf8_lossy(key))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_plain_key(key: &[u8]) -> bool {
+    !key.is_empty()
+        && key
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'-')
+}
+
+/// An annotated hex and ASCII excerpt of a source.
+///
+/// Constructed with [`Error::snippet()`].
+#[derive(Clone, Copy, Debug)]
+pub struct Snippet<'a> {
+    source: &'a [u8],
+    byte_offset: usize,
+}
+
+impl Snippet<'_> {
+    /// The number of bytes in a line.
+    const BYTES_PER_LINE: usize = 16;
+
+    /// The number of lines before and after the line with the byte offset.
+    const CONTEXT_LINES: usize = 1;
+
+    fn fmt_line(&self, f: &mut fmt::Formatter<'_>, start: usize) -> fmt::Result {
+        let end = usize::min(start + Self::BYTES_PER_LINE, self.source.len());
+        let bytes = self.source.get(start..end).unwrap_or(&[]);
+
+        write!(f, "{:08x} ", start)?;
+        for pos in 0..Self::BYTES_PER_LINE {
+            if pos == Self::BYTES_PER_LINE / 2 {
+                f.write_str(" ")?;
+            }
+            match bytes.get(pos) {
+                Some(b) => write!(f, " {:02x}", b)?,
+                None => f.write_str("   ")?,
+            }
+        }
+
+        f.write_str("  |")?;
+        for &b in bytes {
+            if b.is_ascii_graphic() || b == b' ' {
+                f.write_char(char::from(b))?;
+            } else {
+                f.write_char('.')?;
+            }
+        }
+        f.write_str("|")
+    }
+
+    fn fmt_marker(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let col = self.byte_offset % Self::BYTES_PER_LINE;
+        // A line starts with the 8 digit offset and a space. Each byte in the
+        // hex column takes 3 characters with an extra space in the middle. The
+        // ASCII column starts after 2 spaces and a `|`.
+        let mut hex_col = 9 + col * 3 + 1;
+        if col >= Self::BYTES_PER_LINE / 2 {
+            hex_col += 1;
+        }
+        let ascii_col = 9 + Self::BYTES_PER_LINE * 3 + 1 + 3 + col;
+
+        write!(f, "{:width$}^^", "", width = hex_col)?;
+        write!(f, "{:width$}^", "", width = ascii_col - hex_col - 2)
+    }
+}
+
+impl Display for Snippet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = self.byte_offset / Self::BYTES_PER_LINE;
+        let last_line = if self.source.is_empty() {
+            0
+        } else {
+            (self.source.len() - 1) / Self::BYTES_PER_LINE
+        };
+
+        let first = line.saturating_sub(Self::CONTEXT_LINES);
+        let last = usize::max(usize::min(line + Self::CONTEXT_LINES, last_line), line);
+        for idx in first..=last {
+            if idx != first {
+                f.write_str("\n")?;
+            }
+            self.fmt_line(f, idx * Self::BYTES_PER_LINE)?;
+            if idx == line {
+                f.write_str("\n")?;
+                self.fmt_marker(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A list index or a dictionary key in a [`Path`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// The index of an element in a list.
+    Index(usize),
+    /// The key of an entry in a dictionary.
+    Key(ByteString),
+}
+
 /// All possible crate errors.
 #[allow(clippy::module_name_repetitions)]
 // Should the type be non_exhaustive? Probably if this crate was version 1.0+ but would need to bump MSRV to 1.40.0
 // #[non_exhaustive]
 pub enum ErrorKind {
+    /// The bytes did not fit in the remaining space of a
+    /// [`SliceWrite`][crate::write::SliceWrite].
+    ///
+    /// The byte offset is the number of bytes which were written.
+    BufferFull,
+    /// The total number of bytes read exceeded the byte budget in
+    /// [`Limits`][crate::Limits].
+    ///
+    /// The byte offset is the size of the byte budget.
+    ByteLimitExceeded,
+    /// A byte string length exceeded the maximum byte string length in
+    /// [`Limits`][crate::Limits].
+    ///
+    /// The byte offset is the position where the byte string starts.
+    ByteStrLenLimitExceeded,
     /// General deserialization error.
     ///
-    /// Usually the error is due to mismatching types (e.g. a struct was expecting an u64 but the data had a string).
+    /// Usually the error is a custom error from a
+    /// [`Deserialize`][serde::Deserialize] implementation (e.g. a missing
+    /// struct field).
     Deserialize(String),
+    /// Lists and dictionaries were nested deeper than the maximum depth in
+    /// [`Limits`][crate::Limits].
+    ///
+    /// The byte offset is the position where the list or dictionary which is
+    /// too deep starts.
+    DepthLimitExceeded,
+    /// A dictionary contained the same key more than once.
+    ///
+    /// Returned if repeated keys are rejected by the
+    /// [`DuplicateKeys`][crate::DuplicateKeys] policy or in strict mode. The
+    /// byte offset is the position where the repeated key starts.
+    DuplicateKey(ByteString),
+    /// A list or dictionary had more elements than the maximum number of
+    /// elements in [`Limits`][crate::Limits].
+    ///
+    /// The byte offset is the position where the first element beyond the
+    /// limit starts.
+    ElementLimitExceeded,
+    #[cfg(feature = "embedded-io")]
+    /// An I/O error from an [`embedded_io`] reader or writer.
+    EmbeddedIo(embedded_io::ErrorKind),
     /// End of file was encountered while parsing a value.
     EofWhileParsingValue,
     /// A value was expected but the deserializer did not find a valid bencoded value.
     ExpectedSomeValue,
-    /// When deserializing a byte string, the length was not a valid number.
-    InvalidByteStrLen,
-    /// When deserializing an integer, the integer contained non-number characters.
+    /// An integer had more digits than the maximum integer length in
+    /// [`Limits`][crate::Limits].
+    ///
+    /// The byte offset is the position of the first digit beyond the limit.
+    IntegerLenLimitExceeded,
+    /// An integer or a byte string length was too large.
+    ///
+    /// Returned if a byte string length does not fit in a `usize` or if an
+    /// integer which does not fit in an `i128` or a `u128` is deserialized
+    /// into a type other than a [`Value`][crate::Value] or a
+    /// [`Number`][crate::value::Number]. An integer which does not fit in a
+    /// smaller type like `i64` or `u8` returns [`ErrorKind::InvalidValue`]
+    /// instead. The byte offset is the position of the integer's first digit
+    /// or, for a byte string length, where the overflow was detected.
+    IntegerOverflow,
+    /// A string could not be parsed as an integer.
+    ///
+    /// Returned when a [`Number`][crate::value::Number] is parsed from a
+    /// string. Malformed integers in encoded data are reported as
+    /// [`ErrorKind::UnexpectedByte`] errors.
     InvalidInteger,
     /// When deserializing a dictionary, the dictionary was not encoded correctly.
     InvalidDict,
@@ -163,15 +505,59 @@ pub enum ErrorKind {
     #[cfg(feature = "std")]
     /// An I/O error.
     Io(std::io::Error),
+    /// A value had a different type than the expected type (e.g. a struct was
+    /// expecting an `u64` but the data had a byte string).
+    InvalidType {
+        /// The description of the value which was found.
+        unexpected: String,
+        /// The description of the expected type.
+        expected: String,
+    },
+    /// A byte string which was deserialized as a string was not valid UTF-8.
+    ///
+    /// The byte offset is the position of the first invalid byte.
+    InvalidUtf8,
+    /// A value had the expected type but an invalid value (e.g. an unknown
+    /// enum variant).
+    InvalidValue {
+        /// The description of the value which was found.
+        unexpected: String,
+        /// The description of the expected value.
+        expected: String,
+    },
     /// When deserializing, a dictionary key was found which was not a byte string.
     KeyMustBeAByteStr,
     /// A dictionary key was serialized but a call to serialize the key's value
     /// was never made after.
     KeyWithoutValue,
+    /// In strict mode, an integer was encoded with a leading zero (e.g. `i03e`).
+    ///
+    /// The byte offset is the position of the leading zero.
+    LeadingZero,
+    /// In strict mode, zero was encoded as a negative integer (e.g. `i-0e`).
+    ///
+    /// The byte offset is the position of the zero digit.
+    NegativeZero,
+    /// In strict mode, a byte string length was encoded with a leading zero
+    /// (e.g. `03:abc`).
+    ///
+    /// The byte offset is the position of the leading zero.
+    NonCanonicalByteStrLen,
     /// General serialization error.
     Serialize(String),
     /// Unparsed trailing data was detected
     TrailingData,
+    /// A byte was found which is not allowed in an integer or a byte string
+    /// length (e.g. the `a` in `i12ae` or a missing digit in `i-e`).
+    ///
+    /// The byte offset is the position of the byte.
+    UnexpectedByte(u8),
+    /// In strict mode, a dictionary key was not in sorted order.
+    ///
+    /// Keys must be sorted by their raw bytes. The byte offset is the position
+    /// where the out of order key starts. A repeated key is reported as a
+    /// [`ErrorKind::DuplicateKey`] error instead.
+    UnsortedKeys,
     /// An unsupported type was used during serialization.
     ///
     /// Bencode only supports integers, byte strings, lists, and dictionaries.
@@ -188,19 +574,36 @@ pub enum ErrorKind {
 impl error::Error for ErrorKind {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            ErrorKind::Deserialize(_)
+            ErrorKind::BufferFull
+            | ErrorKind::ByteLimitExceeded
+            | ErrorKind::ByteStrLenLimitExceeded
+            | ErrorKind::Deserialize(_)
+            | ErrorKind::DepthLimitExceeded
+            | ErrorKind::DuplicateKey(_)
+            | ErrorKind::ElementLimitExceeded
             | ErrorKind::EofWhileParsingValue
             | ErrorKind::ExpectedSomeValue
-            | ErrorKind::InvalidByteStrLen
+            | ErrorKind::IntegerLenLimitExceeded
+            | ErrorKind::IntegerOverflow
             | ErrorKind::InvalidInteger
             | ErrorKind::InvalidDict
             | ErrorKind::InvalidList
+            | ErrorKind::InvalidType { .. }
+            | ErrorKind::InvalidUtf8
+            | ErrorKind::InvalidValue { .. }
             | ErrorKind::KeyMustBeAByteStr
             | ErrorKind::KeyWithoutValue
+            | ErrorKind::LeadingZero
+            | ErrorKind::NegativeZero
+            | ErrorKind::NonCanonicalByteStrLen
             | ErrorKind::Serialize(_)
             | ErrorKind::TrailingData
+            | ErrorKind::UnexpectedByte(_)
+            | ErrorKind::UnsortedKeys
             | ErrorKind::UnsupportedType
             | ErrorKind::ValueWithoutKey => None,
+            #[cfg(feature = "embedded-io")]
+            ErrorKind::EmbeddedIo(_) => None,
             #[cfg(feature = "std")]
             ErrorKind::Io(source) => Some(source),
         }
@@ -210,18 +613,50 @@ impl error::Error for ErrorKind {
 impl Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            ErrorKind::BufferFull => f.write_str("buffer full"),
+            ErrorKind::ByteLimitExceeded => f.write_str("byte limit exceeded"),
+            ErrorKind::ByteStrLenLimitExceeded => f.write_str("byte string length limit exceeded"),
             ErrorKind::Deserialize(str) | ErrorKind::Serialize(str) => f.write_str(str),
+            ErrorKind::DepthLimitExceeded => f.write_str("depth limit exceeded"),
+            ErrorKind::DuplicateKey(key) => write!(
+                f,
+                "duplicate dictionary key \"{}\"",
+                String::from_utf8_lossy(key)
+            ),
+            ErrorKind::ElementLimitExceeded => f.write_str("element limit exceeded"),
             ErrorKind::EofWhileParsingValue => f.write_str("eof while parsing value"),
             ErrorKind::ExpectedSomeValue => f.write_str("expected some value"),
-            ErrorKind::InvalidByteStrLen => f.write_str("invalid byte string length"),
+            ErrorKind::IntegerLenLimitExceeded => f.write_str("integer length limit exceeded"),
+            ErrorKind::IntegerOverflow => f.write_str("integer overflow"),
             ErrorKind::InvalidInteger => f.write_str("invalid integer"),
             ErrorKind::InvalidDict => f.write_str("invalid dictionary"),
             ErrorKind::InvalidList => f.write_str("invalid list"),
+            ErrorKind::InvalidType {
+                unexpected,
+                expected,
+            } => write!(f, "invalid type: {}, expected {}", unexpected, expected),
+            ErrorKind::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            ErrorKind::InvalidValue {
+                unexpected,
+                expected,
+            } => write!(f, "invalid value: {}, expected {}", unexpected, expected),
             ErrorKind::KeyMustBeAByteStr => f.write_str("key must be a byte string"),
             ErrorKind::KeyWithoutValue => f.write_str("key without value"),
+            ErrorKind::LeadingZero => f.write_str("integer with leading zero"),
+            ErrorKind::NegativeZero => f.write_str("negative zero integer"),
+            ErrorKind::NonCanonicalByteStrLen => {
+                f.write_str("byte string length with leading zero")
+            }
             ErrorKind::TrailingData => f.write_str("trailing data error"),
+            ErrorKind::UnexpectedByte(b) if b.is_ascii_graphic() => {
+                write!(f, "unexpected byte 0x{:02x} ('{}')", b, char::from(*b))
+            }
+            ErrorKind::UnexpectedByte(b) => write!(f, "unexpected byte 0x{:02x}", b),
+            ErrorKind::UnsortedKeys => f.write_str("dictionary keys not in sorted order"),
             ErrorKind::UnsupportedType => f.write_str("unsupported type"),
             ErrorKind::ValueWithoutKey => f.write_str("value without key"),
+            #[cfg(feature = "embedded-io")]
+            ErrorKind::EmbeddedIo(kind) => write!(f, "embedded I/O error: {:?}", kind),
             #[cfg(feature = "std")]
             ErrorKind::Io(source) => Display::fmt(source, f),
         }
@@ -231,20 +666,128 @@ impl Display for ErrorKind {
 impl fmt::Debug for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            ErrorKind::BufferFull => f.write_str("buffer full"),
+            ErrorKind::ByteLimitExceeded => f.write_str("byte limit exceeded"),
+            ErrorKind::ByteStrLenLimitExceeded => f.write_str("byte string length limit exceeded"),
             ErrorKind::Deserialize(str) | ErrorKind::Serialize(str) => f.write_str(str),
+            ErrorKind::DepthLimitExceeded => f.write_str("depth limit exceeded"),
+            ErrorKind::DuplicateKey(key) => write!(
+                f,
+                "duplicate dictionary key \"{}\"",
+                String::from_utf8_lossy(key)
+            ),
+            ErrorKind::ElementLimitExceeded => f.write_str("element limit exceeded"),
             ErrorKind::EofWhileParsingValue => f.write_str("eof while parsing value"),
             ErrorKind::ExpectedSomeValue => f.write_str("expected some value"),
-            ErrorKind::InvalidByteStrLen => f.write_str("invalid byte string length"),
+            ErrorKind::IntegerLenLimitExceeded => f.write_str("integer length limit exceeded"),
+            ErrorKind::IntegerOverflow => f.write_str("integer overflow"),
             ErrorKind::InvalidInteger => f.write_str("invalid integer"),
             ErrorKind::InvalidDict => f.write_str("invalid dictionary"),
             ErrorKind::InvalidList => f.write_str("invalid list"),
+            ErrorKind::InvalidType {
+                unexpected,
+                expected,
+            } => write!(f, "invalid type: {}, expected {}", unexpected, expected),
+            ErrorKind::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            ErrorKind::InvalidValue {
+                unexpected,
+                expected,
+            } => write!(f, "invalid value: {}, expected {}", unexpected, expected),
             ErrorKind::KeyMustBeAByteStr => f.write_str("key must be a byte string"),
             ErrorKind::KeyWithoutValue => f.write_str("key without value"),
+            ErrorKind::LeadingZero => f.write_str("integer with leading zero"),
+            ErrorKind::NegativeZero => f.write_str("negative zero integer"),
+            ErrorKind::NonCanonicalByteStrLen => {
+                f.write_str("byte string length with leading zero")
+            }
             ErrorKind::TrailingData => f.write_str("trailing data error"),
+            ErrorKind::UnexpectedByte(b) if b.is_ascii_graphic() => {
+                write!(f, "unexpected byte 0x{:02x} ('{}')", b, char::from(*b))
+            }
+            ErrorKind::UnexpectedByte(b) => write!(f, "unexpected byte 0x{:02x}", b),
+            ErrorKind::UnsortedKeys => f.write_str("dictionary keys not in sorted order"),
             ErrorKind::UnsupportedType => f.write_str("unsupported type"),
             ErrorKind::ValueWithoutKey => f.write_str("value without key"),
+            #[cfg(feature = "embedded-io")]
+            ErrorKind::EmbeddedIo(kind) => fmt::Debug::fmt(kind, f),
             #[cfg(feature = "std")]
             ErrorKind::Io(source) => fmt::Debug::fmt(source, f),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{format, vec::Vec};
+
+    fn snippet(source: &[u8], byte_offset: usize) -> String {
+        format!(
+            "{}",
+            Error::new(ErrorKind::EofWhileParsingValue, byte_offset).snippet(source)
+        )
+    }
+
+    #[test]
+    fn test_snippet_context_lines() {
+        let source = (0..64).collect::<Vec<u8>>();
+        let snippet = snippet(&source, 40);
+        let lines = snippet.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("00000010  10 11"));
+        assert!(lines[1].starts_with("00000020  20 21"));
+        assert_eq!(lines[2].find("^^"), Some(9 + 8 * 3 + 2));
+        assert_eq!(lines[2].rfind('^'), Some(61 + 8));
+        assert!(lines[3].starts_with("00000030  30 31"));
+        assert!(lines[3].ends_with("|0123456789:;<=>?|"));
+    }
+
+    #[test]
+    fn test_snippet_end_of_source() {
+        let snippet_end = snippet(b"i12", 3);
+        let lines = snippet_end.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("00000000  69 31 32  "));
+        assert!(lines[0].ends_with("  |i12|"));
+        assert_eq!(lines[1].find("^^"), Some(9 + 3 * 3 + 1));
+        assert_eq!(lines[1].rfind('^'), Some(61 + 3));
+
+        let snippet_empty = snippet(b"", 0);
+        let lines = snippet_empty.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("  ||"));
+        assert_eq!(lines[1].find("^^"), Some(10));
+
+        let snippet_full_line = snippet(&[b'a'; 16], 16);
+        let lines = snippet_full_line.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("00000010 "));
+        assert!(lines[1].ends_with("||"));
+    }
+
+    #[test]
+    fn test_snippet_non_ascii() {
+        let snippet = snippet(b"4:\xff\x00ab", 2);
+        assert!(snippet.starts_with("00000000  34 3a ff 00 61 62"));
+        assert!(snippet.lines().next().unwrap().ends_with("|4:..ab|"));
+    }
+
+    #[test]
+    fn test_byte_offset_zero() {
+        let err = Error::new(ErrorKind::UnexpectedByte(b'x'), 0).fix_byte_offset(3);
+        assert_eq!(err.byte_offset(), 0);
+        assert_eq!(
+            format!("{}", err),
+            "unexpected byte 0x78 ('x') at byte offset 0"
+        );
+
+        let err = Error::with_kind(ErrorKind::UnsupportedType);
+        assert_eq!(err.byte_offset(), 0);
+        assert_eq!(format!("{}", err.offset_by(2)), "unsupported type");
+
+        let err = Error::with_kind(ErrorKind::UnsupportedType).fix_byte_offset(3);
+        assert_eq!(err.byte_offset(), 3);
+    }
+}
@@ -105,32 +105,78 @@ extern crate alloc;
 #[macro_use]
 extern crate serde;
 
+#[cfg(test)]
+#[macro_use]
+mod test_util;
+
 mod bstring;
+#[cfg(all(feature = "std", feature = "tokio-util"))]
+mod codec;
 mod de;
 mod error;
 
 pub mod read;
 pub mod write;
 
+mod push;
+mod raw;
 mod ser;
+mod spanned;
 pub mod value;
 
 #[doc(inline)]
 pub use bstring::ByteString;
 #[doc(inline)]
-pub use de::{from_slice, Deserializer};
+#[cfg(all(feature = "std", feature = "tokio-util"))]
+pub use codec::BencodeCodec;
+#[doc(inline)]
+pub use de::{
+    from_slice, from_slice_strict, Deserializer, DuplicateKeys, Limits, StreamDeserializer,
+};
+#[doc(inline)]
+pub use error::{Error, ErrorKind, Path, PathSegment, Result, Snippet};
+#[doc(inline)]
+pub use push::{Feed, PushDecoder};
 #[doc(inline)]
-pub use error::{Error, ErrorKind, Result};
+pub use raw::RawValue;
 #[doc(inline)]
-pub use value::{from_value, to_value, Value};
+pub use spanned::Spanned;
+#[doc(inline)]
+pub use value::{
+    from_value, from_value_bool_as_int, to_value, to_value_bool_as_int, BorrowedValue, Value,
+};
 
 #[doc(inline)]
 #[cfg(feature = "std")]
 pub use ser::to_writer;
 
 #[doc(inline)]
-pub use ser::{to_vec, Serializer};
+#[cfg(feature = "embedded-io")]
+pub use ser::to_embedded_writer;
+
+#[doc(inline)]
+#[cfg(all(feature = "std", feature = "tokio"))]
+pub use ser::to_tokio_writer;
+
+#[doc(inline)]
+#[cfg(all(feature = "std", feature = "futures-io"))]
+pub use ser::to_futures_writer;
+
+#[doc(inline)]
+pub use ser::{to_slice, to_vec, Serializer};
 
 #[doc(inline)]
 #[cfg(feature = "std")]
-pub use de::from_reader;
+pub use de::{from_buf_reader, from_reader, from_reader_strict};
+
+#[doc(inline)]
+#[cfg(feature = "embedded-io")]
+pub use de::from_embedded_reader;
+
+#[doc(inline)]
+#[cfg(all(feature = "std", feature = "tokio"))]
+pub use de::from_tokio_reader;
+
+#[doc(inline)]
+#[cfg(all(feature = "std", feature = "futures-io"))]
+pub use de::from_futures_reader;
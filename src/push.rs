@@ -0,0 +1,283 @@
+//! Decodes values from bytes which are received in chunks.
+
+use crate::de::{Config, Deserializer, DuplicateKeys, Limits};
+use crate::error::Result;
+use crate::read::{
+    scan::{Scan, Scanner},
+    SliceRead,
+};
+use serde::de;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// The result of feeding bytes to a [`PushDecoder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Feed<T> {
+    /// The value is incomplete.
+    ///
+    /// At least the given number of bytes are needed to complete the value.
+    /// All of the fed bytes were consumed.
+    NeedMore(usize),
+    /// The value is complete.
+    ///
+    /// The number is how many of the fed bytes were consumed. Any remaining
+    /// bytes belong to the next value.
+    Complete(T, usize),
+}
+
+/// Decodes values from bytes which are received in chunks.
+///
+/// Each chunk is fed to the decoder as it arrives. The bytes are checked
+/// while they are fed, so malformed input is reported as an error
+/// immediately while incomplete input is reported as [`Feed::NeedMore`].
+/// Previously fed bytes are not parsed again.
+///
+/// Byte offsets in errors and the limits are relative to the start of the
+/// value being decoded. After a value is complete or an error is returned,
+/// the decoder starts on the next value.
+///
+/// # Example
+///
+/// ```
+/// use bt_bencode::{Feed, PushDecoder, Value};
+///
+/// let mut decoder = PushDecoder::new();
+///
+/// assert_eq!(decoder.feed::<Value>(b"d3:cow")?, Feed::NeedMore(1));
+/// assert_eq!(decoder.feed::<Value>(b"3:m")?, Feed::NeedMore(2));
+///
+/// match decoder.feed::<Value>(b"ooe4:spam")? {
+///     Feed::Complete(value, consumed) => {
+///         assert_eq!(value["cow"].as_str(), Some("moo"));
+///         assert_eq!(consumed, 3);
+///     }
+///     Feed::NeedMore(_) => unreachable!(),
+/// }
+///
+/// # Ok::<_, bt_bencode::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PushDecoder {
+    scanner: Scanner,
+    /// The bytes of an incomplete value
+    buf: Vec<u8>,
+    config: Config,
+}
+
+impl Default for PushDecoder {
+    fn default() -> Self {
+        PushDecoder::new()
+    }
+}
+
+impl PushDecoder {
+    /// Constructs a new decoder.
+    #[must_use]
+    pub fn new() -> Self {
+        let config = Config::default();
+        PushDecoder {
+            scanner: Scanner::new(config.limits, 0),
+            buf: Vec::new(),
+            config,
+        }
+    }
+
+    /// Sets if only the canonical encoding of values is accepted.
+    ///
+    /// See [`Deserializer::set_strict()`].
+    pub fn set_strict(&mut self, strict: bool) {
+        self.config.strict = strict;
+    }
+
+    /// Sets how a dictionary with a repeated key is decoded.
+    ///
+    /// See [`Deserializer::set_duplicate_keys()`].
+    pub fn set_duplicate_keys(&mut self, duplicate_keys: DuplicateKeys) {
+        self.config.duplicate_keys = duplicate_keys;
+    }
+
+    /// Sets the limits for each value.
+    ///
+    /// The byte limit is the maximum number of bytes in a single value. See
+    /// [`Deserializer::set_limits()`].
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.config.limits = limits;
+        self.scanner.set_limits(limits);
+    }
+
+    /// Returns the number of bytes of the incomplete value which were fed.
+    #[must_use]
+    pub fn buffered_len(&self) -> usize {
+        self.scanner.byte_offset()
+    }
+
+    /// Feeds the next chunk of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Errors include malformatted input, exceeded limits, and if the value
+    /// cannot be deserialized into an instance of `T`.
+    pub fn feed<T>(&mut self, bytes: &[u8]) -> Result<Feed<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        match self.scanner.scan(bytes) {
+            Ok(Scan::NeedMore(len)) => {
+                self.buf.extend_from_slice(bytes);
+                Ok(Feed::NeedMore(len))
+            }
+            Ok(Scan::Complete(len)) => {
+                let result = if self.buf.is_empty() {
+                    decode(&bytes[..len], self.config)
+                } else {
+                    self.buf.extend_from_slice(&bytes[..len]);
+                    decode(&self.buf, self.config)
+                };
+                self.reset();
+                result.map(|value| Feed::Complete(value, len))
+            }
+            Err(err) => {
+                self.reset();
+                Err(err)
+            }
+        }
+    }
+
+    /// Discards the bytes of an incomplete value.
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.scanner = Scanner::new(self.config.limits, 0);
+    }
+}
+
+/// Deserializes a complete value.
+pub(crate) fn decode<T>(bytes: &[u8], config: Config) -> Result<T>
+where
+    T: de::DeserializeOwned,
+{
+    let mut de = Deserializer::with_config(SliceRead::new(bytes), config);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, Value};
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{string::String, vec};
+    #[cfg(feature = "std")]
+    use std::string::String;
+
+    /// Feeds each byte separately and returns the decoded values.
+    fn feed_bytes<T>(decoder: &mut PushDecoder, bytes: &[u8]) -> Result<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let mut values = Vec::new();
+        for idx in 0..bytes.len() {
+            match decoder.feed(&bytes[idx..=idx])? {
+                Feed::NeedMore(len) => assert!(len > 0),
+                Feed::Complete(value, len) => {
+                    assert_eq!(len, 1);
+                    values.push(value);
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    #[test]
+    fn test_feed_bytes() -> Result<()> {
+        let input = b"d3:cow3:moo4:spaml4:eggsi-1eee4:spami42e";
+        let mut decoder = PushDecoder::new();
+        let values: Vec<Value> = feed_bytes(&mut decoder, input)?;
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0], crate::from_slice::<Value>(&input[..30])?);
+        assert_eq!(values[1].as_str(), Some("spam"));
+        assert_eq!(values[2].as_u64(), Some(42));
+        assert_eq!(decoder.buffered_len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_feed_chunks() -> Result<()> {
+        let mut decoder = PushDecoder::new();
+        assert_eq!(decoder.feed::<Value>(b"")?, Feed::NeedMore(1));
+        assert_eq!(decoder.feed::<Value>(b"l10:01")?, Feed::NeedMore(8));
+        assert_eq!(decoder.buffered_len(), 6);
+        assert_eq!(
+            decoder.feed::<Vec<String>>(b"23456789ei1e")?,
+            Feed::Complete(vec![String::from("0123456789")], 9)
+        );
+        assert_eq!(decoder.buffered_len(), 0);
+        assert_eq!(decoder.feed::<i64>(b"i1e")?, Feed::Complete(1, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_feed_errors() {
+        let mut decoder = PushDecoder::new();
+        assert_eq!(decoder.feed::<Value>(b"d3:cow").unwrap(), Feed::NeedMore(1));
+        assert_error_kind!(decoder.feed::<Value>(b"x"), ErrorKind::InvalidDict, 6);
+        assert_eq!(decoder.buffered_len(), 0);
+
+        assert_error_kind!(decoder.feed::<Value>(b"li1ex"), ErrorKind::InvalidList, 4);
+        assert_error_kind!(
+            decoder.feed::<Value>(b"i-e"),
+            ErrorKind::UnexpectedByte(b'e'),
+            2
+        );
+        assert_error_kind!(decoder.feed::<Value>(b"e"), ErrorKind::ExpectedSomeValue, 0);
+        assert_error_kind!(
+            decoder.feed::<String>(b"i1e"),
+            ErrorKind::InvalidType { .. },
+            3
+        );
+        assert_eq!(
+            decoder.feed::<String>(b"4:spam").unwrap(),
+            Feed::Complete(String::from("spam"), 6)
+        );
+    }
+
+    #[test]
+    fn test_feed_config() {
+        let mut decoder = PushDecoder::new();
+        decoder.set_limits(Limits::default().max_bytes(8).max_depth(2));
+        assert_eq!(decoder.feed::<Value>(b"4:spa").unwrap(), Feed::NeedMore(1));
+        assert_eq!(
+            decoder.feed::<Value>(b"m").unwrap(),
+            Feed::Complete(Value::from("spam"), 1)
+        );
+        assert_eq!(
+            decoder.feed::<Value>(b"l4:spam").unwrap(),
+            Feed::NeedMore(1)
+        );
+        assert_error_kind!(
+            decoder.feed::<Value>(b"4:eggse"),
+            ErrorKind::ByteLimitExceeded,
+            8
+        );
+        assert_error_kind!(
+            decoder.feed::<Value>(b"llle"),
+            ErrorKind::DepthLimitExceeded,
+            2
+        );
+
+        let mut decoder = PushDecoder::new();
+        decoder.set_strict(true);
+        assert_error_kind!(decoder.feed::<Value>(b"i-0e"), ErrorKind::NegativeZero, 2);
+        decoder.set_duplicate_keys(DuplicateKeys::KeepFirst);
+        decoder.set_strict(false);
+        let value = match decoder.feed::<Value>(b"d1:ai1e1:ai2ee").unwrap() {
+            Feed::Complete(value, _) => value,
+            Feed::NeedMore(_) => panic!("expected complete value"),
+        };
+        assert_eq!(value["a"].as_u64(), Some(1));
+    }
+}
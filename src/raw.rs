@@ -0,0 +1,336 @@
+//! Captures the encoded bytes of a value.
+
+use crate::error::Result;
+use core::fmt;
+use serde::{
+    de::{self, Deserialize, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{borrow::ToOwned, boxed::Box, vec::Vec};
+#[cfg(feature = "std")]
+use std::{boxed::Box, vec::Vec};
+
+/// The name of the newtype struct which carries the encoded bytes of a
+/// [`RawValue`] through the Serde data model.
+pub(crate) const RAW_VALUE_TOKEN: &str = "$bt_bencode::private::RawValue";
+
+/// The complete encoded bytes of a single value.
+///
+/// When a `&RawValue` or a `Box<RawValue>` is deserialized, the next value is
+/// not parsed into a type. Instead, its encoded bytes are captured as-is,
+/// including the length prefix of a byte string and the `i`, `l`, `d`, and `e`
+/// delimiters. When a `RawValue` is serialized, the bytes are written verbatim.
+///
+/// A common use is to capture the `info` dictionary in a BitTorrent metainfo
+/// to generate the infohash.
+///
+/// ```rust
+/// use bt_bencode::RawValue;
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Metainfo<'a> {
+///     #[serde(borrow)]
+///     info: &'a RawValue,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     name: String,
+/// }
+///
+/// let metainfo: Metainfo<'_> = bt_bencode::from_slice(b"d4:infod4:name4:spamee")?;
+/// assert_eq!(metainfo.info.as_bytes(), b"d4:name4:spame");
+///
+/// let info: Info = metainfo.info.deserialize_into()?;
+/// assert_eq!(info.name, "spam");
+///
+/// assert_eq!(bt_bencode::to_vec(metainfo.info)?, b"d4:name4:spame");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// A `&RawValue` borrows from the source, so it can only be deserialized with
+/// [`from_slice()`][crate::from_slice]. A `Box<RawValue>` can be deserialized
+/// from any source.
+#[repr(transparent)]
+pub struct RawValue {
+    bytes: [u8],
+}
+
+impl RawValue {
+    fn from_borrowed(bytes: &[u8]) -> &Self {
+        // `RawValue` is a transparent wrapper around `[u8]`.
+        unsafe { &*(bytes as *const [u8] as *const RawValue) }
+    }
+
+    fn from_owned(bytes: Box<[u8]>) -> Box<Self> {
+        // `RawValue` is a transparent wrapper around `[u8]`.
+        unsafe { Box::from_raw(Box::into_raw(bytes) as *mut RawValue) }
+    }
+
+    /// Verifies that the bytes are a single encoded value and returns them as
+    /// a `RawValue`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the bytes are not a valid encoded value or if
+    /// there are bytes after the value.
+    pub fn from_slice(bytes: &[u8]) -> Result<&Self> {
+        crate::from_slice::<de::IgnoredAny>(bytes)?;
+        Ok(Self::from_borrowed(bytes))
+    }
+
+    /// Returns the encoded bytes.
+    #[must_use]
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Deserializes the encoded bytes into an instance of `T`.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the encoded value does not match the
+    /// expected type. Byte offsets in the error are relative to the start of
+    /// the raw value.
+    pub fn deserialize_into<'a, T>(&'a self) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        crate::from_slice(&self.bytes)
+    }
+}
+
+impl fmt::Debug for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawValue").field(&&self.bytes).finish()
+    }
+}
+
+impl PartialEq for RawValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for RawValue {}
+
+impl ToOwned for RawValue {
+    type Owned = Box<RawValue>;
+
+    fn to_owned(&self) -> Box<RawValue> {
+        Self::from_owned(Box::from(&self.bytes))
+    }
+}
+
+impl Clone for Box<RawValue> {
+    fn clone(&self) -> Self {
+        (**self).to_owned()
+    }
+}
+
+impl Serialize for RawValue {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(RAW_VALUE_TOKEN, &RawBytes(&self.bytes))
+    }
+}
+
+/// Serializes the encoded bytes inside of the [`RAW_VALUE_TOKEN`] newtype
+/// struct.
+struct RawBytes<'a>(&'a [u8]);
+
+impl Serialize for RawBytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for &'a RawValue {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct BorrowedVisitor;
+
+        impl<'de> Visitor<'de> for BorrowedVisitor {
+            type Value = &'de RawValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("any valid Bencode value borrowed from the source")
+            }
+
+            fn visit_borrowed_bytes<E>(
+                self,
+                value: &'de [u8],
+            ) -> core::result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_borrowed(value))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(RAW_VALUE_TOKEN, BorrowedVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<RawValue> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct OwnedVisitor;
+
+        impl<'de> Visitor<'de> for OwnedVisitor {
+            type Value = Box<RawValue>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("any valid Bencode value")
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_owned(Box::from(value)))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> core::result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_owned(value.into_boxed_slice()))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(RAW_VALUE_TOKEN, OwnedVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Deserializer, ErrorKind, Value};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::string::String;
+    #[cfg(feature = "std")]
+    use std::string::String;
+
+    #[test]
+    fn test_deserialize_borrowed() -> Result<()> {
+        #[derive(Deserialize)]
+        struct Test<'a> {
+            #[serde(borrow)]
+            a: &'a RawValue,
+            #[serde(borrow)]
+            b: &'a RawValue,
+            #[serde(borrow)]
+            c: &'a RawValue,
+            #[serde(borrow)]
+            d: &'a RawValue,
+        }
+
+        let input = b"d1:a4:spam1:bi-3e1:cl1:xe1:dd1:yi1eee";
+        let test: Test<'_> = crate::from_slice(input)?;
+        assert_eq!(test.a.as_bytes(), b"4:spam");
+        assert_eq!(test.b.as_bytes(), b"i-3e");
+        assert_eq!(test.c.as_bytes(), b"l1:xe");
+        assert_eq!(test.d.as_bytes(), b"d1:yi1ee");
+
+        assert_eq!(test.a.deserialize_into::<&str>()?, "spam");
+        assert_eq!(test.b.deserialize_into::<i64>()?, -3);
+        assert_eq!(test.d.deserialize_into::<Value>()?["y"].as_u64(), Some(1));
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_owned() -> Result<()> {
+        #[derive(Deserialize)]
+        struct Test {
+            a: Box<RawValue>,
+            b: Box<RawValue>,
+        }
+
+        let input = &b"d1:a4:spam1:bli1ei2eee"[..];
+        let test: Test = crate::from_reader(input)?;
+        assert_eq!(test.a.as_bytes(), b"4:spam");
+        assert_eq!(test.b.as_bytes(), b"li1ei2ee");
+        assert_eq!(test.b.clone(), test.b);
+
+        let mut de = Deserializer::from_reader(&b"4:spam"[..]);
+        assert!(<&RawValue>::deserialize(&mut de).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_strict() {
+        let mut de = Deserializer::from_slice(b"d1:bi1e1:ai2ee");
+        de.set_strict(true);
+        match <&RawValue>::deserialize(&mut de).unwrap_err().kind() {
+            ErrorKind::UnsortedKeys => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_from_value() -> Result<()> {
+        let value: Value = crate::from_slice(b"d1:ai1e1:bl4:spamee")?;
+        let raw: Box<RawValue> = crate::from_value(value.clone())?;
+        assert_eq!(raw.as_bytes(), b"d1:ai1e1:bl4:spamee");
+        let raw = <Box<RawValue>>::deserialize(&value["b"])?;
+        assert_eq!(raw.as_bytes(), b"l4:spame");
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize() -> Result<()> {
+        #[derive(Serialize)]
+        struct Test<'a> {
+            a: &'a RawValue,
+            b: Box<RawValue>,
+        }
+
+        let test = Test {
+            a: RawValue::from_slice(b"4:spam")?,
+            b: RawValue::from_slice(b"d1:xi1ee")?.to_owned(),
+        };
+        assert_eq!(crate::to_vec(&test)?, b"d1:a4:spam1:bd1:xi1eee");
+
+        let value = crate::to_value(&test)?;
+        assert_eq!(value["a"].as_str(), Some("spam"));
+        assert_eq!(value["b"]["x"].as_u64(), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_slice() {
+        assert!(RawValue::from_slice(b"i1e").is_ok());
+        assert!(RawValue::from_slice(b"i1ei2e").is_err());
+        assert!(RawValue::from_slice(b"l").is_err());
+        assert!(RawValue::from_slice(b"").is_err());
+        assert_eq!(
+            format_debug(RawValue::from_slice(b"i1e").unwrap()),
+            "RawValue([105, 49, 101])"
+        );
+    }
+
+    fn format_debug(raw: &RawValue) -> String {
+        use core::fmt::Write as _;
+
+        let mut s = String::new();
+        write!(s, "{:?}", raw).unwrap();
+        s
+    }
+}
@@ -1,7 +1,8 @@
 //! [Read] trait and helpers to read bytes for the deserializer.
 
+use crate::de::Limits;
 use crate::error::{Error, ErrorKind, Result};
-use core::ops::Deref;
+use core::{cmp, ops::Deref};
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
 use alloc::vec::Vec;
@@ -9,6 +10,15 @@ use alloc::vec::Vec;
 #[cfg(feature = "std")]
 use std::{io, vec::Vec};
 
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+mod async_io;
+pub(crate) mod scan;
+
+#[cfg(all(feature = "std", feature = "futures-io"))]
+pub use self::async_io::FuturesIoRead;
+#[cfg(all(feature = "std", feature = "tokio"))]
+pub use self::async_io::TokioIoRead;
+
 /// A reference to borrowed data.
 ///
 /// The variant determines if the slice comes from a long lived source (e.g. an
@@ -56,6 +66,21 @@ pub trait Read<'a> {
     /// Returns the position in the stream of bytes.
     fn byte_offset(&self) -> usize;
 
+    /// Sets the limits for the data which is read afterwards.
+    ///
+    /// Implementations should return an error with the corresponding
+    /// [`ErrorKind`] before reading or buffering data beyond a limit:
+    ///
+    /// - the total number of bytes read from the source
+    /// - the length of any byte string
+    /// - for the `parse_raw_*` methods, the nesting depth of lists and
+    ///   dictionaries (the list or dictionary being parsed has a depth of 1)
+    ///   and the number of elements in any list or dictionary
+    ///
+    /// The default implementation ignores the limits.
+    #[inline]
+    fn set_limits(&mut self, _limits: Limits) {}
+
     /// Returns the next slice of data for the given length.
     ///
     /// If all of the data is already read and available to borrowed against,
@@ -127,6 +152,13 @@ pub trait Read<'a> {
     fn parse_raw_dict<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>>;
 }
 
+/// The maximum number of bytes reserved before a byte string is read.
+///
+/// The length of a byte string comes from untrusted input, so the buffer for
+/// a longer byte string grows as the bytes are actually read.
+#[cfg(any(feature = "std", feature = "embedded-io"))]
+const MAX_RESERVE_LEN: usize = 64 * 1024;
+
 /// A wrapper to implement this crate's [Read] trait for [`std::io::Read`] trait implementations.
 #[cfg(feature = "std")]
 #[derive(Debug)]
@@ -138,6 +170,7 @@ where
     iter: io::Bytes<R>,
     peeked_byte: Option<u8>,
     byte_offset: usize,
+    limits: Limits,
 }
 
 #[cfg(feature = "std")]
@@ -146,61 +179,305 @@ where
     R: io::Read,
 {
     /// Instantiates a new reader.
+    #[allow(clippy::unbuffered_bytes)]
     pub fn new(reader: R) -> Self {
         IoRead {
             iter: reader.bytes(),
             peeked_byte: None,
             byte_offset: 0,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Reads the next byte from the underlying reader.
+    ///
+    /// Returns an error if there is a byte beyond the byte budget.
+    #[inline]
+    fn read_byte(&mut self) -> Option<Result<u8>> {
+        match self.iter.next() {
+            Some(Ok(_)) if self.byte_offset >= self.limits.max_bytes => Some(Err(Error::new(
+                ErrorKind::ByteLimitExceeded,
+                self.byte_offset(),
+            ))),
+            Some(Ok(b)) => Some(Ok(b)),
+            Some(Err(err)) => Some(Err(Error::new(ErrorKind::Io(err), self.byte_offset()))),
+            None => None,
         }
     }
 }
 
+/// Implements [Read] for a reader which reads one byte at a time with a
+/// `read_byte()` method.
+#[cfg(any(feature = "std", feature = "embedded-io"))]
+macro_rules! byte_read {
+    ($name:ident, $bound:path) => {
+        impl<R> $name<R>
+        where
+            R: $bound,
+        {
+            /// Reads a byte string length up to and including the `:` and pushes the
+            /// read bytes into the buffer if `raw` is true.
+            fn parse_byte_str_len(&mut self, buf: &mut Vec<u8>, raw: bool) -> Result<usize> {
+                let start = self.byte_offset();
+                let mut len: usize = 0;
+                loop {
+                    match self.next().ok_or_else(|| {
+                        Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
+                    })?? {
+                        b':' => {
+                            if raw {
+                                buf.push(b':');
+                            }
+                            break;
+                        }
+                        n @ b'0'..=b'9' => {
+                            if raw {
+                                buf.push(n);
+                            }
+
+                            len = len.checked_mul(10).ok_or_else(|| {
+                                Error::new(ErrorKind::IntegerOverflow, self.byte_offset())
+                            })?;
+                            len = len.checked_add(usize::from(n - b'0')).ok_or_else(|| {
+                                Error::new(ErrorKind::IntegerOverflow, self.byte_offset())
+                            })?;
+                        }
+                        b => {
+                            return Err(Error::new(
+                                ErrorKind::UnexpectedByte(b),
+                                self.byte_offset() - 1,
+                            ))
+                        }
+                    }
+                }
+
+                if len > self.limits.max_byte_str_len {
+                    return Err(Error::new(ErrorKind::ByteStrLenLimitExceeded, start));
+                }
+
+                Ok(len)
+            }
+
+            /// Reads the contents of a byte string into the buffer.
+            fn parse_byte_str_contents(&mut self, buf: &mut Vec<u8>, len: usize) -> Result<()> {
+                buf.reserve(cmp::min(len, MAX_RESERVE_LEN));
+                for _ in 0..len {
+                    buf.push(self.next().ok_or_else(|| {
+                        Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
+                    })??);
+                }
+                Ok(())
+            }
+        }
+
+        impl<'a, R> Read<'a> for $name<R>
+        where
+            R: $bound,
+        {
+            #[inline]
+            fn next(&mut self) -> Option<Result<u8>> {
+                match self.peeked_byte.take() {
+                    Some(b) => {
+                        self.byte_offset += 1;
+                        Some(Ok(b))
+                    }
+                    None => match self.read_byte() {
+                        Some(Ok(b)) => {
+                            self.byte_offset += 1;
+                            Some(Ok(b))
+                        }
+                        result => result,
+                    },
+                }
+            }
+
+            #[inline]
+            fn peek(&mut self) -> Option<Result<u8>> {
+                match self.peeked_byte {
+                    Some(b) => Some(Ok(b)),
+                    None => match self.read_byte() {
+                        Some(Ok(b)) => {
+                            self.peeked_byte = Some(b);
+                            Some(Ok(b))
+                        }
+                        result => result,
+                    },
+                }
+            }
+
+            #[inline]
+            fn byte_offset(&self) -> usize {
+                self.byte_offset
+            }
+
+            #[inline]
+            fn set_limits(&mut self, limits: Limits) {
+                self.limits = limits;
+            }
+
+            fn parse_byte_str<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+                debug_assert!(buf.is_empty());
+
+                let len = self.parse_byte_str_len(buf, false)?;
+                self.parse_byte_str_contents(buf, len)?;
+                Ok(Ref::Buffer(&buf[..]))
+            }
+
+            fn parse_raw_integer<'b>(
+                &'b mut self,
+                buf: &'b mut Vec<u8>,
+            ) -> Result<Ref<'a, 'b, [u8]>> {
+                let start_idx = buf.len();
+                buf.push(self.next().ok_or_else(|| {
+                    Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
+                })??);
+
+                match self.peek().ok_or_else(|| {
+                    Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
+                })?? {
+                    b'-' => {
+                        buf.push(self.next().ok_or_else(|| {
+                            Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
+                        })??);
+                    }
+                    b'0'..=b'9' => {}
+                    b => return Err(Error::new(ErrorKind::UnexpectedByte(b), self.byte_offset())),
+                }
+
+                match self.peek().ok_or_else(|| {
+                    Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
+                })?? {
+                    b'0'..=b'9' => {}
+                    b => {
+                        return Err(Error::new(ErrorKind::UnexpectedByte(b), self.byte_offset()));
+                    }
+                }
+
+                let digits_start = self.byte_offset();
+                loop {
+                    match self.next().ok_or_else(|| {
+                        Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
+                    })?? {
+                        b'e' => {
+                            buf.push(b'e');
+                            return Ok(Ref::Buffer(&buf[start_idx..]));
+                        }
+                        n @ b'0'..=b'9' => {
+                            if self.byte_offset() - digits_start > self.limits.max_integer_len {
+                                return Err(Error::new(
+                                    ErrorKind::IntegerLenLimitExceeded,
+                                    self.byte_offset() - 1,
+                                ));
+                            }
+                            buf.push(n);
+                        }
+                        b => {
+                            return Err(Error::new(
+                                ErrorKind::UnexpectedByte(b),
+                                self.byte_offset() - 1,
+                            ))
+                        }
+                    }
+                }
+            }
+
+            fn parse_raw_byte_str<'b>(
+                &mut self,
+                buf: &'b mut Vec<u8>,
+            ) -> Result<Ref<'a, 'b, [u8]>> {
+                let start_idx = buf.len();
+                let len = self.parse_byte_str_len(buf, true)?;
+                self.parse_byte_str_contents(buf, len)?;
+                Ok(Ref::Buffer(&buf[start_idx..]))
+            }
+
+            fn parse_raw_list<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+                let start_idx = buf.len();
+                let limits = self.limits;
+                parse_raw_nested(self, buf, limits, true)?;
+                Ok(Ref::Buffer(&buf[start_idx..]))
+            }
+
+            fn parse_raw_dict<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+                let start_idx = buf.len();
+                let limits = self.limits;
+                parse_raw_nested(self, buf, limits, true)?;
+                Ok(Ref::Buffer(&buf[start_idx..]))
+            }
+        }
+    };
+}
+
 #[cfg(feature = "std")]
-impl<'a, R> Read<'a> for IoRead<R>
+byte_read!(IoRead, io::Read);
+
+/// A wrapper to implement this crate's [Read] trait for [`std::io::BufRead`]
+/// trait implementations.
+///
+/// Unlike [`IoRead`], byte strings are copied from the underlying buffer in
+/// bulk instead of byte by byte.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BufIoRead<R>
 where
-    R: io::Read,
+    R: io::BufRead,
 {
-    #[inline]
-    fn next(&mut self) -> Option<Result<u8>> {
-        match self.peeked_byte.take() {
-            Some(b) => {
-                self.byte_offset += 1;
-                Some(Ok(b))
-            }
-            None => match self.iter.next() {
-                Some(Ok(b)) => {
-                    self.byte_offset += 1;
-                    Some(Ok(b))
-                }
-                Some(Err(err)) => Some(Err(Error::new(ErrorKind::Io(err), self.byte_offset()))),
-                None => None,
-            },
+    reader: R,
+    byte_offset: usize,
+    limits: Limits,
+}
+
+#[cfg(feature = "std")]
+impl<R> BufIoRead<R>
+where
+    R: io::BufRead,
+{
+    /// Instantiates a new reader.
+    pub fn new(reader: R) -> Self {
+        BufIoRead {
+            reader,
+            byte_offset: 0,
+            limits: Limits::default(),
         }
     }
 
-    #[inline]
-    fn peek(&mut self) -> Option<Result<u8>> {
-        match self.peeked_byte {
-            Some(b) => Some(Ok(b)),
-            None => match self.iter.next() {
-                Some(Ok(b)) => {
-                    self.peeked_byte = Some(b);
-                    Some(Ok(b))
+    /// Passes the buffered bytes which can be read within the byte budget to
+    /// `f` and consumes the number of bytes which `f` returns.
+    ///
+    /// The underlying reader is only read from if its buffer is empty. An
+    /// empty slice means the end of the source has been reached. Returns an
+    /// error if there are bytes beyond the byte budget.
+    fn with_buf<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&[u8]) -> (usize, T),
+    {
+        loop {
+            let bytes = match self.reader.fill_buf() {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    if err.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(Error::new(ErrorKind::Io(err), self.byte_offset));
                 }
-                Some(Err(err)) => Some(Err(Error::new(ErrorKind::Io(err), self.byte_offset()))),
-                None => None,
-            },
+            };
+            if !bytes.is_empty() && self.byte_offset >= self.limits.max_bytes {
+                return Err(Error::new(ErrorKind::ByteLimitExceeded, self.byte_offset));
+            }
+            let len = cmp::min(bytes.len(), self.limits.max_bytes - self.byte_offset);
+            let (consumed, value) = f(&bytes[..len]);
+            debug_assert!(consumed <= len);
+            self.reader.consume(consumed);
+            self.byte_offset += consumed;
+            return Ok(value);
         }
     }
 
-    #[inline]
-    fn byte_offset(&self) -> usize {
-        self.byte_offset
-    }
-
-    fn parse_byte_str<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
-        debug_assert!(buf.is_empty());
-
+    /// Reads a byte string length up to and including the `:` and pushes the
+    /// read bytes into the buffer if `raw` is true.
+    fn parse_byte_str_len(&mut self, buf: &mut Vec<u8>, raw: bool) -> Result<usize> {
+        let start = self.byte_offset();
         let mut len: usize = 0;
         loop {
             match self
@@ -208,28 +485,93 @@ where
                 .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
             {
                 b':' => {
+                    if raw {
+                        buf.push(b':');
+                    }
                     break;
                 }
                 n @ b'0'..=b'9' => {
+                    if raw {
+                        buf.push(n);
+                    }
+
                     len = len.checked_mul(10).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
+                        Error::new(ErrorKind::IntegerOverflow, self.byte_offset())
                     })?;
                     len = len.checked_add(usize::from(n - b'0')).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
+                        Error::new(ErrorKind::IntegerOverflow, self.byte_offset())
                     })?;
                 }
-                _ => return Err(Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())),
+                b => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedByte(b),
+                        self.byte_offset() - 1,
+                    ))
+                }
             }
         }
 
-        buf.reserve(len);
+        if len > self.limits.max_byte_str_len {
+            return Err(Error::new(ErrorKind::ByteStrLenLimitExceeded, start));
+        }
+
+        Ok(len)
+    }
 
-        for _ in 0..len {
-            buf.push(self.next().ok_or_else(|| {
-                Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
-            })??);
+    /// Copies the contents of a byte string into the buffer.
+    fn parse_byte_str_contents(&mut self, buf: &mut Vec<u8>, len: usize) -> Result<()> {
+        buf.reserve(cmp::min(len, MAX_RESERVE_LEN));
+        let mut remaining = len;
+        while remaining > 0 {
+            let len = self.with_buf(|bytes| {
+                let len = cmp::min(bytes.len(), remaining);
+                buf.extend_from_slice(&bytes[..len]);
+                (len, len)
+            })?;
+            if len == 0 {
+                return Err(Error::new(
+                    ErrorKind::EofWhileParsingValue,
+                    self.byte_offset(),
+                ));
+            }
+            remaining -= len;
         }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, R> Read<'a> for BufIoRead<R>
+where
+    R: io::BufRead,
+{
+    #[inline]
+    fn next(&mut self) -> Option<Result<u8>> {
+        self.with_buf(|bytes| (cmp::min(bytes.len(), 1), bytes.first().copied()))
+            .transpose()
+    }
+
+    #[inline]
+    fn peek(&mut self) -> Option<Result<u8>> {
+        self.with_buf(|bytes| (0, bytes.first().copied()))
+            .transpose()
+    }
+
+    #[inline]
+    fn byte_offset(&self) -> usize {
+        self.byte_offset
+    }
+
+    #[inline]
+    fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    fn parse_byte_str<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        debug_assert!(buf.is_empty());
 
+        let len = self.parse_byte_str_len(buf, false)?;
+        self.parse_byte_str_contents(buf, len)?;
         Ok(Ref::Buffer(&buf[..]))
     }
 
@@ -250,7 +592,7 @@ where
                 })??);
             }
             b'0'..=b'9' => {}
-            _ => return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset())),
+            b => return Err(Error::new(ErrorKind::UnexpectedByte(b), self.byte_offset())),
         }
 
         match self
@@ -258,11 +600,12 @@ where
             .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
         {
             b'0'..=b'9' => {}
-            _ => {
-                return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset()));
+            b => {
+                return Err(Error::new(ErrorKind::UnexpectedByte(b), self.byte_offset()));
             }
         }
 
+        let digits_start = self.byte_offset();
         loop {
             match self
                 .next()
@@ -272,138 +615,124 @@ where
                     buf.push(b'e');
                     return Ok(Ref::Buffer(&buf[start_idx..]));
                 }
-                n @ b'0'..=b'9' => buf.push(n),
-                _ => return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset())),
-            }
-        }
-    }
-
-    fn parse_raw_byte_str<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
-        let start_idx = buf.len();
-        let mut len: usize = 0;
-        loop {
-            match self
-                .next()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b':' => {
-                    buf.push(b':');
-                    break;
-                }
                 n @ b'0'..=b'9' => {
+                    if self.byte_offset() - digits_start > self.limits.max_integer_len {
+                        return Err(Error::new(
+                            ErrorKind::IntegerLenLimitExceeded,
+                            self.byte_offset() - 1,
+                        ));
+                    }
                     buf.push(n);
-
-                    len = len.checked_mul(10).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
-                    })?;
-                    len = len.checked_add(usize::from(n - b'0')).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
-                    })?;
                 }
-                _ => return Err(Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())),
+                b => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedByte(b),
+                        self.byte_offset() - 1,
+                    ))
+                }
             }
         }
+    }
 
-        buf.reserve(len);
-        for _ in 0..len {
-            buf.push(self.next().ok_or_else(|| {
-                Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
-            })??);
-        }
+    fn parse_raw_byte_str<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        let start_idx = buf.len();
+        let len = self.parse_byte_str_len(buf, true)?;
+        self.parse_byte_str_contents(buf, len)?;
         Ok(Ref::Buffer(&buf[start_idx..]))
     }
 
     fn parse_raw_list<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
         let start_idx = buf.len();
-        buf.push(
-            self.next()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??,
-        );
-
-        loop {
-            match self
-                .peek()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b'e' => {
-                    buf.push(self.next().ok_or_else(|| {
-                        Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
-                    })??);
-                    return Ok(Ref::Buffer(&buf[start_idx..]));
-                }
-                b'0'..=b'9' => {
-                    self.parse_raw_byte_str(buf)?;
-                }
-                b'i' => {
-                    self.parse_raw_integer(buf)?;
-                }
-                b'l' => {
-                    self.parse_raw_list(buf)?;
-                }
-                b'd' => {
-                    self.parse_raw_dict(buf)?;
-                }
-                _ => return Err(Error::new(ErrorKind::InvalidList, self.byte_offset())),
-            }
-        }
+        let limits = self.limits;
+        parse_raw_nested(self, buf, limits, true)?;
+        Ok(Ref::Buffer(&buf[start_idx..]))
     }
 
     fn parse_raw_dict<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
         let start_idx = buf.len();
-        buf.push(
-            self.next()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??,
-        );
+        let limits = self.limits;
+        parse_raw_nested(self, buf, limits, true)?;
+        Ok(Ref::Buffer(&buf[start_idx..]))
+    }
+}
 
-        loop {
-            match self
-                .peek()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b'0'..=b'9' => {
-                    self.parse_raw_byte_str(buf)?;
-                }
-                b'e' => {
-                    buf.push(self.next().ok_or_else(|| {
-                        Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
-                    })??);
-                    return Ok(Ref::Buffer(&buf[start_idx..]));
-                }
-                _ => {
-                    return Err(Error::new(ErrorKind::InvalidDict, self.byte_offset()));
-                }
-            }
+/// A wrapper to implement this crate's [Read] trait for
+/// [`embedded_io::Read`] trait implementations.
+///
+/// Bytes are read one at a time, so the reader should be buffered if a read
+/// is expensive.
+#[cfg(feature = "embedded-io")]
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EmbeddedIoRead<R>
+where
+    R: embedded_io::Read,
+{
+    reader: R,
+    peeked_byte: Option<u8>,
+    byte_offset: usize,
+    limits: Limits,
+}
 
-            match self
-                .peek()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b'0'..=b'9' => {
-                    self.parse_raw_byte_str(buf)?;
-                }
-                b'i' => {
-                    self.parse_raw_integer(buf)?;
-                }
-                b'l' => {
-                    self.parse_raw_list(buf)?;
-                }
-                b'd' => {
-                    self.parse_raw_dict(buf)?;
+#[cfg(feature = "embedded-io")]
+impl<R> EmbeddedIoRead<R>
+where
+    R: embedded_io::Read,
+{
+    /// Instantiates a new reader.
+    pub fn new(reader: R) -> Self {
+        EmbeddedIoRead {
+            reader,
+            peeked_byte: None,
+            byte_offset: 0,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Reads the next byte from the underlying reader.
+    ///
+    /// Returns an error if there is a byte beyond the byte budget.
+    #[inline]
+    fn read_byte(&mut self) -> Option<Result<u8>> {
+        use embedded_io::Error as _;
+
+        let mut byte = [0; 1];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return None,
+                Ok(_) if self.byte_offset >= self.limits.max_bytes => {
+                    return Some(Err(Error::new(
+                        ErrorKind::ByteLimitExceeded,
+                        self.byte_offset,
+                    )))
                 }
-                _ => {
-                    return Err(Error::new(ErrorKind::InvalidDict, self.byte_offset()));
+                Ok(_) => return Some(Ok(byte[0])),
+                Err(err) => {
+                    let kind = err.kind();
+                    if kind != embedded_io::ErrorKind::Interrupted {
+                        return Some(Err(Error::new(
+                            ErrorKind::EmbeddedIo(kind),
+                            self.byte_offset,
+                        )));
+                    }
                 }
             }
         }
     }
 }
 
+#[cfg(feature = "embedded-io")]
+byte_read!(EmbeddedIoRead, embedded_io::Read);
+
 /// A wrapper to implement this crate's [Read] trait for byte slices.
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
 pub struct SliceRead<'a> {
     slice: &'a [u8],
     byte_offset: usize,
+    /// The index after the last byte which can be read within the byte budget
+    end: usize,
+    limits: Limits,
 }
 
 impl<'a> SliceRead<'a> {
@@ -413,6 +742,67 @@ impl<'a> SliceRead<'a> {
         SliceRead {
             slice,
             byte_offset: 0,
+            end: slice.len(),
+            limits: Limits::default(),
+        }
+    }
+
+    /// Returns the error when the end of the readable bytes is reached.
+    fn end_error(&self) -> Error {
+        if self.end < self.slice.len() {
+            Error::new(ErrorKind::ByteLimitExceeded, self.end)
+        } else {
+            Error::new(ErrorKind::EofWhileParsingValue, self.end)
+        }
+    }
+
+    /// Reads a byte string length up to and including the `:`.
+    fn parse_byte_str_len(&mut self) -> Result<usize> {
+        let start = self.byte_offset;
+        let mut len: usize = 0;
+        loop {
+            match self
+                .next()
+                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
+            {
+                b':' => {
+                    break;
+                }
+                n @ b'0'..=b'9' => {
+                    len = len.checked_mul(10).ok_or_else(|| {
+                        Error::new(ErrorKind::IntegerOverflow, self.byte_offset())
+                    })?;
+                    len = len.checked_add(usize::from(n - b'0')).ok_or_else(|| {
+                        Error::new(ErrorKind::IntegerOverflow, self.byte_offset())
+                    })?;
+                }
+                b => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedByte(b),
+                        self.byte_offset() - 1,
+                    ))
+                }
+            }
+        }
+
+        if len > self.limits.max_byte_str_len {
+            return Err(Error::new(ErrorKind::ByteStrLenLimitExceeded, start));
+        }
+
+        Ok(len)
+    }
+
+    /// Skips over the contents of a byte string.
+    fn skip_byte_str_contents(&mut self, len: usize) -> Result<()> {
+        match self.byte_offset.checked_add(len) {
+            Some(end) if end <= self.end => {
+                self.byte_offset = end;
+                Ok(())
+            }
+            _ => {
+                self.byte_offset = self.end;
+                Err(self.end_error())
+            }
         }
     }
 }
@@ -420,10 +810,12 @@ impl<'a> SliceRead<'a> {
 impl<'a> Read<'a> for SliceRead<'a> {
     #[inline]
     fn next(&mut self) -> Option<Result<u8>> {
-        if self.byte_offset < self.slice.len() {
+        if self.byte_offset < self.end {
             let b = self.slice[self.byte_offset];
             self.byte_offset += 1;
             Some(Ok(b))
+        } else if self.end < self.slice.len() {
+            Some(Err(self.end_error()))
         } else {
             None
         }
@@ -431,8 +823,10 @@ impl<'a> Read<'a> for SliceRead<'a> {
 
     #[inline]
     fn peek(&mut self) -> Option<Result<u8>> {
-        if self.byte_offset < self.slice.len() {
+        if self.byte_offset < self.end {
             Some(Ok(self.slice[self.byte_offset]))
+        } else if self.end < self.slice.len() {
+            Some(Err(self.end_error()))
         } else {
             None
         }
@@ -444,40 +838,16 @@ impl<'a> Read<'a> for SliceRead<'a> {
     }
 
     #[inline]
-    fn parse_byte_str<'b>(&'b mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
-        let mut len: usize = 0;
-        loop {
-            match self
-                .next()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b':' => {
-                    break;
-                }
-                n @ b'0'..=b'9' => {
-                    len = len.checked_mul(10).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
-                    })?;
-                    len = len.checked_add(usize::from(n - b'0')).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
-                    })?;
-                }
-                _ => return Err(Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())),
-            }
-        }
+    fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.end = cmp::min(self.slice.len(), limits.max_bytes);
+    }
 
+    #[inline]
+    fn parse_byte_str<'b>(&'b mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        let len = self.parse_byte_str_len()?;
         let start_idx = self.byte_offset;
-        self.byte_offset += len;
-
-        let slice_len = self.slice.len();
-        if slice_len < self.byte_offset {
-            self.byte_offset = slice_len;
-            return Err(Error::new(
-                ErrorKind::EofWhileParsingValue,
-                self.byte_offset(),
-            ));
-        }
-
+        self.skip_byte_str_contents(len)?;
         Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]))
     }
 
@@ -497,7 +867,7 @@ impl<'a> Read<'a> for SliceRead<'a> {
                 })??;
             }
             b'0'..=b'9' => {}
-            _ => return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset())),
+            b => return Err(Error::new(ErrorKind::UnexpectedByte(b), self.byte_offset())),
         }
 
         match self
@@ -505,11 +875,12 @@ impl<'a> Read<'a> for SliceRead<'a> {
             .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
         {
             b'0'..=b'9' => {}
-            _ => {
-                return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset()));
+            b => {
+                return Err(Error::new(ErrorKind::UnexpectedByte(b), self.byte_offset()));
             }
         }
 
+        let digits_start = self.byte_offset;
         loop {
             match self
                 .next()
@@ -518,127 +889,266 @@ impl<'a> Read<'a> for SliceRead<'a> {
                 b'e' => {
                     return Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]));
                 }
-                b'0'..=b'9' => {}
-                _ => return Err(Error::new(ErrorKind::InvalidInteger, self.byte_offset())),
+                b'0'..=b'9' => {
+                    if self.byte_offset - digits_start > self.limits.max_integer_len {
+                        return Err(Error::new(
+                            ErrorKind::IntegerLenLimitExceeded,
+                            self.byte_offset - 1,
+                        ));
+                    }
+                }
+                b => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedByte(b),
+                        self.byte_offset() - 1,
+                    ))
+                }
             }
         }
     }
 
     fn parse_raw_byte_str<'b>(&mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
         let start_idx = self.byte_offset;
-
-        let mut len: usize = 0;
-        loop {
-            match self
-                .next()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b':' => {
-                    break;
-                }
-                n @ b'0'..=b'9' => {
-                    len = len.checked_mul(10).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
-                    })?;
-                    len = len.checked_add(usize::from(n - b'0')).ok_or_else(|| {
-                        Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())
-                    })?;
-                }
-                _ => return Err(Error::new(ErrorKind::InvalidByteStrLen, self.byte_offset())),
-            }
-        }
-        self.byte_offset += len;
-
-        let slice_len = self.slice.len();
-        if slice_len < self.byte_offset {
-            self.byte_offset = slice_len;
-            return Err(Error::new(
-                ErrorKind::EofWhileParsingValue,
-                self.byte_offset(),
-            ));
-        }
-
+        let len = self.parse_byte_str_len()?;
+        self.skip_byte_str_contents(len)?;
         Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]))
     }
 
     fn parse_raw_list<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
         let start_idx = self.byte_offset;
+        let limits = self.limits;
+        parse_raw_nested(self, buf, limits, false)?;
+        Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]))
+    }
 
-        self.next()
-            .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??;
+    fn parse_raw_dict<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        let start_idx = self.byte_offset;
+        let limits = self.limits;
+        parse_raw_nested(self, buf, limits, false)?;
+        Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]))
+    }
+}
 
-        loop {
-            match self
-                .peek()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b'e' => {
-                    self.next().ok_or_else(|| {
-                        Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
-                    })??;
-                    return Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]));
+/// Reads a list or dictionary and all of its nested values without
+/// recursion.
+///
+/// If `is_buffered` is true, the read bytes are pushed into the buffer.
+/// Otherwise, the buffer is only passed to the `parse_raw_*` methods for
+/// byte strings and integers.
+fn parse_raw_nested<'a, R>(
+    read: &mut R,
+    buf: &mut Vec<u8>,
+    limits: Limits,
+    is_buffered: bool,
+) -> Result<()>
+where
+    R: Read<'a> + ?Sized,
+{
+    /// A list or dictionary which has not ended yet.
+    struct Nested {
+        is_dict: bool,
+        /// The number of elements or entries read
+        len: usize,
+        /// If a dictionary key is expected next
+        is_key_next: bool,
+    }
+
+    let mut stack: Vec<Nested> = Vec::new();
+
+    loop {
+        let peek = read
+            .peek()
+            .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, read.byte_offset()))??;
+
+        if let Some(nested) = stack.last_mut() {
+            if peek == b'e' && (!nested.is_dict || nested.is_key_next) {
+                let b = read.next().ok_or_else(|| {
+                    Error::new(ErrorKind::EofWhileParsingValue, read.byte_offset())
+                })??;
+                if is_buffered {
+                    buf.push(b);
                 }
-                b'0'..=b'9' => {
-                    self.parse_raw_byte_str(buf)?;
+                stack.pop();
+
+                match stack.last_mut() {
+                    None => return Ok(()),
+                    Some(parent) => {
+                        parent.is_key_next = parent.is_dict;
+                        continue;
+                    }
                 }
-                b'i' => {
-                    self.parse_raw_integer(buf)?;
+            }
+
+            if nested.is_key_next || !nested.is_dict {
+                if nested.len == limits.max_len {
+                    return Err(Error::new(
+                        ErrorKind::ElementLimitExceeded,
+                        read.byte_offset(),
+                    ));
                 }
-                b'l' => {
-                    self.parse_raw_list(buf)?;
+                nested.len += 1;
+            }
+
+            if nested.is_key_next {
+                match peek {
+                    b'0'..=b'9' => {
+                        read.parse_raw_byte_str(buf)?;
+                        nested.is_key_next = false;
+                        continue;
+                    }
+                    _ => return Err(Error::new(ErrorKind::InvalidDict, read.byte_offset())),
                 }
-                b'd' => {
-                    self.parse_raw_dict(buf)?;
+            }
+        }
+
+        match peek {
+            b'0'..=b'9' => {
+                read.parse_raw_byte_str(buf)?;
+            }
+            b'i' => {
+                read.parse_raw_integer(buf)?;
+            }
+            b'l' | b'd' => {
+                if stack.len() == limits.max_depth {
+                    return Err(Error::new(
+                        ErrorKind::DepthLimitExceeded,
+                        read.byte_offset(),
+                    ));
+                }
+                let b = read.next().ok_or_else(|| {
+                    Error::new(ErrorKind::EofWhileParsingValue, read.byte_offset())
+                })??;
+                if is_buffered {
+                    buf.push(b);
                 }
-                _ => return Err(Error::new(ErrorKind::InvalidList, self.byte_offset())),
+                stack.push(Nested {
+                    is_dict: peek == b'd',
+                    len: 0,
+                    is_key_next: peek == b'd',
+                });
+                continue;
+            }
+            _ => {
+                let kind = match stack.last() {
+                    Some(Nested { is_dict: true, .. }) => ErrorKind::InvalidDict,
+                    Some(Nested { is_dict: false, .. }) => ErrorKind::InvalidList,
+                    None => ErrorKind::ExpectedSomeValue,
+                };
+                return Err(Error::new(kind, read.byte_offset()));
             }
         }
+
+        match stack.last_mut() {
+            None => return Ok(()),
+            Some(nested) => nested.is_key_next = nested.is_dict,
+        }
     }
+}
 
-    fn parse_raw_dict<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
-        let start_idx = self.byte_offset;
+/// Reads bytes which were already read from another source.
+///
+/// Used when a value has to be read in full before it can be deserialized
+/// (e.g. to find repeated dictionary keys). The bytes either borrow from the
+/// original source or from a temporary buffer. Byte offsets are reported
+/// relative to the original source.
+#[derive(Debug)]
+pub(crate) enum ReplayRead<'a, 'b> {
+    /// Bytes from the original source and their byte offset.
+    Source(SliceRead<'a>, usize),
+    /// Bytes from a temporary buffer and their byte offset.
+    Buffer(SliceRead<'b>, usize),
+}
 
-        self.next()
-            .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??;
+impl<'a, 'b> ReplayRead<'a, 'b> {
+    /// Instantiates a new reader for bytes which started at `byte_offset` in
+    /// the original source.
+    pub(crate) fn new(bytes: Ref<'a, 'b, [u8]>, byte_offset: usize) -> Self {
+        match bytes {
+            Ref::Source(bytes) => ReplayRead::Source(SliceRead::new(bytes), byte_offset),
+            Ref::Buffer(bytes) => ReplayRead::Buffer(SliceRead::new(bytes), byte_offset),
+        }
+    }
+}
 
-        loop {
-            match self
-                .peek()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b'e' => {
-                    self.next().ok_or_else(|| {
-                        Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset())
-                    })??;
-                    return Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]));
-                }
-                b'0'..=b'9' => {
-                    self.parse_raw_byte_str(buf)?;
-                }
-                _ => {
-                    return Err(Error::new(ErrorKind::InvalidDict, self.byte_offset()));
+/// Converts a reference into a temporary buffer reference.
+#[inline]
+fn into_buffer<'a, 'b: 'c, 'c>(bytes: Ref<'b, 'c, [u8]>) -> Ref<'a, 'c, [u8]> {
+    match bytes {
+        Ref::Source(bytes) => Ref::Buffer(bytes),
+        Ref::Buffer(bytes) => Ref::Buffer(bytes),
+    }
+}
+
+macro_rules! replay_parse_raw {
+    ($method:ident) => {
+        fn $method<'c>(&'c mut self, buf: &'c mut Vec<u8>) -> Result<Ref<'a, 'c, [u8]>> {
+            match self {
+                ReplayRead::Source(read, byte_offset) => {
+                    read.$method(buf).map_err(|e| e.offset_by(*byte_offset))
                 }
+                ReplayRead::Buffer(read, byte_offset) => read
+                    .$method(buf)
+                    .map(into_buffer)
+                    .map_err(|e| e.offset_by(*byte_offset)),
             }
+        }
+    };
+}
 
-            match self
-                .peek()
-                .ok_or_else(|| Error::new(ErrorKind::EofWhileParsingValue, self.byte_offset()))??
-            {
-                b'0'..=b'9' => {
-                    self.parse_raw_byte_str(buf)?;
-                }
-                b'i' => {
-                    self.parse_raw_integer(buf)?;
-                }
-                b'l' => {
-                    self.parse_raw_list(buf)?;
-                }
-                b'd' => {
-                    self.parse_raw_dict(buf)?;
-                }
-                _ => {
-                    return Err(Error::new(ErrorKind::InvalidDict, self.byte_offset()));
-                }
+impl<'a, 'b> Read<'a> for ReplayRead<'a, 'b> {
+    #[inline]
+    fn next(&mut self) -> Option<Result<u8>> {
+        match self {
+            ReplayRead::Source(read, _) => read.next(),
+            ReplayRead::Buffer(read, _) => read.next(),
+        }
+    }
+
+    #[inline]
+    fn peek(&mut self) -> Option<Result<u8>> {
+        match self {
+            ReplayRead::Source(read, _) => read.peek(),
+            ReplayRead::Buffer(read, _) => read.peek(),
+        }
+    }
+
+    #[inline]
+    fn byte_offset(&self) -> usize {
+        match self {
+            ReplayRead::Source(read, byte_offset) => byte_offset + read.byte_offset(),
+            ReplayRead::Buffer(read, byte_offset) => byte_offset + read.byte_offset(),
+        }
+    }
+
+    fn set_limits(&mut self, limits: Limits) {
+        // The bytes were already counted against the byte budget.
+        let limits = limits.max_bytes(core::usize::MAX);
+        match self {
+            ReplayRead::Source(read, _) => read.set_limits(limits),
+            ReplayRead::Buffer(read, _) => read.set_limits(limits),
+        }
+    }
+
+    replay_parse_raw!(parse_byte_str);
+    replay_parse_raw!(parse_raw_integer);
+    replay_parse_raw!(parse_raw_list);
+    replay_parse_raw!(parse_raw_dict);
+
+    fn parse_raw_byte_str<'c>(&mut self, buf: &'c mut Vec<u8>) -> Result<Ref<'a, 'c, [u8]>> {
+        match self {
+            ReplayRead::Source(read, byte_offset) => read
+                .parse_raw_byte_str(buf)
+                .map_err(|e| e.offset_by(*byte_offset)),
+            ReplayRead::Buffer(read, byte_offset) => {
+                // The returned reference cannot borrow from the reader, so the
+                // bytes are copied into the buffer.
+                let start_idx = buf.len();
+                let mut unused_buf = Vec::new();
+                let bytes = read
+                    .parse_raw_byte_str(&mut unused_buf)
+                    .map_err(|e| e.offset_by(*byte_offset))?;
+                buf.extend_from_slice(&bytes);
+                Ok(Ref::Buffer(&buf[start_idx..]))
             }
         }
     }
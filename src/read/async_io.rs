@@ -0,0 +1,377 @@
+//! [Read] implementations for async byte sources.
+//!
+//! A complete value is read asynchronously into a buffer first. The value is
+//! then deserialized from the buffer without blocking.
+
+use super::scan::{Scan, Scanner};
+use super::{Read, Ref, SliceRead};
+use crate::de::Limits;
+use crate::error::{Error, ErrorKind, Result};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::{io, vec::Vec};
+
+/// The number of bytes which are read from the source at a time.
+const CHUNK_LEN: usize = 8 * 1024;
+
+/// An async source of bytes.
+trait AsyncSource {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+}
+
+/// A future which reads bytes from an [`AsyncSource`].
+struct ReadChunk<'a, S> {
+    source: &'a mut S,
+    buf: &'a mut [u8],
+}
+
+impl<S> Future for ReadChunk<'_, S>
+where
+    S: AsyncSource,
+{
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        this.source.poll_read(cx, this.buf)
+    }
+}
+
+/// Truncates a buffer to `len` bytes when dropped.
+///
+/// Bytes are read into zeroed space at the end of the buffer. If the read
+/// fails or the future is dropped before the read completes, the space is
+/// removed again.
+struct TruncateGuard<'a> {
+    buf: &'a mut Vec<u8>,
+    len: usize,
+}
+
+impl Drop for TruncateGuard<'_> {
+    fn drop(&mut self) {
+        self.buf.truncate(self.len);
+    }
+}
+
+/// Bytes which were read from an async source.
+#[derive(Debug)]
+struct Buffer<S> {
+    source: S,
+    buf: Vec<u8>,
+    /// The index of the next byte to deserialize
+    pos: usize,
+    /// The number of bytes which were scanned
+    scanned: usize,
+    /// The byte offset of the first byte in the buffer
+    byte_offset: usize,
+    scanner: Scanner,
+    limits: Limits,
+}
+
+impl<S> Buffer<S> {
+    fn new(source: S) -> Self {
+        Buffer {
+            source,
+            buf: Vec::new(),
+            pos: 0,
+            scanned: 0,
+            byte_offset: 0,
+            scanner: Scanner::new(Limits::default(), 0),
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl<S> Buffer<S>
+where
+    S: AsyncSource,
+{
+    /// Reads more bytes from the source and returns the number of bytes read.
+    async fn read_chunk(&mut self) -> Result<usize> {
+        if self.pos > 0 {
+            self.buf.drain(..self.pos);
+            self.byte_offset += self.pos;
+            self.scanned -= self.pos;
+            self.pos = 0;
+        }
+
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK_LEN, 0);
+        let mut guard = TruncateGuard {
+            buf: &mut self.buf,
+            len,
+        };
+        let result = loop {
+            let read = ReadChunk {
+                source: &mut self.source,
+                buf: &mut guard.buf[len..],
+            };
+            match read.await {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                result => break result,
+            }
+        };
+        match result {
+            Ok(read_len) => {
+                guard.len += read_len;
+                Ok(read_len)
+            }
+            Err(err) => Err(Error::new(ErrorKind::Io(err), self.byte_offset + len)),
+        }
+    }
+
+    /// Reads until the buffer has a complete value.
+    async fn fill_value(&mut self) -> Result<()> {
+        self.scanner.set_limits(self.limits);
+        loop {
+            if self.scanned < self.buf.len() {
+                match self.scanner.scan(&self.buf[self.scanned..])? {
+                    Scan::Complete(len) => {
+                        self.scanned += len;
+                        return Ok(());
+                    }
+                    Scan::NeedMore(_) => self.scanned = self.buf.len(),
+                }
+            }
+
+            if self.read_chunk().await? == 0 {
+                return Err(Error::new(
+                    ErrorKind::EofWhileParsingValue,
+                    self.scanner.byte_offset(),
+                ));
+            }
+        }
+    }
+
+    /// Reads more bytes if every buffered byte was deserialized.
+    async fn fill_buf(&mut self) -> Result<()> {
+        if self.pos == self.buf.len() {
+            self.read_chunk().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts a reference from a [`SliceRead`] into a reference to the buffer.
+#[inline]
+fn into_slice<'a, 'b>(bytes: Ref<'a, 'b, [u8]>) -> &'a [u8] {
+    match bytes {
+        Ref::Source(bytes) => bytes,
+        Ref::Buffer(_) => unreachable!(),
+    }
+}
+
+macro_rules! buffer_parse {
+    ($method:ident) => {
+        fn $method<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+            let mut read = SliceRead {
+                slice: &self.buf,
+                byte_offset: self.pos,
+                end: self.buf.len(),
+                limits: self.limits,
+            };
+            let byte_offset = self.byte_offset;
+            let bytes = read
+                .$method(buf)
+                .map(into_slice)
+                .map_err(|e| e.offset_by(byte_offset));
+            self.pos = read.byte_offset;
+            bytes.map(Ref::Buffer)
+        }
+    };
+}
+
+impl<'a, S> Read<'a> for Buffer<S> {
+    #[inline]
+    fn next(&mut self) -> Option<Result<u8>> {
+        let b = *self.buf.get(self.pos)?;
+        self.pos += 1;
+        Some(Ok(b))
+    }
+
+    #[inline]
+    fn peek(&mut self) -> Option<Result<u8>> {
+        self.buf.get(self.pos).map(|b| Ok(*b))
+    }
+
+    #[inline]
+    fn byte_offset(&self) -> usize {
+        self.byte_offset + self.pos
+    }
+
+    #[inline]
+    fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    buffer_parse!(parse_byte_str);
+    buffer_parse!(parse_raw_integer);
+    buffer_parse!(parse_raw_list);
+    buffer_parse!(parse_raw_dict);
+
+    fn parse_raw_byte_str<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        let mut read = SliceRead {
+            slice: &self.buf,
+            byte_offset: self.pos,
+            end: self.buf.len(),
+            limits: self.limits,
+        };
+        let bytes = read
+            .parse_raw_byte_str(&mut Vec::new())
+            .map(into_slice)
+            .map_err(|e| e.offset_by(self.byte_offset))?;
+        self.pos = read.byte_offset;
+        let start_idx = buf.len();
+        buf.extend_from_slice(bytes);
+        Ok(Ref::Buffer(&buf[start_idx..]))
+    }
+}
+
+macro_rules! async_read {
+    ($(#[$attr:meta])* $name:ident, $source:ident, $bound:path) => {
+        #[derive(Debug)]
+        struct $source<R>(R);
+
+        $(#[$attr])*
+        #[derive(Debug)]
+        #[allow(clippy::module_name_repetitions)]
+        pub struct $name<R>
+        where
+            R: $bound + Unpin,
+        {
+            inner: Buffer<$source<R>>,
+        }
+
+        impl<R> $name<R>
+        where
+            R: $bound + Unpin,
+        {
+            /// Instantiates a new reader.
+            pub fn new(reader: R) -> Self {
+                $name {
+                    inner: Buffer::new($source(reader)),
+                }
+            }
+
+            /// Reads until a complete value is buffered.
+            pub(crate) async fn fill_value(&mut self) -> Result<()> {
+                self.inner.fill_value().await
+            }
+
+            /// Reads more bytes if every buffered byte was deserialized.
+            pub(crate) async fn fill_buf(&mut self) -> Result<()> {
+                self.inner.fill_buf().await
+            }
+        }
+
+        impl<'a, R> Read<'a> for $name<R>
+        where
+            R: $bound + Unpin,
+        {
+            #[inline]
+            fn next(&mut self) -> Option<Result<u8>> {
+                self.inner.next()
+            }
+
+            #[inline]
+            fn peek(&mut self) -> Option<Result<u8>> {
+                self.inner.peek()
+            }
+
+            #[inline]
+            fn byte_offset(&self) -> usize {
+                Read::byte_offset(&self.inner)
+            }
+
+            #[inline]
+            fn set_limits(&mut self, limits: Limits) {
+                self.inner.set_limits(limits);
+            }
+
+            fn parse_byte_str<'b>(
+                &'b mut self,
+                buf: &'b mut Vec<u8>,
+            ) -> Result<Ref<'a, 'b, [u8]>> {
+                self.inner.parse_byte_str(buf)
+            }
+
+            fn parse_raw_integer<'b>(
+                &'b mut self,
+                buf: &'b mut Vec<u8>,
+            ) -> Result<Ref<'a, 'b, [u8]>> {
+                self.inner.parse_raw_integer(buf)
+            }
+
+            fn parse_raw_byte_str<'b>(
+                &mut self,
+                buf: &'b mut Vec<u8>,
+            ) -> Result<Ref<'a, 'b, [u8]>> {
+                self.inner.parse_raw_byte_str(buf)
+            }
+
+            fn parse_raw_list<'b>(
+                &'b mut self,
+                buf: &'b mut Vec<u8>,
+            ) -> Result<Ref<'a, 'b, [u8]>> {
+                self.inner.parse_raw_list(buf)
+            }
+
+            fn parse_raw_dict<'b>(
+                &'b mut self,
+                buf: &'b mut Vec<u8>,
+            ) -> Result<Ref<'a, 'b, [u8]>> {
+                self.inner.parse_raw_dict(buf)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "tokio")]
+async_read!(
+    /// A wrapper to implement this crate's [Read] trait for
+    /// [`tokio::io::AsyncRead`] trait implementations.
+    ///
+    /// Bytes are read asynchronously until a complete value is buffered. See
+    /// [`Deserializer::fill_value()`][crate::Deserializer::fill_value].
+    TokioIoRead,
+    TokioSource,
+    tokio::io::AsyncRead
+);
+
+#[cfg(feature = "tokio")]
+impl<R> AsyncSource for TokioSource<R>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut buf = tokio::io::ReadBuf::new(buf);
+        match Pin::new(&mut self.0).poll_read(cx, &mut buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(buf.filled().len())),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+async_read!(
+    /// A wrapper to implement this crate's [Read] trait for
+    /// [`futures_io::AsyncRead`] trait implementations.
+    ///
+    /// Bytes are read asynchronously until a complete value is buffered. See
+    /// [`Deserializer::fill_value()`][crate::Deserializer::fill_value].
+    FuturesIoRead,
+    FuturesSource,
+    futures_io::AsyncRead
+);
+
+#[cfg(feature = "futures-io")]
+impl<R> AsyncSource for FuturesSource<R>
+where
+    R: futures_io::AsyncRead + Unpin,
+{
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
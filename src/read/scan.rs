@@ -0,0 +1,253 @@
+//! Incremental scanning for the end of a value.
+
+use crate::de::Limits;
+use crate::error::{Error, ErrorKind, Result};
+use core::cmp;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// The result of scanning a chunk of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scan {
+    /// The value ended after the given number of bytes in the chunk.
+    Complete(usize),
+    /// All of the bytes in the chunk were scanned, and at least the given
+    /// number of bytes are needed to complete the value.
+    NeedMore(usize),
+}
+
+/// What the next scanned byte is expected to be.
+#[derive(Debug, Clone, Copy)]
+enum State {
+    /// The start of a value or the end of a list or dictionary
+    Value,
+    /// After the `i`
+    IntStart,
+    /// After the `-`
+    IntSign,
+    /// After `len` digits
+    IntDigits { len: usize },
+    /// The length of a byte string which started at `start`
+    ByteStrLen { start: usize, len: usize },
+    /// The remaining contents of a byte string
+    ByteStr { remaining: usize },
+}
+
+/// A list or dictionary which has not ended yet.
+#[derive(Debug)]
+struct Nested {
+    is_dict: bool,
+    /// The number of elements or entries scanned
+    len: usize,
+    /// If a dictionary key is expected next
+    is_key_next: bool,
+}
+
+/// Finds the end of a value which is split across multiple chunks of bytes.
+///
+/// The value is checked for the same syntax errors and limits as the
+/// `parse_raw_*` methods of a [`Read`][super::Read] implementation, but the
+/// scanned bytes are not kept. Once a value is complete, the scanner starts
+/// on the next value.
+#[derive(Debug)]
+pub(crate) struct Scanner {
+    limits: Limits,
+    stack: Vec<Nested>,
+    state: State,
+    byte_offset: usize,
+}
+
+impl Scanner {
+    /// Instantiates a new scanner which starts at `byte_offset`.
+    pub(crate) fn new(limits: Limits, byte_offset: usize) -> Self {
+        Scanner {
+            limits,
+            stack: Vec::new(),
+            state: State::Value,
+            byte_offset,
+        }
+    }
+
+    /// Sets the limits for the bytes which are scanned afterwards.
+    pub(crate) fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns the byte offset of the next byte to scan.
+    pub(crate) fn byte_offset(&self) -> usize {
+        self.byte_offset
+    }
+
+    /// Scans the next chunk of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not valid or if a limit is exceeded.
+    pub(crate) fn scan(&mut self, bytes: &[u8]) -> Result<Scan> {
+        let budget = self.limits.max_bytes.saturating_sub(self.byte_offset);
+        let (bytes, is_over_budget) = if bytes.len() > budget {
+            (&bytes[..budget], true)
+        } else {
+            (bytes, false)
+        };
+
+        let mut idx = 0;
+        while idx < bytes.len() {
+            let is_complete = if let State::ByteStr { remaining } = self.state {
+                let len = cmp::min(remaining, bytes.len() - idx);
+                idx += len;
+                self.byte_offset += len;
+                if len == remaining {
+                    self.end_value()
+                } else {
+                    self.state = State::ByteStr {
+                        remaining: remaining - len,
+                    };
+                    false
+                }
+            } else {
+                let is_complete = self.scan_byte(bytes[idx])?;
+                idx += 1;
+                is_complete
+            };
+
+            if is_complete {
+                return Ok(Scan::Complete(idx));
+            }
+        }
+
+        if is_over_budget {
+            return Err(Error::new(ErrorKind::ByteLimitExceeded, self.byte_offset));
+        }
+
+        match self.state {
+            State::ByteStr { remaining } => Ok(Scan::NeedMore(remaining)),
+            _ => Ok(Scan::NeedMore(1)),
+        }
+    }
+
+    /// Scans a single byte and returns true if the value is complete.
+    fn scan_byte(&mut self, b: u8) -> Result<bool> {
+        let byte_offset = self.byte_offset;
+        match self.state {
+            State::Value => {
+                if let Some(nested) = self.stack.last_mut() {
+                    if b == b'e' && (!nested.is_dict || nested.is_key_next) {
+                        self.stack.pop();
+                        self.byte_offset += 1;
+                        return Ok(self.end_value());
+                    }
+
+                    if nested.is_key_next || !nested.is_dict {
+                        if nested.len == self.limits.max_len {
+                            return Err(Error::new(ErrorKind::ElementLimitExceeded, byte_offset));
+                        }
+                        nested.len += 1;
+                    }
+
+                    if nested.is_key_next && !b.is_ascii_digit() {
+                        return Err(Error::new(ErrorKind::InvalidDict, byte_offset));
+                    }
+                }
+
+                match b {
+                    b'0'..=b'9' => {
+                        self.state = State::ByteStrLen {
+                            start: byte_offset,
+                            len: usize::from(b - b'0'),
+                        };
+                    }
+                    b'i' => self.state = State::IntStart,
+                    b'l' | b'd' => {
+                        if self.stack.len() == self.limits.max_depth {
+                            return Err(Error::new(ErrorKind::DepthLimitExceeded, byte_offset));
+                        }
+                        self.stack.push(Nested {
+                            is_dict: b == b'd',
+                            len: 0,
+                            is_key_next: b == b'd',
+                        });
+                    }
+                    _ => {
+                        let kind = match self.stack.last() {
+                            Some(Nested { is_dict: true, .. }) => ErrorKind::InvalidDict,
+                            Some(Nested { is_dict: false, .. }) => ErrorKind::InvalidList,
+                            None => ErrorKind::ExpectedSomeValue,
+                        };
+                        return Err(Error::new(kind, byte_offset));
+                    }
+                }
+            }
+            State::IntStart => match b {
+                b'-' => self.state = State::IntSign,
+                b'0'..=b'9' => self.state = self.int_digits(0, byte_offset)?,
+                _ => return Err(Error::new(ErrorKind::UnexpectedByte(b), byte_offset)),
+            },
+            State::IntSign => match b {
+                b'0'..=b'9' => self.state = self.int_digits(0, byte_offset)?,
+                _ => return Err(Error::new(ErrorKind::UnexpectedByte(b), byte_offset)),
+            },
+            State::IntDigits { len } => match b {
+                b'e' => {
+                    self.byte_offset += 1;
+                    return Ok(self.end_value());
+                }
+                b'0'..=b'9' => self.state = self.int_digits(len, byte_offset)?,
+                _ => return Err(Error::new(ErrorKind::UnexpectedByte(b), byte_offset)),
+            },
+            State::ByteStrLen { start, len } => match b {
+                b':' => {
+                    if len > self.limits.max_byte_str_len {
+                        return Err(Error::new(ErrorKind::ByteStrLenLimitExceeded, start));
+                    }
+                    self.byte_offset += 1;
+                    if len == 0 {
+                        return Ok(self.end_value());
+                    }
+                    self.state = State::ByteStr { remaining: len };
+                    return Ok(false);
+                }
+                b'0'..=b'9' => {
+                    let len = len
+                        .checked_mul(10)
+                        .and_then(|len| len.checked_add(usize::from(b - b'0')))
+                        .ok_or_else(|| Error::new(ErrorKind::IntegerOverflow, byte_offset + 1))?;
+                    self.state = State::ByteStrLen { start, len };
+                }
+                _ => return Err(Error::new(ErrorKind::UnexpectedByte(b), byte_offset)),
+            },
+            State::ByteStr { .. } => unreachable!(),
+        }
+
+        self.byte_offset += 1;
+        Ok(false)
+    }
+
+    /// Returns the state after another digit of an integer which already had
+    /// `len` digits.
+    fn int_digits(&self, len: usize, byte_offset: usize) -> Result<State> {
+        if len == self.limits.max_integer_len {
+            return Err(Error::new(ErrorKind::IntegerLenLimitExceeded, byte_offset));
+        }
+        Ok(State::IntDigits { len: len + 1 })
+    }
+
+    /// Marks the end of a value and returns true if it is the top-level
+    /// value.
+    fn end_value(&mut self) -> bool {
+        self.state = State::Value;
+        match self.stack.last_mut() {
+            None => true,
+            Some(nested) => {
+                if nested.is_dict {
+                    nested.is_key_next = !nested.is_key_next;
+                }
+                false
+            }
+        }
+    }
+}
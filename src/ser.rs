@@ -1,24 +1,32 @@
 //! Serializes Bencode data.
 
 use crate::error::{Error, ErrorKind, Result};
+use crate::raw::RAW_VALUE_TOKEN;
+use crate::value::{to_value, Number, NUMBER_TOKEN};
+use core::fmt;
 use serde::{ser, Serialize};
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::{collections::BTreeMap, vec::Vec};
+use alloc::vec::Vec;
 
 #[cfg(feature = "std")]
-use std::{collections::BTreeMap, io, vec::Vec};
+use std::{io, vec::Vec};
 
 use crate::write::Write;
 
 /// Serializes an instance of `T` into the writer `W` as `Bencode` data.
 ///
+/// Each dictionary or struct which is not nested in another dictionary is
+/// serialized into a buffer in memory before it is written, so that its keys
+/// can be sorted. See
+/// [`IoWrite`][crate::write::IoWrite].
+///
 /// # Errors
 ///
 /// Serialization can fail if `T`'s implementation of
 /// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
 /// unsupported types for serialization, or if `T` contains a map with
-/// non-string keys.
+/// unsupported key types.
 #[cfg(feature = "std")]
 #[inline]
 pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
@@ -31,6 +39,78 @@ where
     Ok(())
 }
 
+/// Serializes an instance of `T` into the [`embedded_io::Write`] writer `W`
+/// as `Bencode` data.
+///
+/// Each dictionary or struct which is not nested in another dictionary is
+/// serialized into a buffer in memory before it is written, so that its keys
+/// can be sorted. See
+/// [`EmbeddedIoWrite`][crate::write::EmbeddedIoWrite].
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of
+/// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+/// unsupported types for serialization, if `T` contains a map with
+/// unsupported key types, or if the bytes cannot be written.
+#[cfg(feature = "embedded-io")]
+#[inline]
+pub fn to_embedded_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: embedded_io::Write,
+    T: ?Sized + Serialize,
+{
+    let mut ser = Serializer::new(crate::write::EmbeddedIoWrite::new(writer));
+    value.serialize(&mut ser)?;
+    Ok(())
+}
+
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+macro_rules! to_async_writer {
+    ($(#[$attr:meta])* $name:ident, $bound:path, $sink:ident) => {
+        $(#[$attr])*
+        ///
+        /// The value is not streamed. The complete encoding is serialized into
+        /// a buffer in memory first, and only then is the buffer written
+        /// without blocking. Nothing is written if serialization fails. The
+        /// writer is not flushed.
+        ///
+        /// # Errors
+        ///
+        /// Serialization can fail if `T`'s implementation of
+        /// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+        /// unsupported types for serialization, if `T` contains a map with
+        /// unsupported key types, or if the bytes cannot be written.
+        pub async fn $name<W, T>(writer: W, value: &T) -> Result<()>
+        where
+            W: $bound + Unpin,
+            T: ?Sized + Serialize,
+        {
+            let buf = to_vec(value)?;
+            let mut sink = crate::write::async_io::$sink(writer);
+            crate::write::async_io::write_all(&mut sink, &buf).await
+        }
+    };
+}
+
+#[cfg(all(feature = "std", feature = "tokio"))]
+to_async_writer!(
+    /// Serializes an instance of `T` as `Bencode` data into a buffer and
+    /// writes the buffer to the [`tokio::io::AsyncWrite`] writer `W`.
+    to_tokio_writer,
+    tokio::io::AsyncWrite,
+    TokioSink
+);
+
+#[cfg(all(feature = "std", feature = "futures-io"))]
+to_async_writer!(
+    /// Serializes an instance of `T` as `Bencode` data into a buffer and
+    /// writes the buffer to the [`futures_io::AsyncWrite`] writer `W`.
+    to_futures_writer,
+    futures_io::AsyncWrite,
+    FuturesSink
+);
+
 /// Serializes an instance of `T` into a new [Vec] as `Bencode` data.
 ///
 /// # Errors
@@ -38,7 +118,7 @@ where
 /// Serialization can fail if `T`'s implemenation of
 /// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
 /// unsupported types for serialization, or if `T` contains a map with
-/// non-string keys.
+/// unsupported key types.
 #[inline]
 pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
 where
@@ -50,10 +130,40 @@ where
     Ok(writer)
 }
 
+/// Serializes an instance of `T` into the beginning of a byte slice as
+/// `Bencode` data and returns the number of bytes written.
+///
+/// Nothing is allocated if every dictionary's keys are serialized in sorted
+/// order, which is usually the case for structs with fields declared in
+/// sorted order.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of
+/// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+/// unsupported types for serialization, if `T` contains a map with
+/// unsupported key types, or if the encoded value does not fit in the slice.
+#[inline]
+pub fn to_slice<T>(slice: &mut [u8], value: &T) -> Result<usize>
+where
+    T: ?Sized + Serialize,
+{
+    let mut ser = Serializer::new(crate::write::SliceWrite::new(slice));
+    value.serialize(&mut ser)?;
+    Ok(ser.into_inner().bytes_written())
+}
+
 /// A `Bencode` Serializer for types which implement [Serialize][serde::ser::Serialize].
 #[derive(Debug)]
 pub struct Serializer<W> {
     writer: W,
+    /// Buffered bytes of a dictionary if the writer cannot return the
+    /// written bytes
+    buf: Vec<u8>,
+    /// The number of nested dictionaries which are written to the buffer
+    buffered_dicts: usize,
+    /// If `bool` values are serialized as the integers 0 and 1
+    bool_as_int: bool,
 }
 
 impl<W> Serializer<W>
@@ -62,7 +172,90 @@ where
 {
     /// Constructs a Serializer with an [Write] target.
     pub fn new(writer: W) -> Self {
-        Serializer { writer }
+        Serializer {
+            writer,
+            buf: Vec::new(),
+            buffered_dicts: 0,
+            bool_as_int: false,
+        }
+    }
+
+    /// Sets if `bool` values are serialized as the integers `i0e` and `i1e`.
+    ///
+    /// Bencode does not have a boolean type, but many applications encode
+    /// flags (e.g. the `private` key in a metainfo file) as 0 or 1.
+    ///
+    /// By default, serializing a `bool` returns an
+    /// [`ErrorKind::UnsupportedType`] error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::Serialize as _;
+    /// use bt_bencode::Serializer;
+    ///
+    /// let mut ser = Serializer::new(Vec::new());
+    /// ser.set_bool_as_int(true);
+    /// vec![true, false].serialize(&mut ser)?;
+    /// assert_eq!(ser.into_inner(), b"li1ei0ee");
+    /// # Ok::<_, bt_bencode::Error>(())
+    /// ```
+    pub fn set_bool_as_int(&mut self, bool_as_int: bool) {
+        self.bool_as_int = bool_as_int;
+    }
+
+    #[inline]
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        if self.buffered_dicts == 0 {
+            self.writer.write_all(bytes)
+        } else {
+            self.buf.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    /// Returns all of the written bytes which can be rewritten.
+    #[inline]
+    fn written(&self) -> &[u8] {
+        if self.buffered_dicts == 0 {
+            self.writer.written().unwrap_or(&[])
+        } else {
+            &self.buf
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if self.buffered_dicts == 0 {
+            self.writer.truncate(len);
+        } else {
+            self.buf.truncate(len);
+        }
+    }
+
+    /// Starts a dictionary.
+    ///
+    /// If the writer cannot return the written bytes, the dictionary is
+    /// written to a buffer so its entries can be sorted.
+    fn begin_dict(&mut self) -> Result<()> {
+        if self.buffered_dicts > 0 || self.writer.written().is_none() {
+            self.buffered_dicts += 1;
+        }
+        self.write_all(b"d")
+    }
+
+    /// Ends a dictionary and writes the buffer if it was the outermost
+    /// buffered dictionary.
+    fn end_dict(&mut self) -> Result<()> {
+        self.write_all(b"e")?;
+        if self.buffered_dicts > 0 {
+            self.buffered_dicts -= 1;
+            if self.buffered_dicts == 0 {
+                self.writer.write_all(&self.buf)?;
+                self.buf.clear();
+            }
+        }
+        Ok(())
     }
 }
 
@@ -86,17 +279,21 @@ where
     type Ok = ();
     type Error = Error;
 
-    type SerializeSeq = Self;
-    type SerializeTuple = Self;
-    type SerializeTupleStruct = Self;
-    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeSeq = SerializeList<'a, W>;
+    type SerializeTuple = SerializeList<'a, W>;
+    type SerializeTupleStruct = SerializeList<'a, W>;
+    type SerializeTupleVariant = SerializeList<'a, W>;
     type SerializeMap = SerializeMap<'a, W>;
     type SerializeStruct = SerializeMap<'a, W>;
-    type SerializeStructVariant = ser::Impossible<(), Error>;
+    type SerializeStructVariant = SerializeMap<'a, W>;
 
     #[inline]
-    fn serialize_bool(self, _value: bool) -> Result<()> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_bool(self, value: bool) -> Result<()> {
+        if self.bool_as_int {
+            self.serialize_u64(u64::from(value))
+        } else {
+            Err(Error::with_kind(ErrorKind::UnsupportedType))
+        }
     }
 
     #[inline]
@@ -116,10 +313,9 @@ where
 
     #[inline]
     fn serialize_i64(self, value: i64) -> Result<()> {
-        self.writer.write_all(b"i")?;
-        self.writer
-            .write_all(itoa::Buffer::new().format(value).as_bytes())?;
-        self.writer.write_all(b"e")?;
+        self.write_all(b"i")?;
+        self.write_all(itoa::Buffer::new().format(value).as_bytes())?;
+        self.write_all(b"e")?;
         Ok(())
     }
 
@@ -140,10 +336,25 @@ where
 
     #[inline]
     fn serialize_u64(self, value: u64) -> Result<()> {
-        self.writer.write_all(b"i")?;
-        self.writer
-            .write_all(itoa::Buffer::new().format(value).as_bytes())?;
-        self.writer.write_all(b"e")?;
+        self.write_all(b"i")?;
+        self.write_all(itoa::Buffer::new().format(value).as_bytes())?;
+        self.write_all(b"e")?;
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_i128(self, value: i128) -> Result<()> {
+        self.write_all(b"i")?;
+        self.write_all(itoa::Buffer::new().format(value).as_bytes())?;
+        self.write_all(b"e")?;
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_u128(self, value: u128) -> Result<()> {
+        self.write_all(b"i")?;
+        self.write_all(itoa::Buffer::new().format(value).as_bytes())?;
+        self.write_all(b"e")?;
         Ok(())
     }
 
@@ -165,18 +376,16 @@ where
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<()> {
-        self.writer
-            .write_all(itoa::Buffer::new().format(value.len()).as_bytes())?;
-        self.writer.write_all(b":")?;
-        self.writer.write_all(value.as_bytes())
+        self.write_all(itoa::Buffer::new().format(value.len()).as_bytes())?;
+        self.write_all(b":")?;
+        self.write_all(value.as_bytes())
     }
 
     #[inline]
     fn serialize_bytes(self, value: &[u8]) -> Result<()> {
-        self.writer
-            .write_all(itoa::Buffer::new().format(value.len()).as_bytes())?;
-        self.writer.write_all(b":")?;
-        self.writer.write_all(value)
+        self.write_all(itoa::Buffer::new().format(value.len()).as_bytes())?;
+        self.write_all(b":")?;
+        self.write_all(value)
     }
 
     #[inline]
@@ -207,16 +416,30 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<()> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        self.serialize_str(variant)
     }
 
     #[inline]
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
+        if name == RAW_VALUE_TOKEN {
+            return value.serialize(RawValueSerializer { ser: self });
+        }
+        if name == NUMBER_TOKEN {
+            return match to_value(value)?.as_str().map(str::parse) {
+                Some(Ok(Number::Big(digits))) => {
+                    self.write_all(b"i")?;
+                    self.write_all(digits.as_bytes())?;
+                    self.write_all(b"e")
+                }
+                Some(Ok(number)) => number.serialize(self),
+                _ => Err(Error::with_kind(ErrorKind::InvalidInteger)),
+            };
+        }
         value.serialize(self)
     }
 
@@ -225,19 +448,23 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        // A dictionary with a single key does not need to be sorted.
+        self.write_all(b"d")?;
+        self.serialize_str(variant)?;
+        value.serialize(&mut *self)?;
+        self.write_all(b"e")
     }
 
     #[inline]
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.writer.write_all(b"l")?;
-        Ok(self)
+        self.write_all(b"l")?;
+        Ok(SerializeList::new(self))
     }
 
     #[inline]
@@ -259,15 +486,18 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        self.write_all(b"d")?;
+        self.serialize_str(variant)?;
+        self.write_all(b"l")?;
+        Ok(SerializeList::new(self))
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.writer.write_all(b"d")?;
+        self.begin_dict()?;
         Ok(SerializeMap::new(self))
     }
 
@@ -281,10 +511,12 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
+        variant: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        self.write_all(b"d")?;
+        self.serialize_str(variant)?;
+        self.serialize_map(Some(len))
     }
 
     fn is_human_readable(&self) -> bool {
@@ -292,7 +524,38 @@ where
     }
 }
 
-impl<'a, W> ser::SerializeSeq for &'a mut Serializer<W>
+/// A serializer for writing list data.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct SerializeList<'a, W> {
+    ser: &'a mut Serializer<W>,
+    /// The index of the next element
+    index: usize,
+}
+
+impl<'a, W> SerializeList<'a, W>
+where
+    W: Write,
+{
+    #[inline]
+    fn new(ser: &'a mut Serializer<W>) -> Self {
+        SerializeList { ser, index: 0 }
+    }
+
+    #[inline]
+    fn write_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let index = self.index;
+        self.index += 1;
+        value
+            .serialize(&mut *self.ser)
+            .map_err(|err| err.with_path_index(index))
+    }
+}
+
+impl<'a, W> ser::SerializeSeq for SerializeList<'a, W>
 where
     W: Write,
 {
@@ -304,17 +567,17 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut **self)
+        self.write_element(value)
     }
 
     #[inline]
     fn end(self) -> Result<()> {
-        self.writer.write_all(b"e")?;
+        self.ser.write_all(b"e")?;
         Ok(())
     }
 }
 
-impl<'a, W> ser::SerializeTuple for &'a mut Serializer<W>
+impl<'a, W> ser::SerializeTuple for SerializeList<'a, W>
 where
     W: Write,
 {
@@ -326,17 +589,39 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut **self)
+        self.write_element(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.ser.write_all(b"e")?;
+        Ok(())
+    }
+}
+
+impl<'a, W> ser::SerializeTupleStruct for SerializeList<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.write_element(value)
     }
 
     #[inline]
     fn end(self) -> Result<()> {
-        self.writer.write_all(b"e")?;
+        self.ser.write_all(b"e")?;
         Ok(())
     }
 }
 
-impl<'a, W> ser::SerializeTupleStruct for &'a mut Serializer<W>
+impl<'a, W> ser::SerializeTupleVariant for SerializeList<'a, W>
 where
     W: Write,
 {
@@ -348,23 +633,31 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut **self)
+        self.write_element(value)
     }
 
     #[inline]
     fn end(self) -> Result<()> {
-        self.writer.write_all(b"e")?;
+        self.ser.write_all(b"ee")?;
         Ok(())
     }
 }
 
 /// A serializer for writing map data.
+///
+/// Entries are written directly. If a key is not greater than the previous
+/// key, the entries are sorted when the map ends.
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct SerializeMap<'a, W> {
     ser: &'a mut Serializer<W>,
-    entries: BTreeMap<Vec<u8>, Vec<u8>>,
-    current_key: Option<Vec<u8>>,
+    /// The byte offset where the entries start
+    start: usize,
+    /// The byte offsets of the contents of the previous key
+    last_key: Option<(usize, usize)>,
+    /// The byte offset of the key if a value is expected next
+    current_key: Option<usize>,
+    is_sorted: bool,
 }
 
 impl<'a, W> SerializeMap<'a, W>
@@ -373,11 +666,30 @@ where
 {
     #[inline]
     fn new(ser: &'a mut Serializer<W>) -> Self {
+        let start = ser.written().len();
         SerializeMap {
             ser,
-            entries: BTreeMap::new(),
+            start,
+            last_key: None,
             current_key: None,
+            is_sorted: true,
+        }
+    }
+
+    /// Checks if the key which was written at `key_start` is greater than
+    /// the previous key.
+    ///
+    /// Returns the byte offsets of the key's contents.
+    fn check_key_order(&mut self, key_start: usize) -> (usize, usize) {
+        let written = self.ser.written();
+        let key = byte_str_at(written, key_start);
+        if let Some(last_key) = self.last_key {
+            if written[key.0..key.1] <= written[last_key.0..last_key.1] {
+                self.is_sorted = false;
+            }
         }
+        self.last_key = Some(key);
+        key
     }
 
     #[inline]
@@ -386,12 +698,83 @@ where
             return Err(Error::with_kind(ErrorKind::KeyWithoutValue));
         }
 
-        for (k, v) in &self.entries {
-            ser::Serializer::serialize_bytes(&mut *self.ser, k.as_ref())?;
-            self.ser.writer.write_all(v)?;
+        if !self.is_sorted {
+            self.sort_entries()?;
         }
 
-        Ok(())
+        self.ser.end_dict()
+    }
+
+    /// Sorts the written entries by key.
+    ///
+    /// If a key is repeated, only the last entry for the key is kept.
+    fn sort_entries(&mut self) -> Result<()> {
+        let written = self.ser.written();
+
+        // The byte offsets of the key's contents and the end of each entry
+        let mut entries = Vec::new();
+        let mut idx = self.start;
+        while idx < written.len() {
+            let (key_start, key_end) = byte_str_at(written, idx);
+            let end = value_end_at(written, key_end);
+            entries.push((idx, key_start, key_end, end));
+            idx = end;
+        }
+        entries.sort_by(|a, b| written[a.1..a.2].cmp(&written[b.1..b.2]));
+
+        let mut sorted = Vec::with_capacity(written.len() - self.start);
+        for (pos, entry) in entries.iter().enumerate() {
+            if let Some(next) = entries.get(pos + 1) {
+                if written[entry.1..entry.2] == written[next.1..next.2] {
+                    continue;
+                }
+            }
+            sorted.extend_from_slice(&written[entry.0..entry.3]);
+        }
+
+        self.ser.truncate(self.start);
+        self.ser.write_all(&sorted)
+    }
+}
+
+/// Returns the byte offsets of the contents of the encoded byte string which
+/// starts at `idx`.
+fn byte_str_at(bytes: &[u8], mut idx: usize) -> (usize, usize) {
+    let mut len = 0;
+    while bytes[idx] != b':' {
+        len = len * 10 + usize::from(bytes[idx] - b'0');
+        idx += 1;
+    }
+    (idx + 1, idx + 1 + len)
+}
+
+/// Returns the byte offset after the encoded value which starts at `idx`.
+///
+/// The value must have been written by the serializer.
+fn value_end_at(bytes: &[u8], mut idx: usize) -> usize {
+    let mut depth = 0_usize;
+    loop {
+        match bytes[idx] {
+            b'i' => {
+                while bytes[idx] != b'e' {
+                    idx += 1;
+                }
+                idx += 1;
+            }
+            b'l' | b'd' => {
+                depth += 1;
+                idx += 1;
+                continue;
+            }
+            b'e' => {
+                depth -= 1;
+                idx += 1;
+            }
+            _ => idx = byte_str_at(bytes, idx).1,
+        }
+        if depth == 0 {
+            return idx;
+        }
     }
 }
 
@@ -410,7 +793,11 @@ where
         if self.current_key.is_some() {
             return Err(Error::with_kind(ErrorKind::KeyWithoutValue));
         }
-        self.current_key = Some(key.serialize(&mut MapKeySerializer {})?);
+        let key_start = self.ser.written().len();
+        key.serialize(MapKeySerializer {
+            ser: &mut *self.ser,
+        })?;
+        self.current_key = Some(key_start);
         Ok(())
     }
 
@@ -419,22 +806,24 @@ where
     where
         T: ?Sized + Serialize,
     {
-        let key = self
+        let key_start = self
             .current_key
             .take()
             .ok_or_else(|| Error::with_kind(ErrorKind::ValueWithoutKey))?;
-        let buf: Vec<u8> = Vec::new();
-        let mut ser = Serializer::new(buf);
-        value.serialize(&mut ser)?;
-        self.entries.insert(key, ser.into_inner());
-        Ok(())
+        if is_none(value) {
+            self.ser.truncate(key_start);
+            return Ok(());
+        }
+        let key = self.check_key_order(key_start);
+        value.serialize(&mut *self.ser).map_err(|err| {
+            let written = self.ser.written();
+            err.with_path_key(&written[key.0..key.1])
+        })
     }
 
     #[inline]
     fn end(mut self) -> Result<()> {
-        self.end_map()?;
-        self.ser.writer.write_all(b"e")?;
-        Ok(())
+        self.end_map()
     }
 }
 
@@ -450,103 +839,136 @@ where
     where
         T: ?Sized + Serialize,
     {
-        let key = key.serialize(&mut MapKeySerializer {})?;
+        if is_none(value) {
+            return Ok(());
+        }
 
-        let buf: Vec<u8> = Vec::new();
-        let mut ser = Serializer::new(buf);
-        value.serialize(&mut ser)?;
-        self.entries.insert(key, ser.into_inner());
-        Ok(())
+        let key_start = self.ser.written().len();
+        ser::Serializer::serialize_str(&mut *self.ser, key)?;
+        self.check_key_order(key_start);
+        value
+            .serialize(&mut *self.ser)
+            .map_err(|err| err.with_path_key(key.as_bytes()))
+    }
+
+    #[inline]
+    fn end(mut self) -> Result<()> {
+        self.end_map()
+    }
+}
+
+impl<'a, W> ser::SerializeStructVariant for SerializeMap<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeStruct::serialize_field(self, key, value)
     }
 
     #[inline]
     fn end(mut self) -> Result<()> {
         self.end_map()?;
-        self.ser.writer.write_all(b"e")?;
-        Ok(())
+        self.ser.write_all(b"e")
     }
 }
 
-struct MapKeySerializer;
+/// Serializes a dictionary key as a byte string.
+struct MapKeySerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+}
 
-impl<'a> ser::Serializer for &'a mut MapKeySerializer {
-    type Ok = Vec<u8>;
+impl<'a, W> ser::Serializer for MapKeySerializer<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
     type Error = Error;
 
-    type SerializeSeq = ser::Impossible<Vec<u8>, Error>;
-    type SerializeTuple = ser::Impossible<Vec<u8>, Error>;
-    type SerializeTupleStruct = ser::Impossible<Vec<u8>, Error>;
-    type SerializeTupleVariant = ser::Impossible<Vec<u8>, Error>;
-    type SerializeMap = ser::Impossible<Vec<u8>, Error>;
-    type SerializeStruct = ser::Impossible<Vec<u8>, Error>;
-    type SerializeStructVariant = ser::Impossible<Vec<u8>, Error>;
+    type SerializeSeq = ser::Impossible<(), Error>;
+    type SerializeTuple = ser::Impossible<(), Error>;
+    type SerializeTupleStruct = ser::Impossible<(), Error>;
+    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeMap = ser::Impossible<(), Error>;
+    type SerializeStruct = ser::Impossible<(), Error>;
+    type SerializeStructVariant = ser::Impossible<(), Error>;
 
-    fn serialize_bool(self, _value: bool) -> Result<Vec<u8>> {
+    fn serialize_bool(self, _value: bool) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_i8(self, _value: i8) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i8(self, value: i8) -> Result<()> {
+        self.serialize_i64(i64::from(value))
     }
 
-    fn serialize_i16(self, _value: i16) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i16(self, value: i16) -> Result<()> {
+        self.serialize_i64(i64::from(value))
     }
 
-    fn serialize_i32(self, _value: i32) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i32(self, value: i32) -> Result<()> {
+        self.serialize_i64(i64::from(value))
     }
 
-    fn serialize_i64(self, _value: i64) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i64(self, value: i64) -> Result<()> {
+        self.serialize_str(itoa::Buffer::new().format(value))
     }
 
-    fn serialize_u8(self, _value: u8) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u8(self, value: u8) -> Result<()> {
+        self.serialize_u64(u64::from(value))
     }
 
-    fn serialize_u16(self, _value: u16) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u16(self, value: u16) -> Result<()> {
+        self.serialize_u64(u64::from(value))
     }
 
-    fn serialize_u32(self, _value: u32) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u32(self, value: u32) -> Result<()> {
+        self.serialize_u64(u64::from(value))
     }
 
-    fn serialize_u64(self, _value: u64) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u64(self, value: u64) -> Result<()> {
+        self.serialize_str(itoa::Buffer::new().format(value))
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<()> {
+        self.serialize_str(itoa::Buffer::new().format(value))
     }
 
-    fn serialize_f32(self, _value: f32) -> Result<Vec<u8>> {
+    fn serialize_u128(self, value: u128) -> Result<()> {
+        self.serialize_str(itoa::Buffer::new().format(value))
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_f64(self, _value: f64) -> Result<Vec<u8>> {
+    fn serialize_f64(self, _value: f64) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_char(self, value: char) -> Result<Vec<u8>> {
+    fn serialize_char(self, value: char) -> Result<()> {
         let mut buf = [0; 4];
         self.serialize_str(value.encode_utf8(&mut buf))
     }
 
-    fn serialize_str(self, value: &str) -> Result<Vec<u8>> {
-        let mut buf: Vec<u8> = Vec::with_capacity(value.len());
-        buf.extend_from_slice(value.as_bytes());
-        Ok(buf)
+    fn serialize_str(self, value: &str) -> Result<()> {
+        ser::Serializer::serialize_str(self.ser, value)
     }
 
-    fn serialize_bytes(self, value: &[u8]) -> Result<Vec<u8>> {
-        let mut buf: Vec<u8> = Vec::with_capacity(value.len());
-        buf.extend_from_slice(value);
-        Ok(buf)
+    fn serialize_bytes(self, value: &[u8]) -> Result<()> {
+        ser::Serializer::serialize_bytes(self.ser, value)
     }
 
-    fn serialize_unit(self) -> Result<Vec<u8>> {
+    fn serialize_unit(self) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Vec<u8>> {
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
         self.serialize_unit()
     }
 
@@ -554,16 +976,16 @@ impl<'a> ser::Serializer for &'a mut MapKeySerializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-    ) -> Result<Vec<u8>> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        variant: &'static str,
+    ) -> Result<()> {
+        self.serialize_str(variant)
     }
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
         self,
         _name: &'static str,
         _value: &T,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
@@ -573,23 +995,23 @@ impl<'a> ser::Serializer for &'a mut MapKeySerializer {
         _variant_index: u32,
         _variant: &'static str,
         _value: &T,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_none(self) -> Result<Vec<u8>> {
+    fn serialize_none(self) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Vec<u8>> {
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<()> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<ser::Impossible<Vec<u8>, Error>> {
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ser::Impossible<(), Error>> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_tuple(self, _size: usize) -> Result<ser::Impossible<Vec<u8>, Error>> {
+    fn serialize_tuple(self, _size: usize) -> Result<ser::Impossible<(), Error>> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
@@ -597,7 +1019,7 @@ impl<'a> ser::Serializer for &'a mut MapKeySerializer {
         self,
         _name: &'static str,
         _len: usize,
-    ) -> Result<ser::Impossible<Vec<u8>, Error>> {
+    ) -> Result<ser::Impossible<(), Error>> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
@@ -607,11 +1029,11 @@ impl<'a> ser::Serializer for &'a mut MapKeySerializer {
         _variant_index: u32,
         _variant: &'static str,
         _len: usize,
-    ) -> Result<ser::Impossible<Vec<u8>, Error>> {
+    ) -> Result<ser::Impossible<(), Error>> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<ser::Impossible<Vec<u8>, Error>> {
+    fn serialize_map(self, _len: Option<usize>) -> Result<ser::Impossible<(), Error>> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
@@ -619,7 +1041,7 @@ impl<'a> ser::Serializer for &'a mut MapKeySerializer {
         self,
         _name: &'static str,
         _len: usize,
-    ) -> Result<ser::Impossible<Vec<u8>, Error>> {
+    ) -> Result<ser::Impossible<(), Error>> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
@@ -629,39 +1051,454 @@ impl<'a> ser::Serializer for &'a mut MapKeySerializer {
         _variant_index: u32,
         _variant: &'static str,
         _len: usize,
-    ) -> Result<ser::Impossible<Vec<u8>, Error>> {
+    ) -> Result<ser::Impossible<(), Error>> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::ByteString;
+/// Writes the encoded bytes of a [`RawValue`][crate::RawValue] without
+/// copying them.
+struct RawValueSerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+}
 
-    use super::*;
+impl<'a, W> ser::Serializer for RawValueSerializer<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
 
-    #[cfg(all(feature = "alloc", not(feature = "std")))]
-    use alloc::{format, string::String, vec};
-    #[cfg(feature = "std")]
-    use std::string::String;
+    type SerializeSeq = ser::Impossible<(), Error>;
+    type SerializeTuple = ser::Impossible<(), Error>;
+    type SerializeTupleStruct = ser::Impossible<(), Error>;
+    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeMap = ser::Impossible<(), Error>;
+    type SerializeStruct = ser::Impossible<(), Error>;
+    type SerializeStructVariant = ser::Impossible<(), Error>;
 
-    macro_rules! assert_is_unsupported_type {
-        ($e:expr) => {
-            match $e {
-                Ok(_) => unreachable!(),
-                Err(error) => match error.kind() {
-                    ErrorKind::UnsupportedType => {}
-                    _ => panic!("wrong error type"),
-                },
-            }
-        };
+    fn serialize_bool(self, _value: bool) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    #[test]
-    fn test_serialize_bool() {
+    fn serialize_i8(self, _value: i8) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_i16(self, _value: i16) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_i32(self, _value: i32) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_i64(self, _value: i64) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_u8(self, _value: u8) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_u16(self, _value: u16) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_u32(self, _value: u32) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_u64(self, _value: u64) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_char(self, _value: char) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_str(self, _value: &str) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<()> {
+        self.ser.write_all(value)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<()> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ser::Impossible<(), Error>> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_tuple(self, _size: usize) -> Result<ser::Impossible<(), Error>> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ser::Impossible<(), Error>> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<ser::Impossible<(), Error>> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<ser::Impossible<(), Error>> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ser::Impossible<(), Error>> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<ser::Impossible<(), Error>> {
+        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    }
+}
+
+/// Returns true if the value serializes as none.
+///
+/// Bencode does not have a null value, so dictionary entries with a none
+/// value are skipped.
+pub(crate) fn is_none<T>(value: &T) -> bool
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(IsNoneSerializer).unwrap_or(false)
+}
+
+/// Stops serializing a value which is not none.
+#[derive(Debug)]
+struct NotNone;
+
+impl fmt::Display for NotNone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value is not none")
+    }
+}
+
+impl ser::StdError for NotNone {}
+
+impl ser::Error for NotNone {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        NotNone
+    }
+}
+
+/// Checks if a value serializes as none without serializing the rest of the
+/// value.
+struct IsNoneSerializer;
+
+impl ser::Serializer for IsNoneSerializer {
+    type Ok = bool;
+    type Error = NotNone;
+
+    type SerializeSeq = ser::Impossible<bool, NotNone>;
+    type SerializeTuple = ser::Impossible<bool, NotNone>;
+    type SerializeTupleStruct = ser::Impossible<bool, NotNone>;
+    type SerializeTupleVariant = ser::Impossible<bool, NotNone>;
+    type SerializeMap = ser::Impossible<bool, NotNone>;
+    type SerializeStruct = ser::Impossible<bool, NotNone>;
+    type SerializeStructVariant = ser::Impossible<bool, NotNone>;
+
+    fn serialize_bool(self, _value: bool) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_i8(self, _value: i8) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_i16(self, _value: i16) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_i32(self, _value: i32) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_i64(self, _value: i64) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_u8(self, _value: u8) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_u16(self, _value: u16) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_u32(self, _value: u32) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_u64(self, _value: u64) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_f32(self, _value: f32) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_f64(self, _value: f64) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_char(self, _value: char) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_str(self, _value: &str) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_none(self) -> core::result::Result<bool, NotNone> {
+        Ok(true)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> core::result::Result<bool, NotNone>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> core::result::Result<bool, NotNone> {
+        Ok(false)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> core::result::Result<bool, NotNone>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> core::result::Result<bool, NotNone>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(false)
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> core::result::Result<Self::SerializeSeq, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> core::result::Result<Self::SerializeTuple, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeTupleStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeTupleVariant, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> core::result::Result<Self::SerializeMap, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self::SerializeStructVariant, NotNone> {
+        Err(NotNone)
+    }
+
+    fn collect_str<T>(self, _value: &T) -> core::result::Result<bool, NotNone>
+    where
+        T: ?Sized + fmt::Display,
+    {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ByteString;
+
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{collections::BTreeMap, format, string::String, vec};
+    #[cfg(feature = "std")]
+    use std::{collections::BTreeMap, string::String};
+
+    use crate::test_util::path_info;
+    #[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+    use crate::test_util::{block_on, ChunkedWriter};
+
+    macro_rules! assert_is_unsupported_type {
+        ($e:expr) => {
+            match $e {
+                Ok(_) => unreachable!(),
+                Err(error) => match error.kind() {
+                    ErrorKind::UnsupportedType => {}
+                    _ => panic!("wrong error type"),
+                },
+            }
+        };
+    }
+
+    #[test]
+    fn test_serialize_bool() {
         assert_is_unsupported_type!(to_vec(&true));
     }
 
+    #[test]
+    fn test_serialize_bool_as_int() {
+        #[derive(serde_derive::Serialize)]
+        struct Info {
+            private: bool,
+            public: Option<bool>,
+        }
+
+        let mut writer = Vec::new();
+        let mut ser = Serializer::new(&mut writer);
+        ser.set_bool_as_int(true);
+        Info {
+            private: true,
+            public: Some(false),
+        }
+        .serialize(&mut ser)
+        .unwrap();
+        assert_eq!(writer, b"d7:privatei1e6:publici0ee");
+
+        let mut writer = Vec::new();
+        let mut ser = Serializer::new(&mut writer);
+        ser.set_bool_as_int(true);
+        let mut dict = BTreeMap::new();
+        dict.insert(true, 1);
+        assert_is_unsupported_type!(dict.serialize(&mut ser));
+    }
+
     #[test]
     fn test_serialize_isize() {
         let value: isize = 2;
@@ -809,9 +1646,11 @@ mod tests {
         use serde::Serializer;
 
         let mut writer = Vec::new();
-        assert_is_unsupported_type!(
-            super::Serializer::new(&mut writer).serialize_unit_variant("Nothing", 0, "Case")
-        );
+        super::Serializer::new(&mut writer)
+            .serialize_unit_variant("Nothing", 0, "Case")
+            .unwrap();
+
+        assert_eq!(String::from_utf8(writer).unwrap(), "4:Case");
     }
 
     #[test]
@@ -832,9 +1671,11 @@ mod tests {
         use serde::Serializer;
 
         let mut writer = Vec::new();
-        assert_is_unsupported_type!(
-            super::Serializer::new(&mut writer).serialize_unit_variant("Nothing", 0, "Case")
-        );
+        super::Serializer::new(&mut writer)
+            .serialize_newtype_variant("Nothing", 0, "Case", &2)
+            .unwrap();
+
+        assert_eq!(String::from_utf8(writer).unwrap(), "d4:Casei2ee");
     }
 
     #[test]
@@ -873,30 +1714,77 @@ mod tests {
 
     #[test]
     fn test_serialize_tuple_variant() {
-        use serde::Serializer;
+        #[derive(serde_derive::Serialize)]
+        enum E<'a> {
+            Case(i64, &'a str),
+        }
 
-        let mut writer = Vec::new();
-        assert_is_unsupported_type!(super::Serializer::new(&mut writer).serialize_tuple_variant(
-            "Tuple Variant",
-            2,
-            "Case",
-            1
-        ));
+        assert_eq!(
+            to_vec(&E::Case(201, "spam")).unwrap(),
+            String::from("d4:Caseli201e4:spamee").into_bytes()
+        );
     }
 
     #[test]
     fn test_serialize_struct_variant() {
-        use serde::Serializer;
+        #[derive(serde_derive::Serialize)]
+        enum E {
+            Case { int: u32, s: String },
+        }
 
-        let mut writer = Vec::new();
-        assert_is_unsupported_type!(
-            super::Serializer::new(&mut writer).serialize_struct_variant(
-                "Struct Variant",
-                2,
-                "Case",
-                1
-            )
-        );
+        let value = E::Case {
+            int: 3,
+            s: String::from("spam"),
+        };
+        let expected = String::from("d4:Cased3:inti3e1:s4:spamee").into_bytes();
+        assert_eq!(to_vec(&value).unwrap(), expected);
+
+        #[cfg(feature = "std")]
+        {
+            let mut writer = Vec::new();
+            to_writer(&mut writer, &value).unwrap();
+            assert_eq!(writer, expected);
+        }
+    }
+
+    #[test]
+    fn test_serialize_enum_in_unsorted_dict() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        enum E {
+            Unit,
+            Newtype(u32),
+            Tuple(u32, u32),
+            Struct { b: u32, a: u32 },
+        }
+
+        #[derive(Serialize)]
+        struct Test {
+            z: E,
+            y: E,
+            x: E,
+            w: E,
+        }
+
+        let value = Test {
+            z: E::Unit,
+            y: E::Newtype(1),
+            x: E::Tuple(2, 3),
+            w: E::Struct { b: 4, a: 5 },
+        };
+        let expected = String::from(
+            "d1:wd6:Structd1:ai5e1:bi4eee1:xd5:Tupleli2ei3eee1:yd7:Newtypei1ee1:z4:Unite",
+        )
+        .into_bytes();
+        assert_eq!(to_vec(&value).unwrap(), expected);
+
+        #[cfg(feature = "std")]
+        {
+            let mut writer = Vec::new();
+            to_writer(&mut writer, &value).unwrap();
+            assert_eq!(writer, expected);
+        }
     }
 
     #[test]
@@ -918,4 +1806,372 @@ mod tests {
             String::from("d3:inti3e1:s13:Hello, World!e").into_bytes()
         );
     }
+
+    #[test]
+    fn test_serialize_unsorted_struct() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        struct Inner {
+            spam: u32,
+            eggs: &'static str,
+        }
+
+        #[derive(Serialize)]
+        struct Test {
+            cow: Inner,
+            moo: Vec<Inner>,
+            bacon: u32,
+        }
+
+        let test = Test {
+            cow: Inner { spam: 1, eggs: "a" },
+            moo: vec![Inner { spam: 2, eggs: "b" }],
+            bacon: 3,
+        };
+        let expected = &b"d5:baconi3e3:cowd4:eggs1:a4:spami1ee3:moold4:eggs1:b4:spami2eeee"[..];
+        assert_eq!(to_vec(&test).unwrap(), expected);
+
+        let mut writer = vec![b'x'];
+        test.serialize(&mut Serializer::new(&mut writer)).unwrap();
+        assert_eq!(writer[0], b'x');
+        assert_eq!(&writer[1..], expected);
+
+        let mut buf = [0; 64];
+        let len = to_slice(&mut buf, &test).unwrap();
+        assert_eq!(&buf[..len], expected);
+
+        #[cfg(feature = "std")]
+        {
+            let mut writer = Vec::new();
+            to_writer(&mut writer, &test).unwrap();
+            assert_eq!(writer, expected);
+        }
+    }
+
+    #[test]
+    fn test_serialize_map() {
+        let mut inner = BTreeMap::new();
+        inner.insert(String::from("spam"), 1);
+        inner.insert(String::from("eggs"), 2);
+        let mut dict = BTreeMap::new();
+        dict.insert(String::from("cow"), inner.clone());
+        dict.insert(String::from("moo"), inner);
+
+        let expected = &b"d3:cowd4:eggsi2e4:spami1ee3:mood4:eggsi2e4:spami1eee"[..];
+        assert_eq!(to_vec(&dict).unwrap(), expected);
+
+        let mut buf = [0; 64];
+        let len = to_slice(&mut buf, &dict).unwrap();
+        assert_eq!(&buf[..len], expected);
+
+        #[cfg(feature = "std")]
+        {
+            let mut writer = Vec::new();
+            let mut ser = Serializer::new(crate::write::IoWrite::new(&mut writer));
+            dict.serialize(&mut ser).unwrap();
+            vec![&dict].serialize(&mut ser).unwrap();
+            assert_eq!(writer[..expected.len()], *expected);
+            assert_eq!(writer[expected.len()], b'l');
+            assert_eq!(writer[expected.len() + 1..writer.len() - 1], *expected);
+        }
+    }
+
+    #[test]
+    fn test_serialize_map_key_value_order() {
+        use ser::{SerializeMap as _, Serializer as _};
+
+        let mut writer = Vec::new();
+        let mut ser = Serializer::new(&mut writer);
+        let mut map = (&mut ser).serialize_map(None).unwrap();
+        assert_error_kind!(map.serialize_value(&1), ErrorKind::ValueWithoutKey);
+        map.serialize_key("spam").unwrap();
+        assert_error_kind!(map.serialize_key("eggs"), ErrorKind::KeyWithoutValue);
+        assert_error_kind!(map.end(), ErrorKind::KeyWithoutValue);
+
+        let mut writer = Vec::new();
+        let mut ser = Serializer::new(&mut writer);
+        let mut map = (&mut ser).serialize_map(None).unwrap();
+        assert_is_unsupported_type!(map.serialize_key(&true));
+    }
+
+    #[test]
+    fn test_serialize_integer_128() {
+        assert_eq!(
+            to_vec(&u128::max_value()).unwrap(),
+            &b"i340282366920938463463374607431768211455e"[..]
+        );
+        assert_eq!(
+            to_vec(&i128::min_value()).unwrap(),
+            &b"i-170141183460469231731687303715884105728e"[..]
+        );
+
+        let big = Number::Big(String::from("-1000000000000000000000000000000000000000000"));
+        assert_eq!(
+            to_vec(&big).unwrap(),
+            &b"i-1000000000000000000000000000000000000000000e"[..]
+        );
+        let invalid = Number::Big(String::from("1e"));
+        assert!(to_vec(&invalid).is_err());
+
+        let mut dict = BTreeMap::new();
+        dict.insert(u128::max_value(), 1);
+        assert_eq!(
+            to_vec(&dict).unwrap(),
+            &b"d39:340282366920938463463374607431768211455i1ee"[..]
+        );
+    }
+
+    #[test]
+    fn test_serialize_map_non_string_keys() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        enum Key {
+            Spam,
+            Eggs,
+        }
+
+        let mut dict = BTreeMap::new();
+        dict.insert(1_u32, "a");
+        dict.insert(9, "b");
+        dict.insert(10, "c");
+        let expected = &b"d1:11:a2:101:c1:91:be"[..];
+        assert_eq!(to_vec(&dict).unwrap(), expected);
+
+        let mut buf = [0; 64];
+        let len = to_slice(&mut buf, &dict).unwrap();
+        assert_eq!(&buf[..len], expected);
+
+        let mut dict = BTreeMap::new();
+        dict.insert(-1_i8, 1);
+        dict.insert(i8::max_value(), 2);
+        assert_eq!(to_vec(&dict).unwrap(), b"d2:-1i1e3:127i2ee");
+
+        let mut dict = BTreeMap::new();
+        dict.insert('b', 1);
+        dict.insert('a', 2);
+        assert_eq!(to_vec(&dict).unwrap(), b"d1:ai2e1:bi1ee");
+
+        let mut dict = BTreeMap::new();
+        dict.insert(Key::Spam, 1);
+        dict.insert(Key::Eggs, 2);
+        assert_eq!(to_vec(&dict).unwrap(), b"d4:Eggsi2e4:Spami1ee");
+    }
+
+    #[test]
+    fn test_serialize_map_duplicate_keys() {
+        struct Entries(&'static [(&'static str, u32)]);
+
+        impl Serialize for Entries {
+            fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                use ser::SerializeMap as _;
+
+                let mut map = serializer.serialize_map(Some(self.0.len()))?;
+                for (k, v) in self.0 {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+
+        assert_eq!(
+            to_vec(&Entries(&[("a", 1), ("b", 2), ("a", 3)])).unwrap(),
+            b"d1:ai3e1:bi2ee"
+        );
+        assert_eq!(
+            to_vec(&Entries(&[("a", 1), ("b", 2), ("b", 3)])).unwrap(),
+            b"d1:ai1e1:bi3ee"
+        );
+    }
+
+    #[test]
+    fn test_serialize_none_entries() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        struct Test {
+            z: Option<u32>,
+            a: Option<Option<u32>>,
+            m: Option<&'static str>,
+        }
+
+        let test = Test {
+            z: None,
+            a: Some(None),
+            m: Some("spam"),
+        };
+        let expected = &b"d1:m4:spame"[..];
+        assert_eq!(to_vec(&test).unwrap(), expected);
+
+        let mut buf = [0; 64];
+        let len = to_slice(&mut buf, &test).unwrap();
+        assert_eq!(&buf[..len], expected);
+
+        #[cfg(feature = "std")]
+        {
+            let mut writer = Vec::new();
+            to_writer(&mut writer, &test).unwrap();
+            assert_eq!(writer, expected);
+        }
+
+        let mut dict = BTreeMap::new();
+        dict.insert("a", None);
+        dict.insert("b", Some(1));
+        dict.insert("c", None);
+        assert_eq!(to_vec(&dict).unwrap(), b"d1:bi1ee");
+
+        // The skipped key is not compared with the next key.
+        struct Entries;
+
+        impl Serialize for Entries {
+            fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                use ser::SerializeMap as _;
+
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("a", &Some(1))?;
+                map.serialize_entry("z", &None::<u32>)?;
+                map.serialize_entry("b", &Some(2))?;
+                map.end()
+            }
+        }
+        assert_eq!(to_vec(&Entries).unwrap(), b"d1:ai1e1:bi2ee");
+
+        assert_is_unsupported_type!(to_vec(&vec![None::<u32>]));
+        assert_is_unsupported_type!(to_vec(&Some(None::<u32>)));
+    }
+
+    #[test]
+    fn test_serialize_to_slice() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        struct Test {
+            id: &'static str,
+            t: &'static str,
+            y: &'static str,
+        }
+
+        let test = Test {
+            id: "abcdefghij0123456789",
+            t: "aa",
+            y: "q",
+        };
+        let expected = &b"d2:id20:abcdefghij01234567891:t2:aa1:y1:qe"[..];
+
+        let mut buf = [0; 64];
+        let len = to_slice(&mut buf, &test).unwrap();
+        assert_eq!(len, expected.len());
+        assert_eq!(&buf[..len], expected);
+
+        let mut buf = [0; 64];
+        let mut ser = Serializer::new(crate::write::SliceWrite::new(&mut buf[..len]));
+        test.serialize(&mut ser).unwrap();
+        let writer = ser.into_inner();
+        assert_eq!(writer.as_slice(), expected);
+        assert_eq!(writer.remaining(), 0);
+
+        let mut buf = [0; 64];
+        match to_slice(&mut buf[..len - 1], &test) {
+            Ok(_) => panic!("expected error"),
+            Err(error) => {
+                match error.kind() {
+                    ErrorKind::BufferFull => {}
+                    _ => panic!("wrong error type: {:?}", error),
+                }
+                assert_eq!(error.byte_offset(), len - 1);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "std", feature = "tokio"))]
+    #[test]
+    fn test_serialize_to_tokio_writer() {
+        let mut dict = BTreeMap::new();
+        dict.insert(String::from("spam"), vec![1, 2, 3]);
+        dict.insert(String::from("cow"), vec![4]);
+
+        let mut writer = ChunkedWriter::default();
+        block_on(to_tokio_writer(&mut writer, &dict)).unwrap();
+        assert_eq!(writer.bytes, to_vec(&dict).unwrap());
+        assert_eq!(writer.bytes, b"d3:cowli4ee4:spamli1ei2ei3eee");
+
+        assert_is_unsupported_type!(block_on(to_tokio_writer(&mut writer, &true)));
+    }
+
+    #[cfg(all(feature = "std", feature = "futures-io"))]
+    #[test]
+    fn test_serialize_to_futures_writer() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        struct Test {
+            s: String,
+            int: u32,
+        }
+
+        let test = Test {
+            s: String::from("Hello, World!"),
+            int: 3,
+        };
+
+        let mut writer = ChunkedWriter::default();
+        block_on(to_futures_writer(&mut writer, &test)).unwrap();
+        assert_eq!(writer.bytes, b"d3:inti3e1:s13:Hello, World!e");
+    }
+
+    #[test]
+    fn test_serialize_error_path() {
+        let err = to_vec(&path_info()).unwrap_err();
+        match err.kind() {
+            ErrorKind::UnsupportedType => {}
+            _ => panic!("wrong error type: {:?}", err),
+        }
+        assert_eq!(format!("{}", err.path()), "files[1].mtime");
+        assert_eq!(format!("{}", err), "unsupported type in files[1].mtime");
+
+        let mut dict = BTreeMap::new();
+        dict.insert(String::from("piece length"), vec![Some(1), None]);
+        let err = to_vec(&dict).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "[\"piece length\"][1]");
+
+        let mut dict = BTreeMap::new();
+        dict.insert((1, 2), 3);
+        let err = to_vec(&vec![dict]).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "[0]");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_serialize_error_path_to_writer() {
+        let err = to_writer(Vec::new(), &path_info()).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "files[1].mtime");
+    }
+
+    #[cfg(feature = "embedded-io")]
+    #[test]
+    fn test_serialize_to_embedded_writer() {
+        let mut dict = BTreeMap::new();
+        dict.insert(String::from("spam"), vec![1, 2, 3]);
+        dict.insert(String::from("cow"), vec![4]);
+
+        let mut buf = [0; 29];
+        to_embedded_writer(&mut buf[..], &dict).unwrap();
+        assert_eq!(&buf[..], b"d3:cowli4ee4:spamli1ei2ei3eee");
+
+        let mut buf = [0; 28];
+        match to_embedded_writer(&mut buf[..], &dict) {
+            Ok(()) => panic!("expected error"),
+            Err(error) => match error.kind() {
+                ErrorKind::EmbeddedIo(embedded_io::ErrorKind::WriteZero) => {}
+                _ => panic!("wrong error type: {:?}", error),
+            },
+        }
+    }
 }
@@ -0,0 +1,253 @@
+//! Records the byte range of a deserialized value.
+
+use core::{fmt, marker::PhantomData, ops::Range};
+use serde::{
+    de::{self, Deserialize, MapAccess, Unexpected, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+/// The name of the struct which carries a span through the Serde data model.
+pub(crate) const SPANNED_TOKEN: &str = "$bt_bencode::private::Spanned";
+
+pub(crate) const START_FIELD: &str = "$bt_bencode::private::Spanned::start";
+pub(crate) const VALUE_FIELD: &str = "$bt_bencode::private::Spanned::value";
+pub(crate) const END_FIELD: &str = "$bt_bencode::private::Spanned::end";
+
+pub(crate) const SPANNED_FIELDS: &[&str] = &[START_FIELD, VALUE_FIELD, END_FIELD];
+
+/// A deserialized value with the byte range of its encoding in the source.
+///
+/// The span starts at the first byte of the value's encoding and ends after
+/// the last byte. The byte offsets are the same as
+/// [`Deserializer::byte_offset()`][crate::Deserializer::byte_offset], so they
+/// are relative to the start of the source.
+///
+/// ```rust
+/// use bt_bencode::Spanned;
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     #[serde(rename = "piece length")]
+///     piece_length: Spanned<u64>,
+/// }
+///
+/// let info: Info = bt_bencode::from_slice(b"d12:piece lengthi1000ee")?;
+/// assert_eq!(*info.piece_length.get_ref(), 1000);
+/// assert_eq!(info.piece_length.span(), 16..22);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// Only a [`Deserializer`][crate::Deserializer] records spans. Deserializing a
+/// `Spanned<T>` from a [`Value`][crate::Value] or with another format returns an
+/// error.
+///
+/// Equality and ordering only compare the inner values. A `Spanned<T>` is
+/// serialized as the inner value.
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    span: Range<usize>,
+    value: T,
+}
+
+impl<T> Spanned<T> {
+    /// Constructs a `Spanned` with a byte range and a value.
+    #[must_use]
+    #[inline]
+    pub fn new(span: Range<usize>, value: T) -> Self {
+        Self { span, value }
+    }
+
+    /// The byte range of the value's encoding.
+    #[must_use]
+    #[inline]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The byte offset of the first byte of the value's encoding.
+    #[must_use]
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.span.start
+    }
+
+    /// The byte offset after the last byte of the value's encoding.
+    #[must_use]
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.span.end
+    }
+
+    /// Returns a reference to the inner value.
+    #[must_use]
+    #[inline]
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the inner value.
+    #[must_use]
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Returns the inner value.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T: PartialOrd> PartialOrd for Spanned<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord> Ord for Spanned<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Spanned<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct SpannedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for SpannedVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = Spanned<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a spanned value")
+            }
+
+            fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                if visitor.next_key::<&str>()? != Some(START_FIELD) {
+                    return Err(de::Error::invalid_type(Unexpected::Map, &self));
+                }
+                let start = visitor.next_value()?;
+
+                if visitor.next_key::<&str>()? != Some(VALUE_FIELD) {
+                    return Err(de::Error::invalid_type(Unexpected::Map, &self));
+                }
+                let value = visitor.next_value()?;
+
+                if visitor.next_key::<&str>()? != Some(END_FIELD) {
+                    return Err(de::Error::invalid_type(Unexpected::Map, &self));
+                }
+                let end = visitor.next_value()?;
+
+                Ok(Spanned {
+                    span: start..end,
+                    value,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct(SPANNED_TOKEN, SPANNED_FIELDS, SpannedVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, Value};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{string::String, vec::Vec};
+    #[cfg(feature = "std")]
+    use std::{string::String, vec::Vec};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Info {
+        name: Spanned<String>,
+        #[serde(rename = "piece length")]
+        piece_length: Spanned<u64>,
+        files: Spanned<Vec<Spanned<Value>>>,
+    }
+
+    const INPUT: &[u8] = b"d5:filesl1:ai1ee4:name4:spam12:piece lengthi1000ee";
+
+    fn assert_spans(info: &Info) {
+        assert_eq!(info.files.span(), 8..16);
+        assert_eq!(info.files.get_ref()[0].span(), 9..12);
+        assert_eq!(info.files.get_ref()[1].span(), 12..15);
+        assert_eq!(info.name.span(), 22..28);
+        assert_eq!(info.name.get_ref(), "spam");
+        assert_eq!(info.piece_length.span(), 43..49);
+        assert_eq!(info.piece_length.start(), 43);
+        assert_eq!(info.piece_length.end(), 49);
+        assert_eq!(info.piece_length.clone().into_inner(), 1000);
+    }
+
+    #[test]
+    fn test_deserialize_from_slice() -> Result<()> {
+        let info: Info = crate::from_slice(INPUT)?;
+        assert_spans(&info);
+
+        let value: Spanned<Value> = crate::from_slice(INPUT)?;
+        assert_eq!(value.span(), 0..INPUT.len());
+        assert_eq!(value.get_ref()["name"].as_str(), Some("spam"));
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_from_reader() -> Result<()> {
+        let info: Info = crate::from_reader(INPUT)?;
+        assert_spans(&info);
+
+        let info: Info = crate::from_buf_reader(std::io::BufReader::with_capacity(3, INPUT))?;
+        assert_spans(&info);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        assert!(crate::from_slice::<Spanned<u64>>(b"4:spam").is_err());
+
+        let value: Value = crate::from_slice(INPUT).unwrap();
+        assert!(crate::from_value::<Spanned<Value>>(value).is_err());
+    }
+
+    #[test]
+    fn test_serialize() -> Result<()> {
+        let info: Info = crate::from_slice(INPUT)?;
+        assert_eq!(crate::to_vec(&info)?, INPUT);
+        assert_eq!(Spanned::new(0..0, 1), Spanned::new(1..3, 1));
+        Ok(())
+    }
+}
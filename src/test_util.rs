@@ -0,0 +1,245 @@
+//! Helpers which are shared by the tests of several modules.
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{vec, vec::Vec};
+
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+use std::io;
+
+/// Asserts that the result is an error with the kind and, if given, the byte
+/// offset.
+macro_rules! assert_error_kind {
+    ($e:expr, $kind:pat) => {
+        match $e {
+            Ok(_) => panic!("expected error"),
+            Err(error) => match error.kind() {
+                $kind => {}
+                _ => panic!("wrong error type: {:?}", error),
+            },
+        }
+    };
+    ($e:expr, $kind:pat, $byte_offset:expr) => {
+        match $e {
+            Ok(_) => panic!("expected error"),
+            Err(error) => {
+                match error.kind() {
+                    $kind => {}
+                    _ => panic!("wrong error type: {:?}", error),
+                }
+                assert_eq!(error.byte_offset(), $byte_offset);
+            }
+        }
+    };
+}
+
+/// A torrent info dictionary which is used to test error paths.
+#[derive(Debug, serde_derive::Deserialize, serde_derive::Serialize)]
+pub(crate) struct PathInfo {
+    pub(crate) files: Vec<PathFile>,
+}
+
+/// A file in a [`PathInfo`].
+#[derive(Debug, serde_derive::Deserialize, serde_derive::Serialize)]
+pub(crate) struct PathFile {
+    pub(crate) length: u64,
+    #[serde(rename = "piece length", default)]
+    pub(crate) piece_length: u64,
+    pub(crate) mtime: Option<f64>,
+}
+
+/// Returns a [`PathInfo`] which fails to serialize at `files[1].mtime`.
+pub(crate) fn path_info() -> PathInfo {
+    PathInfo {
+        files: vec![
+            PathFile {
+                length: 1,
+                piece_length: 0,
+                mtime: None,
+            },
+            PathFile {
+                length: 2,
+                piece_length: 0,
+                mtime: Some(1.5),
+            },
+        ],
+    }
+}
+
+/// Returns a waker which does nothing when woken.
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+pub(crate) fn noop_waker() -> core::task::Waker {
+    use core::task::{RawWaker, RawWakerVTable, Waker};
+
+    fn raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+
+    unsafe { Waker::from_raw(raw_waker()) }
+}
+
+/// Polls a future until it is ready.
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+pub(crate) fn block_on<F>(future: F) -> F::Output
+where
+    F: core::future::Future,
+{
+    use core::task::{Context, Poll};
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// An async reader which returns a few bytes at a time and is pending
+/// before every read.
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+pub(crate) struct ChunkedReader {
+    bytes: &'static [u8],
+    chunk_len: usize,
+    is_ready: bool,
+}
+
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+impl ChunkedReader {
+    pub(crate) fn new(bytes: &'static [u8], chunk_len: usize) -> Self {
+        ChunkedReader {
+            bytes,
+            chunk_len,
+            is_ready: false,
+        }
+    }
+
+    fn poll_read_chunk(
+        &mut self,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> core::task::Poll<usize> {
+        if !self.is_ready {
+            self.is_ready = true;
+            cx.waker().wake_by_ref();
+            return core::task::Poll::Pending;
+        }
+        self.is_ready = false;
+        let len = core::cmp::min(core::cmp::min(self.chunk_len, buf.len()), self.bytes.len());
+        buf[..len].copy_from_slice(&self.bytes[..len]);
+        self.bytes = &self.bytes[len..];
+        core::task::Poll::Ready(len)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "futures-io"))]
+impl futures_io::AsyncRead for ChunkedReader {
+    fn poll_read(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> core::task::Poll<io::Result<usize>> {
+        self.poll_read_chunk(cx, buf).map(Ok)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "tokio"))]
+impl tokio::io::AsyncRead for ChunkedReader {
+    fn poll_read(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> core::task::Poll<io::Result<()>> {
+        self.poll_read_chunk(cx, buf.initialize_unfilled())
+            .map(|len| {
+                buf.advance(len);
+                Ok(())
+            })
+    }
+}
+
+/// An async writer which accepts a few bytes at a time and is pending
+/// before every write.
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+#[derive(Default)]
+pub(crate) struct ChunkedWriter {
+    pub(crate) bytes: Vec<u8>,
+    is_ready: bool,
+}
+
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+impl ChunkedWriter {
+    fn poll_write_chunk(
+        &mut self,
+        cx: &mut core::task::Context<'_>,
+        buf: &[u8],
+    ) -> core::task::Poll<io::Result<usize>> {
+        if !self.is_ready {
+            self.is_ready = true;
+            cx.waker().wake_by_ref();
+            return core::task::Poll::Pending;
+        }
+        self.is_ready = false;
+        let len = core::cmp::min(3, buf.len());
+        self.bytes.extend_from_slice(&buf[..len]);
+        core::task::Poll::Ready(Ok(len))
+    }
+}
+
+#[cfg(all(feature = "std", feature = "tokio"))]
+impl tokio::io::AsyncWrite for ChunkedWriter {
+    fn poll_write(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &[u8],
+    ) -> core::task::Poll<io::Result<usize>> {
+        self.poll_write_chunk(cx, buf)
+    }
+
+    fn poll_flush(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(all(feature = "std", feature = "futures-io"))]
+impl futures_io::AsyncWrite for ChunkedWriter {
+    fn poll_write(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &[u8],
+    ) -> core::task::Poll<io::Result<usize>> {
+        self.poll_write_chunk(cx, buf)
+    }
+
+    fn poll_flush(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+}
@@ -1,36 +1,80 @@
 //! Represents valid Bencode data.
 
-use crate::{error::Error, ByteString};
-use core::fmt::Display;
+use crate::{
+    error::{Error, ErrorKind},
+    ByteString,
+};
+use core::{fmt::Display, mem};
 use serde::{
-    de::{Deserialize, DeserializeOwned, MapAccess, SeqAccess, Visitor},
+    de::{
+        value::{BorrowedStrDeserializer, BytesDeserializer, StrDeserializer},
+        Deserialize, DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Unexpected, Visitor,
+    },
     ser::Serialize,
 };
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::{collections::BTreeMap, fmt, str, str::FromStr, string::String, vec::Vec};
+use alloc::{
+    borrow::Cow, collections::BTreeMap, fmt, format, str, str::FromStr, string::String, vec::Vec,
+};
 
 #[cfg(feature = "std")]
-use std::{collections::BTreeMap, fmt, str, str::FromStr, string::String, vec::Vec};
+use std::{borrow::Cow, collections::BTreeMap, fmt, str, str::FromStr, string::String, vec::Vec};
 
 /// Represents a valid Bencode number.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///
+/// Bencode does not limit the size of an integer. Integers which do not fit
+/// into an [i64] or a [u64] are kept as their decimal digits, so they can be
+/// encoded again without any loss.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Number {
     /// A signed integer.
     Signed(i64),
     /// An unsigned integer.
     Unsigned(u64),
+    /// The canonical decimal digits of an integer which does not fit into an
+    /// [i64] or a [u64], with a leading `-` if the integer is negative.
+    Big(String),
 }
 
 impl Display for Number {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Number::Signed(arg0) => fmt::Display::fmt(arg0, f),
-            Number::Unsigned(arg0) => fmt::Display::fmt(arg0, f),
+            Number::Signed(arg0) => Display::fmt(arg0, f),
+            Number::Unsigned(arg0) => Display::fmt(arg0, f),
+            Number::Big(arg0) => f.write_str(arg0),
         }
     }
 }
 
+impl FromStr for Number {
+    type Err = Error;
+
+    /// Parses the canonical decimal representation of an integer of any size.
+    ///
+    /// Leading zeros, a leading `+`, and negative zero are rejected with
+    /// [`ErrorKind::InvalidInteger`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_negative = s.as_bytes().first() == Some(&b'-');
+        let digits = if is_negative { &s[1..] } else { s };
+        if digits.is_empty()
+            || !digits.bytes().all(|b| b.is_ascii_digit())
+            || (digits.as_bytes()[0] == b'0' && (digits.len() > 1 || is_negative))
+        {
+            return Err(Error::with_kind(ErrorKind::InvalidInteger));
+        }
+
+        if is_negative {
+            if let Ok(value) = s.parse() {
+                return Ok(Number::Signed(value));
+            }
+        } else if let Ok(value) = s.parse() {
+            return Ok(Number::Unsigned(value));
+        }
+        Ok(Number::Big(String::from(s)))
+    }
+}
+
 impl From<isize> for Number {
     fn from(value: isize) -> Self {
         Number::Signed(value as i64)
@@ -91,6 +135,427 @@ impl From<u8> for Number {
     }
 }
 
+impl From<i128> for Number {
+    fn from(value: i128) -> Self {
+        use core::convert::TryFrom;
+
+        match i64::try_from(value) {
+            Ok(value) => Number::Signed(value),
+            Err(_) => Number::Big(String::from(itoa::Buffer::new().format(value))),
+        }
+    }
+}
+
+impl From<u128> for Number {
+    fn from(value: u128) -> Self {
+        use core::convert::TryFrom;
+
+        match u64::try_from(value) {
+            Ok(value) => Number::Unsigned(value),
+            Err(_) => Number::Big(String::from(itoa::Buffer::new().format(value))),
+        }
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Number::Signed(n) => serializer.serialize_i64(*n),
+            Number::Unsigned(n) => serializer.serialize_u64(*n),
+            Number::Big(digits) => {
+                if let Ok(n) = digits.parse::<u128>() {
+                    serializer.serialize_u128(n)
+                } else if let Ok(n) = digits.parse::<i128>() {
+                    serializer.serialize_i128(n)
+                } else {
+                    serializer.serialize_newtype_struct(NUMBER_TOKEN, digits.as_str())
+                }
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    #[inline]
+    fn deserialize<T>(deserializer: T) -> Result<Number, T::Error>
+    where
+        T: serde::Deserializer<'de>,
+    {
+        struct NumberVisitor;
+
+        impl<'de> Visitor<'de> for NumberVisitor {
+            type Value = Number;
+
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an integer")
+            }
+
+            #[inline]
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(Number::Signed(value))
+            }
+
+            #[inline]
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(Number::Unsigned(value))
+            }
+
+            #[inline]
+            fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+                Ok(Number::from(value))
+            }
+
+            #[inline]
+            fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E> {
+                Ok(Number::from(value))
+            }
+
+            fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                match visitor.next_key_seed(MapKeyClassifier)? {
+                    Some(MapKeyClass::Number) => visit_number_digits(visitor),
+                    _ => Err(serde::de::Error::invalid_type(Unexpected::Map, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+/// The name of the newtype struct and the map key which carry the digits of a
+/// [`Number::Big`] through the Serde data model.
+pub(crate) const NUMBER_TOKEN: &str = "$bt_bencode::private::Number";
+
+/// Presents the digits of an integer which does not fit into an [i128] or a
+/// [u128] as a map with a single [`NUMBER_TOKEN`] key.
+pub(crate) struct NumberDeserializer<'a> {
+    digits: &'a str,
+    visited_key: bool,
+}
+
+impl<'a> NumberDeserializer<'a> {
+    pub(crate) fn new(digits: &'a str) -> Self {
+        Self {
+            digits,
+            visited_key: false,
+        }
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for NumberDeserializer<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.visited_key {
+            return Ok(None);
+        }
+        self.visited_key = true;
+        seed.deserialize(BorrowedStrDeserializer::new(NUMBER_TOKEN))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(StrDeserializer::new(self.digits))
+    }
+}
+
+/// The name of the newtype struct which requests the complete encoding of a
+/// [`Value`], so the value can be built without recursion.
+pub(crate) const VALUE_TOKEN: &str = "$bt_bencode::private::Value";
+
+/// The map key which carries the encoding of a [`Value`] through the Serde
+/// data model.
+const ENCODED_VALUE_TOKEN: &str = "$bt_bencode::private::Value::encoded";
+
+/// The map key which carries the encoding of a [`Value`] where only the first
+/// entry for a repeated dictionary key is kept.
+const ENCODED_VALUE_KEEP_FIRST_TOKEN: &str = "$bt_bencode::private::Value::encoded_keep_first";
+
+/// Presents the complete encoding of a [`Value`] as a map with a single
+/// [`ENCODED_VALUE_TOKEN`] or [`ENCODED_VALUE_KEEP_FIRST_TOKEN`] key.
+pub(crate) struct EncodedValueDeserializer<'a> {
+    token: &'static str,
+    bytes: &'a [u8],
+    visited_key: bool,
+}
+
+impl<'a> EncodedValueDeserializer<'a> {
+    pub(crate) fn new(bytes: &'a [u8], keep_first: bool) -> Self {
+        Self {
+            token: if keep_first {
+                ENCODED_VALUE_KEEP_FIRST_TOKEN
+            } else {
+                ENCODED_VALUE_TOKEN
+            },
+            bytes,
+            visited_key: false,
+        }
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for EncodedValueDeserializer<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.visited_key {
+            return Ok(None);
+        }
+        self.visited_key = true;
+        seed.deserialize(BorrowedStrDeserializer::new(self.token))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(BytesDeserializer::new(self.bytes))
+    }
+}
+
+/// Builds a [`Value`] from the encoding after an [`ENCODED_VALUE_TOKEN`] or
+/// an [`ENCODED_VALUE_KEEP_FIRST_TOKEN`] key.
+struct EncodedValueSeed {
+    keep_first: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for EncodedValueSeed {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(self)
+    }
+}
+
+impl<'de> Visitor<'de> for EncodedValueSeed {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the encoding of a value")
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        build_value(value, self.keep_first)
+            .ok_or_else(|| serde::de::Error::custom("invalid encoding of a value"))
+    }
+}
+
+/// Builds a [`Value`] from an encoding which has already been checked by the
+/// [`Deserializer`][crate::Deserializer].
+///
+/// Nested lists and dictionaries are kept on an explicit stack, so deeply
+/// nested values do not overflow the call stack.
+fn build_value(bytes: &[u8], keep_first: bool) -> Option<Value> {
+    enum Container {
+        List(Vec<Value>),
+        /// The entries and the key of the next entry if it has been read
+        Dict(BTreeMap<ByteString, Value>, Option<ByteString>),
+    }
+
+    let mut stack: Vec<Container> = Vec::new();
+    let mut idx = 0;
+
+    loop {
+        let value = match *bytes.get(idx)? {
+            b'e' => {
+                idx += 1;
+                match stack.pop()? {
+                    Container::List(l) => Value::List(l),
+                    Container::Dict(d, None) => Value::Dict(d),
+                    Container::Dict(_, Some(_)) => return None,
+                }
+            }
+            b'l' => {
+                idx += 1;
+                stack.push(Container::List(Vec::new()));
+                continue;
+            }
+            b'd' => {
+                idx += 1;
+                stack.push(Container::Dict(BTreeMap::new(), None));
+                continue;
+            }
+            b'i' => {
+                let end = idx + 1 + bytes[idx + 1..].iter().position(|b| *b == b'e')?;
+                let n = parse_encoded_integer(&bytes[idx + 1..end])?;
+                idx = end + 1;
+                Value::Int(n)
+            }
+            b'0'..=b'9' => {
+                let colon = idx + bytes[idx..].iter().position(|b| *b == b':')?;
+                let len: usize = str::from_utf8(&bytes[idx..colon]).ok()?.parse().ok()?;
+                let end = (colon + 1).checked_add(len)?;
+                let contents = ByteString::from(bytes.get(colon + 1..end)?);
+                idx = end;
+                if let Some(Container::Dict(_, key @ None)) = stack.last_mut() {
+                    *key = Some(contents);
+                    continue;
+                }
+                Value::ByteStr(contents)
+            }
+            _ => return None,
+        };
+
+        match stack.last_mut() {
+            None => {
+                return if idx == bytes.len() {
+                    Some(value)
+                } else {
+                    None
+                }
+            }
+            Some(Container::List(l)) => l.push(value),
+            Some(Container::Dict(d, key)) => {
+                let key = key.take()?;
+                if keep_first {
+                    d.entry(key).or_insert(value);
+                } else {
+                    d.insert(key, value);
+                }
+            }
+        }
+    }
+}
+
+/// Parses the digits of an encoded integer which may not be canonical.
+fn parse_encoded_integer(digits: &[u8]) -> Option<Number> {
+    let s = str::from_utf8(digits).ok()?;
+    let (sign, digits) = if s.starts_with('-') {
+        ("-", &s[1..])
+    } else {
+        ("", s)
+    };
+    let trimmed = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        None
+    } else if trimmed.is_empty() {
+        Some(if sign.is_empty() {
+            Number::Unsigned(0)
+        } else {
+            Number::Signed(0)
+        })
+    } else if trimmed.len() == digits.len() {
+        s.parse().ok()
+    } else {
+        format!("{}{}", sign, trimmed).parse().ok()
+    }
+}
+
+/// Visits an integer which does not fit into an [i64] or a [u64].
+pub(crate) fn visit_big_number<'de, V>(digits: &str, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if let Ok(n) = digits.parse::<u128>() {
+        visitor.visit_u128(n)
+    } else if let Ok(n) = digits.parse::<i128>() {
+        visitor.visit_i128(n)
+    } else {
+        visitor.visit_map(NumberDeserializer::new(digits))
+    }
+}
+
+/// The first key of a map which may be the [`NUMBER_TOKEN`] or an encoded
+/// value token.
+enum MapKeyClass<'de> {
+    Number,
+    /// The [`ENCODED_VALUE_TOKEN`] or the [`ENCODED_VALUE_KEEP_FIRST_TOKEN`]
+    Encoded(&'static str),
+    Key(Cow<'de, [u8]>),
+}
+
+impl<'de> MapKeyClass<'de> {
+    fn from_token(value: &str) -> Option<Self> {
+        match value {
+            NUMBER_TOKEN => Some(MapKeyClass::Number),
+            ENCODED_VALUE_TOKEN => Some(MapKeyClass::Encoded(ENCODED_VALUE_TOKEN)),
+            ENCODED_VALUE_KEEP_FIRST_TOKEN => {
+                Some(MapKeyClass::Encoded(ENCODED_VALUE_KEEP_FIRST_TOKEN))
+            }
+            _ => None,
+        }
+    }
+}
+
+struct MapKeyClassifier;
+
+impl<'de> DeserializeSeed<'de> for MapKeyClassifier {
+    type Value = MapKeyClass<'de>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for MapKeyClassifier {
+    type Value = MapKeyClass<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte string key")
+    }
+
+    // Dictionary keys from a `Deserializer` are always visited as bytes, so
+    // only a `NumberDeserializer` or an `EncodedValueDeserializer` can produce
+    // a token as a `str`.
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(MapKeyClass::from_token(value)
+            .unwrap_or_else(|| MapKeyClass::Key(Cow::Owned(value.as_bytes().to_vec()))))
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+        Ok(MapKeyClass::from_token(value)
+            .unwrap_or(MapKeyClass::Key(Cow::Borrowed(value.as_bytes()))))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(MapKeyClass::Key(Cow::Owned(value.to_vec())))
+    }
+
+    fn visit_borrowed_bytes<E>(self, value: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(MapKeyClass::Key(Cow::Borrowed(value)))
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(MapKeyClass::Key(Cow::Owned(value)))
+    }
+}
+
+/// Deserializes the digits after a [`NUMBER_TOKEN`] key.
+fn visit_number_digits<'de, V>(mut visitor: V) -> Result<Number, V::Error>
+where
+    V: MapAccess<'de>,
+{
+    let digits: String = visitor.next_value()?;
+    digits
+        .parse()
+        .map_err(|_| serde::de::Error::invalid_value(Unexpected::Str(&digits), &"an integer"))
+}
+
 /// Represents a valid Bencode value.
 ///
 /// It is useful when it is unknown what the data may contain (e.g. when different kinds of
@@ -253,6 +718,45 @@ impl Value {
     }
 }
 
+impl Drop for Value {
+    /// Drops nested lists and dictionaries without recursion.
+    ///
+    /// Otherwise, dropping a deeply nested value could overflow the stack.
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        take_nested(self, &mut stack);
+        while let Some(mut value) = stack.pop() {
+            take_nested(&mut value, &mut stack);
+        }
+    }
+}
+
+/// Moves the elements of a list or dictionary into the stack if any of them
+/// are non-empty lists or dictionaries.
+fn take_nested(value: &mut Value, stack: &mut Vec<Value>) {
+    fn is_nested(value: &Value) -> bool {
+        match value {
+            Value::List(l) => !l.is_empty(),
+            Value::Dict(d) => !d.is_empty(),
+            Value::ByteStr(_) | Value::Int(_) => false,
+        }
+    }
+
+    match value {
+        Value::List(l) => {
+            if l.iter().any(is_nested) {
+                stack.append(l);
+            }
+        }
+        Value::Dict(d) => {
+            if d.values().any(is_nested) {
+                stack.extend(mem::replace(d, BTreeMap::new()).into_iter().map(|(_, v)| v));
+            }
+        }
+        Value::ByteStr(_) | Value::Int(_) => {}
+    }
+}
+
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         struct DebugByteStr<'a>(&'a ByteString);
@@ -349,6 +853,18 @@ impl From<usize> for Value {
     }
 }
 
+impl From<i128> for Value {
+    fn from(other: i128) -> Value {
+        Value::Int(Number::from(other))
+    }
+}
+
+impl From<u128> for Value {
+    fn from(other: u128) -> Value {
+        Value::Int(Number::from(other))
+    }
+}
+
 impl FromStr for Value {
     type Err = Error;
 
@@ -371,7 +887,7 @@ impl From<String> for Value {
 
 impl<V: Into<Value>> From<Vec<V>> for Value {
     fn from(other: Vec<V>) -> Value {
-        Value::List(other.into_iter().map(core::convert::Into::into).collect())
+        Value::List(other.into_iter().map(Into::into).collect())
     }
 }
 
@@ -412,6 +928,16 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::Int(Number::Unsigned(value)))
             }
 
+            #[inline]
+            fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+                Ok(Value::Int(Number::from(value)))
+            }
+
+            #[inline]
+            fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E> {
+                Ok(Value::Int(Number::from(value)))
+            }
+
             #[inline]
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
                 Ok(Value::ByteStr(ByteString::from(String::from(value))))
@@ -440,6 +966,14 @@ impl<'de> Deserialize<'de> for Value {
                 Deserialize::deserialize(deserializer)
             }
 
+            #[inline]
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+
             fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
             where
                 V: SeqAccess<'de>,
@@ -459,6 +993,21 @@ impl<'de> Deserialize<'de> for Value {
                 V: MapAccess<'de>,
             {
                 let mut dict = BTreeMap::new();
+                match visitor.next_key_seed(MapKeyClassifier)? {
+                    Some(MapKeyClass::Number) => {
+                        return visit_number_digits(visitor).map(Value::Int)
+                    }
+                    Some(MapKeyClass::Encoded(token)) => {
+                        return visitor.next_value_seed(EncodedValueSeed {
+                            keep_first: token == ENCODED_VALUE_KEEP_FIRST_TOKEN,
+                        })
+                    }
+                    Some(MapKeyClass::Key(key)) => {
+                        let value = visitor.next_value()?;
+                        dict.insert(ByteString::from(key.into_owned()), value);
+                    }
+                    None => return Ok(Value::Dict(dict)),
+                }
                 while let Some((key, value)) = visitor.next_entry()? {
                     dict.insert(key, value);
                 }
@@ -466,7 +1015,9 @@ impl<'de> Deserialize<'de> for Value {
             }
         }
 
-        deserializer.deserialize_any(ValueVisitor)
+        // A `Deserializer` passes the complete encoding of the value, so it is
+        // built without recursion. Other deserializers visit the value itself.
+        deserializer.deserialize_newtype_struct(VALUE_TOKEN, ValueVisitor)
     }
 }
 
@@ -477,21 +1028,20 @@ impl Serialize for Value {
     {
         match self {
             Value::ByteStr(ref b) => b.serialize(serializer),
-            Value::Int(i) => match i {
-                Number::Signed(s) => s.serialize(serializer),
-                Number::Unsigned(u) => u.serialize(serializer),
-            },
+            Value::Int(i) => i.serialize(serializer),
             Value::List(l) => l.serialize(serializer),
             Value::Dict(d) => d.serialize(serializer),
         }
     }
 }
 
+mod borrowed;
 mod de;
 mod index;
 mod ser;
 
-pub use index::Index;
+pub use borrowed::BorrowedValue;
+pub use index::{BorrowedIndex, Index};
 
 impl Value {
     /// Used to get a reference to a value with an index.
@@ -522,6 +1072,24 @@ where
     T::deserialize(value)
 }
 
+/// Deserializes an instance of `T` from a [Value] where `bool` values are
+/// deserialized from the integers 0 and 1.
+///
+/// See [`Deserializer::set_bool_as_int()`][crate::Deserializer::set_bool_as_int].
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid, if the data cannot be
+/// deserialized into an instance of `T`, and if a `bool` is not 0 or 1.
+#[allow(clippy::module_name_repetitions)]
+#[inline]
+pub fn from_value_bool_as_int<T>(value: Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(de::ValueDeserializer::new(value, true))
+}
+
 /// Serializes an instance of `T` into a [Value].
 ///
 /// # Errors
@@ -529,14 +1097,34 @@ where
 /// Serialization can fail if `T`'s implementation of
 /// [`Serialize`] decides to fail, if `T` contains
 /// unsupported types for serialization, or if `T` contains a map with
-/// non-string keys.
+/// unsupported key types.
 #[allow(clippy::module_name_repetitions)]
 #[inline]
 pub fn to_value<T>(value: &T) -> Result<Value, Error>
 where
     T: ?Sized + Serialize,
 {
-    value.serialize(ser::Serializer)
+    value.serialize(ser::Serializer::default())
+}
+
+/// Serializes an instance of `T` into a [Value] where `bool` values are
+/// serialized as the integers 0 and 1.
+///
+/// See [`Serializer::set_bool_as_int()`][crate::Serializer::set_bool_as_int].
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of
+/// [`Serialize`] decides to fail, if `T` contains
+/// unsupported types for serialization, or if `T` contains a map with
+/// unsupported key types.
+#[allow(clippy::module_name_repetitions)]
+#[inline]
+pub fn to_value_bool_as_int<T>(value: &T) -> Result<Value, Error>
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(ser::Serializer { bool_as_int: true })
 }
 
 #[cfg(test)]
@@ -545,7 +1133,7 @@ mod tests {
     use crate::error::Result;
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
-    use alloc::vec;
+    use alloc::{format, vec};
     #[cfg(feature = "std")]
     use std::vec;
 
@@ -581,6 +1169,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_number_from_str() -> Result<()> {
+        assert_eq!("0".parse::<Number>()?, Number::Unsigned(0));
+        assert_eq!("-3".parse::<Number>()?, Number::Signed(-3));
+        assert_eq!(
+            "18446744073709551615".parse::<Number>()?,
+            Number::Unsigned(u64::max_value())
+        );
+        let n = "-340282366920938463463374607431768211456".parse::<Number>()?;
+        assert_eq!(
+            n,
+            Number::Big(String::from("-340282366920938463463374607431768211456"))
+        );
+        assert_eq!(format!("{}", n), "-340282366920938463463374607431768211456");
+
+        for input in ["", "-", "+1", "01", "-0", "1a", " 1"].iter() {
+            assert!(input.parse::<Number>().is_err());
+        }
+
+        assert_eq!(Number::from(-3_i128), Number::Signed(-3));
+        assert_eq!(
+            Number::from(i128::min_value()),
+            Number::Big(String::from("-170141183460469231731687303715884105728"))
+        );
+        assert_eq!(Number::from(3_u128), Number::Unsigned(3));
+        assert_eq!(
+            Value::from(u128::max_value()),
+            Value::Int(Number::Big(String::from(
+                "340282366920938463463374607431768211455"
+            )))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_list() -> Result<()> {
         let input = "l4:spam4:eggse";
@@ -712,4 +1334,21 @@ mod tests {
         assert_eq!(v, expected.to_string().into_bytes());
         Ok(())
     }
+
+    #[test]
+    fn test_drop_deeply_nested() {
+        let mut value = Value::List(Vec::new());
+        for _ in 0..1_000_000 {
+            value = Value::List(vec![value]);
+        }
+        drop(value);
+
+        let mut value = Value::Dict(BTreeMap::new());
+        for _ in 0..1_000_000 {
+            let mut dict = BTreeMap::new();
+            dict.insert(ByteString::from(String::from("spam")), value);
+            value = Value::Dict(dict);
+        }
+        drop(value);
+    }
 }
@@ -0,0 +1,839 @@
+//! Represents valid Bencode data which borrows from the input.
+
+use super::{
+    visit_number_digits, BorrowedIndex, MapKeyClass, MapKeyClassifier, Number, Value, NUMBER_TOKEN,
+};
+use crate::ByteString;
+use core::{fmt, mem, slice, str};
+use serde::{
+    de::{Deserialize, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeMap},
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    borrow::Cow,
+    collections::{btree_map, BTreeMap},
+    string::String,
+    vec::{self, Vec},
+};
+#[cfg(feature = "std")]
+use std::{
+    borrow::Cow,
+    collections::{btree_map, BTreeMap},
+    string::String,
+    vec::{self, Vec},
+};
+
+/// Represents a valid Bencode value which can borrow its byte strings from the
+/// input.
+///
+/// Byte strings and dictionary keys are borrowed when the value is
+/// deserialized from a slice with [`from_slice()`][crate::from_slice], so only
+/// lists, dictionaries, and integers which do not fit into an [i64] or a [u64]
+/// allocate. If the source cannot lend its bytes (e.g. when reading from an
+/// [`IoRead`][crate::read::IoRead]), the byte strings are owned.
+///
+/// ```rust
+/// use bt_bencode::BorrowedValue;
+///
+/// let input = b"d1:ad2:id3:abce1:q4:ping1:t2:aa1:y1:qe";
+/// let value: BorrowedValue<'_> = bt_bencode::from_slice(input)?;
+/// assert_eq!(value["q"].as_str(), Some("ping"));
+/// assert_eq!(value["a"]["id"].as_byte_str(), Some(&b"abc"[..]));
+///
+/// let owned: bt_bencode::Value = value.into_owned();
+/// assert_eq!(owned["y"].as_str(), Some("q"));
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, PartialEq)]
+pub enum BorrowedValue<'a> {
+    /// A byte string.
+    ///
+    /// Encoded strings can contain non-UTF-8 bytes, so a byte string is used to represent
+    /// "strings".
+    ByteStr(Cow<'a, [u8]>),
+    /// An integer which can be signed or unsigned.
+    Int(Number),
+    /// A list of values.
+    List(Vec<BorrowedValue<'a>>),
+    /// A dictionary of values.
+    Dict(BTreeMap<Cow<'a, [u8]>, BorrowedValue<'a>>),
+}
+
+impl<'a> BorrowedValue<'a> {
+    /// If the value is a byte string, returns a reference to the underlying value.
+    #[must_use]
+    pub fn as_byte_str(&self) -> Option<&[u8]> {
+        match self {
+            BorrowedValue::ByteStr(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// If the value is a byte string, returns a mutable reference to the underlying value.
+    #[must_use]
+    pub fn as_byte_str_mut(&mut self) -> Option<&mut Cow<'a, [u8]>> {
+        match self {
+            BorrowedValue::ByteStr(ref mut b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// If the value is a UTF-8 string, returns a reference to the underlying value.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BorrowedValue::ByteStr(b) => str::from_utf8(b).ok(),
+            _ => None,
+        }
+    }
+
+    /// If the value is a UTF-8 string, returns a mutable reference to the underlying value.
+    ///
+    /// A borrowed byte string is copied before a reference is returned.
+    #[must_use]
+    pub fn as_str_mut(&mut self) -> Option<&mut str> {
+        match self {
+            BorrowedValue::ByteStr(ref mut b) if str::from_utf8(b).is_ok() => {
+                str::from_utf8_mut(b.to_mut()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// If the value is a number, returns a reference to the underlying value.
+    #[must_use]
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            BorrowedValue::Int(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// If the value is a [u64], returns the underlying value.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            BorrowedValue::Int(Number::Unsigned(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// If the value is a [i64], returns the underlying value.
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            BorrowedValue::Int(Number::Signed(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// If the value is an array, returns a reference to the underlying value.
+    #[must_use]
+    pub fn as_array(&self) -> Option<&Vec<BorrowedValue<'a>>> {
+        match self {
+            BorrowedValue::List(ref l) => Some(l),
+            _ => None,
+        }
+    }
+
+    /// If the value is an array, returns a mutable reference to the underlying value.
+    #[must_use]
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<BorrowedValue<'a>>> {
+        match self {
+            BorrowedValue::List(ref mut l) => Some(l),
+            _ => None,
+        }
+    }
+
+    /// If the value is a dictionary, returns a reference to the underlying value.
+    #[must_use]
+    pub fn as_dict(&self) -> Option<&BTreeMap<Cow<'a, [u8]>, BorrowedValue<'a>>> {
+        match self {
+            BorrowedValue::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// If the value is a dictionary, returns a mutable reference to the underlying value.
+    #[must_use]
+    pub fn as_dict_mut(&mut self) -> Option<&mut BTreeMap<Cow<'a, [u8]>, BorrowedValue<'a>>> {
+        match self {
+            BorrowedValue::Dict(ref mut d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the value is a byte string.
+    #[must_use]
+    pub fn is_byte_str(&self) -> bool {
+        self.as_byte_str().is_some()
+    }
+
+    /// Returns true if the value is a UTF-8 string.
+    ///
+    /// Note that the value could be a byte string but not a UTF-8 string.
+    #[must_use]
+    pub fn is_string(&self) -> bool {
+        self.as_str().is_some()
+    }
+
+    /// Returns true if the value is a an [u64].
+    ///
+    /// Note that the value could be a [i64].
+    #[must_use]
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    /// Returns true if the value is a an [i64].
+    ///
+    /// Note that the value could be a [u64].
+    #[must_use]
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    /// Returns true if the value is an array.
+    #[must_use]
+    pub fn is_array(&self) -> bool {
+        self.as_array().is_some()
+    }
+
+    /// Returns true if the value is a dictionary.
+    #[must_use]
+    pub fn is_dict(&self) -> bool {
+        self.as_dict().is_some()
+    }
+
+    /// Used to get a reference to a value with an index.
+    #[inline]
+    pub fn get<I: BorrowedIndex>(&self, index: I) -> Option<&BorrowedValue<'a>> {
+        index.index(self)
+    }
+
+    /// Used to get a mutable reference to a value with an index.
+    #[inline]
+    pub fn get_mut<I: BorrowedIndex>(&mut self, index: I) -> Option<&mut BorrowedValue<'a>> {
+        index.index_mut(self)
+    }
+
+    /// Converts the value into a [Value] which owns its data.
+    ///
+    /// Owned byte strings are moved, so only borrowed byte strings and
+    /// dictionary keys are copied.
+    #[must_use]
+    pub fn into_owned(self) -> Value {
+        /// A list or dictionary with its remaining elements and the elements
+        /// which have been converted.
+        enum Container<'a> {
+            List(vec::IntoIter<BorrowedValue<'a>>, Vec<Value>),
+            /// The remaining entries, the converted entries, and the key of
+            /// the entry which is being converted
+            Dict(
+                btree_map::IntoIter<Cow<'a, [u8]>, BorrowedValue<'a>>,
+                BTreeMap<ByteString, Value>,
+                ByteString,
+            ),
+        }
+
+        // Values are converted without recursion, so a deeply nested value
+        // does not overflow the stack.
+        let mut stack: Vec<Container<'a>> = Vec::new();
+        let mut next = self;
+
+        loop {
+            let mut value = match next {
+                BorrowedValue::ByteStr(ref mut b) => Value::ByteStr(ByteString::from(
+                    mem::replace(b, Cow::Borrowed(&[])).into_owned(),
+                )),
+                BorrowedValue::Int(ref mut n) => Value::Int(mem::replace(n, Number::Unsigned(0))),
+                BorrowedValue::List(ref mut l) => {
+                    let l = mem::replace(l, Vec::new());
+                    let len = l.len();
+                    stack.push(Container::List(l.into_iter(), Vec::with_capacity(len)));
+                    Value::List(Vec::new())
+                }
+                BorrowedValue::Dict(ref mut d) => {
+                    let d = mem::replace(d, BTreeMap::new());
+                    stack.push(Container::Dict(
+                        d.into_iter(),
+                        BTreeMap::new(),
+                        ByteString::from(Vec::new()),
+                    ));
+                    Value::Dict(BTreeMap::new())
+                }
+            };
+            let mut is_converted = match value {
+                Value::List(_) | Value::Dict(_) => false,
+                Value::ByteStr(_) | Value::Int(_) => true,
+            };
+
+            // Adds the converted value to its container and finds the next
+            // value to convert.
+            next = loop {
+                match stack.last_mut() {
+                    None => return value,
+                    Some(Container::List(iter, values)) => {
+                        if is_converted {
+                            values.push(value);
+                        }
+                        if let Some(next) = iter.next() {
+                            break next;
+                        }
+                        value = Value::List(mem::replace(values, Vec::new()));
+                    }
+                    Some(Container::Dict(iter, entries, key)) => {
+                        if is_converted {
+                            entries.insert(mem::replace(key, ByteString::from(Vec::new())), value);
+                        }
+                        if let Some((next_key, next)) = iter.next() {
+                            *key = ByteString::from(next_key.into_owned());
+                            break next;
+                        }
+                        value = Value::Dict(mem::replace(entries, BTreeMap::new()));
+                    }
+                }
+                stack.pop();
+                is_converted = true;
+            };
+        }
+    }
+}
+
+impl<'a> Drop for BorrowedValue<'a> {
+    /// Drops nested lists and dictionaries without recursion.
+    ///
+    /// Otherwise, dropping a deeply nested value could overflow the stack.
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        take_nested(self, &mut stack);
+        while let Some(mut value) = stack.pop() {
+            take_nested(&mut value, &mut stack);
+        }
+    }
+}
+
+/// Moves the elements of a list or dictionary into the stack if any of them
+/// are non-empty lists or dictionaries.
+fn take_nested<'a>(value: &mut BorrowedValue<'a>, stack: &mut Vec<BorrowedValue<'a>>) {
+    fn is_nested(value: &BorrowedValue<'_>) -> bool {
+        match value {
+            BorrowedValue::List(l) => !l.is_empty(),
+            BorrowedValue::Dict(d) => !d.is_empty(),
+            BorrowedValue::ByteStr(_) | BorrowedValue::Int(_) => false,
+        }
+    }
+
+    match value {
+        BorrowedValue::List(l) => {
+            if l.iter().any(is_nested) {
+                stack.append(l);
+            }
+        }
+        BorrowedValue::Dict(d) => {
+            if d.values().any(is_nested) {
+                stack.extend(mem::replace(d, BTreeMap::new()).into_iter().map(|(_, v)| v));
+            }
+        }
+        BorrowedValue::ByteStr(_) | BorrowedValue::Int(_) => {}
+    }
+}
+
+impl<'a> fmt::Debug for BorrowedValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct DebugByteStr<'a>(&'a [u8]);
+
+        impl<'a> fmt::Debug for DebugByteStr<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match str::from_utf8(self.0) {
+                    Ok(key) => f.debug_tuple("ByteStr").field(&key).finish(),
+                    Err(_) => f.debug_tuple("ByteStr").field(&self.0).finish(),
+                }
+            }
+        }
+
+        match self {
+            BorrowedValue::ByteStr(arg0) => fmt::Debug::fmt(&DebugByteStr(arg0), f),
+            BorrowedValue::Int(arg0) => f.debug_tuple("Int").field(arg0).finish(),
+            BorrowedValue::List(arg0) => f.debug_tuple("List").field(arg0).finish(),
+            BorrowedValue::Dict(arg0) => {
+                struct DebugDict<'b, 'a>(&'b BTreeMap<Cow<'a, [u8]>, BorrowedValue<'a>>);
+
+                impl<'b, 'a> fmt::Debug for DebugDict<'b, 'a> {
+                    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        let mut d = &mut f.debug_map();
+                        for (key, value) in self.0 {
+                            d = d.entry(&DebugByteStr(key), value);
+                        }
+                        d.finish()
+                    }
+                }
+
+                f.debug_tuple("Dict").field(&DebugDict(arg0)).finish()
+            }
+        }
+    }
+}
+
+impl<'a> From<&'a [u8]> for BorrowedValue<'a> {
+    fn from(other: &'a [u8]) -> BorrowedValue<'a> {
+        BorrowedValue::ByteStr(Cow::Borrowed(other))
+    }
+}
+
+impl<'a> From<&'a str> for BorrowedValue<'a> {
+    fn from(other: &'a str) -> BorrowedValue<'a> {
+        BorrowedValue::ByteStr(Cow::Borrowed(other.as_bytes()))
+    }
+}
+
+impl<'a> From<&'a Value> for BorrowedValue<'a> {
+    /// Borrows the byte strings and dictionary keys of a [Value].
+    fn from(other: &'a Value) -> BorrowedValue<'a> {
+        /// A list or dictionary with its remaining elements and the elements
+        /// which have been converted.
+        enum Container<'a> {
+            List(slice::Iter<'a, Value>, Vec<BorrowedValue<'a>>),
+            /// The remaining entries, the converted entries, and the key of
+            /// the entry which is being converted
+            Dict(
+                btree_map::Iter<'a, ByteString, Value>,
+                BTreeMap<Cow<'a, [u8]>, BorrowedValue<'a>>,
+                &'a [u8],
+            ),
+        }
+
+        // Values are converted without recursion, so a deeply nested value
+        // does not overflow the stack.
+        let mut stack: Vec<Container<'a>> = Vec::new();
+        let mut next = other;
+
+        loop {
+            let (mut value, mut is_converted) = match *next {
+                Value::ByteStr(ref b) => {
+                    (BorrowedValue::ByteStr(Cow::Borrowed(b.as_slice())), true)
+                }
+                Value::Int(ref n) => (BorrowedValue::Int(n.clone()), true),
+                Value::List(ref l) => {
+                    stack.push(Container::List(l.iter(), Vec::with_capacity(l.len())));
+                    (BorrowedValue::List(Vec::new()), false)
+                }
+                Value::Dict(ref d) => {
+                    stack.push(Container::Dict(d.iter(), BTreeMap::new(), &[]));
+                    (BorrowedValue::Dict(BTreeMap::new()), false)
+                }
+            };
+
+            // Adds the converted value to its container and finds the next
+            // value to convert.
+            next = loop {
+                match stack.last_mut() {
+                    None => return value,
+                    Some(Container::List(iter, values)) => {
+                        if is_converted {
+                            values.push(value);
+                        }
+                        if let Some(next) = iter.next() {
+                            break next;
+                        }
+                        value = BorrowedValue::List(mem::replace(values, Vec::new()));
+                    }
+                    Some(Container::Dict(iter, entries, key)) => {
+                        if is_converted {
+                            entries.insert(Cow::Borrowed(*key), value);
+                        }
+                        if let Some((next_key, next)) = iter.next() {
+                            *key = next_key.as_slice();
+                            break next;
+                        }
+                        value = BorrowedValue::Dict(mem::replace(entries, BTreeMap::new()));
+                    }
+                }
+                stack.pop();
+                is_converted = true;
+            };
+        }
+    }
+}
+
+impl<'a> From<BorrowedValue<'a>> for Value {
+    fn from(other: BorrowedValue<'a>) -> Value {
+        other.into_owned()
+    }
+}
+
+impl<'de> Deserialize<'de> for BorrowedValue<'de> {
+    #[inline]
+    fn deserialize<T>(deserializer: T) -> Result<BorrowedValue<'de>, T::Error>
+    where
+        T: serde::Deserializer<'de>,
+    {
+        struct BorrowedValueVisitor;
+
+        impl<'de> Visitor<'de> for BorrowedValueVisitor {
+            type Value = BorrowedValue<'de>;
+
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("any valid Bencode value")
+            }
+
+            #[inline]
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::Int(Number::Signed(value)))
+            }
+
+            #[inline]
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::Int(Number::Unsigned(value)))
+            }
+
+            #[inline]
+            fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::Int(Number::from(value)))
+            }
+
+            #[inline]
+            fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::Int(Number::from(value)))
+            }
+
+            #[inline]
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::ByteStr(Cow::Owned(
+                    value.as_bytes().to_vec(),
+                )))
+            }
+
+            #[inline]
+            fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::ByteStr(Cow::Borrowed(value.as_bytes())))
+            }
+
+            #[inline]
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::ByteStr(Cow::Owned(value.into_bytes())))
+            }
+
+            #[inline]
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::ByteStr(Cow::Owned(value.to_vec())))
+            }
+
+            #[inline]
+            fn visit_borrowed_bytes<E>(self, value: &'de [u8]) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::ByteStr(Cow::Borrowed(value)))
+            }
+
+            #[inline]
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(BorrowedValue::ByteStr(Cow::Owned(value)))
+            }
+
+            #[inline]
+            fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                Deserialize::deserialize(deserializer)
+            }
+
+            fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut list = Vec::new();
+                if let Some(size_hint) = visitor.size_hint() {
+                    list.reserve(size_hint);
+                }
+                while let Some(elem) = visitor.next_element()? {
+                    list.push(elem);
+                }
+                Ok(BorrowedValue::List(list))
+            }
+
+            fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                let mut dict = BTreeMap::new();
+                match visitor.next_key_seed(MapKeyClassifier)? {
+                    Some(MapKeyClass::Number) => {
+                        return visit_number_digits(visitor).map(BorrowedValue::Int)
+                    }
+                    Some(MapKeyClass::Encoded(token)) => {
+                        let value = visitor.next_value()?;
+                        dict.insert(Cow::Borrowed(token.as_bytes()), value);
+                    }
+                    Some(MapKeyClass::Key(key)) => {
+                        let value = visitor.next_value()?;
+                        dict.insert(key, value);
+                    }
+                    None => return Ok(BorrowedValue::Dict(dict)),
+                }
+                while let Some(key) = visitor.next_key_seed(MapKeyClassifier)? {
+                    // Only the first key can carry the digits of a big number.
+                    let key = match key {
+                        MapKeyClass::Key(key) => key,
+                        MapKeyClass::Number => Cow::Borrowed(NUMBER_TOKEN.as_bytes()),
+                        MapKeyClass::Encoded(token) => Cow::Borrowed(token.as_bytes()),
+                    };
+                    let value = visitor.next_value()?;
+                    dict.insert(key, value);
+                }
+                Ok(BorrowedValue::Dict(dict))
+            }
+        }
+
+        deserializer.deserialize_any(BorrowedValueVisitor)
+    }
+}
+
+/// Serializes a byte string which is not a [ByteString].
+struct SerializeByteStr<'a>(&'a [u8]);
+
+impl<'a> Serialize for SerializeByteStr<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl<'a> Serialize for BorrowedValue<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            BorrowedValue::ByteStr(ref b) => serializer.serialize_bytes(b),
+            BorrowedValue::Int(i) => i.serialize(serializer),
+            BorrowedValue::List(l) => l.serialize(serializer),
+            BorrowedValue::Dict(d) => {
+                let mut map = serializer.serialize_map(Some(d.len()))?;
+                for (key, value) in d {
+                    map.serialize_entry(&SerializeByteStr(key), value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ErrorKind, Result};
+    use serde_derive::Deserialize;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{format, vec};
+    #[cfg(feature = "std")]
+    use std::vec;
+
+    const INPUT: &[u8] =
+        b"d1:ad2:id20:abcdefghij01234567896:targeti-3ee1:q9:find_node1:t2:aa1:y1:qe";
+
+    fn is_borrowed(value: &BorrowedValue<'_>) -> bool {
+        match value {
+            BorrowedValue::ByteStr(Cow::Borrowed(_)) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_deserialize_from_slice() -> Result<()> {
+        let value: BorrowedValue<'_> = crate::from_slice(INPUT)?;
+        assert!(is_borrowed(&value["q"]));
+        assert!(is_borrowed(&value["a"]["id"]));
+        assert_eq!(value["q"].as_str(), Some("find_node"));
+        assert_eq!(value["a"]["target"].as_i64(), Some(-3));
+        assert_eq!(
+            value["a"].get("id").and_then(BorrowedValue::as_byte_str),
+            Some(&b"abcdefghij0123456789"[..])
+        );
+        assert!(value.get("r").is_none());
+        assert!(value.as_dict().unwrap().keys().all(|key| match key {
+            Cow::Borrowed(_) => true,
+            Cow::Owned(_) => false,
+        }));
+
+        let value: BorrowedValue<'_> =
+            crate::from_slice(b"li1ei170141183460469231731687303715884105728ee")?;
+        assert_eq!(value[0].as_u64(), Some(1));
+        assert_eq!(
+            value[1].as_number(),
+            Some(&Number::Big(String::from(
+                "170141183460469231731687303715884105728"
+            )))
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_from_reader() -> Result<()> {
+        let mut de = crate::Deserializer::from_reader(INPUT);
+        let value = BorrowedValue::deserialize(&mut de)?;
+        assert!(!is_borrowed(&value["q"]));
+        assert_eq!(value, crate::from_slice::<BorrowedValue<'_>>(INPUT)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_owned() -> Result<()> {
+        let value: BorrowedValue<'_> = crate::from_slice(INPUT)?;
+        let owned = value.clone().into_owned();
+        assert_eq!(owned, crate::from_slice::<Value>(INPUT)?);
+        assert_eq!(BorrowedValue::from(&owned), value);
+        assert_eq!(Value::from(value), owned);
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_owned_nested() -> Result<()> {
+        let input = b"d1:alle1:bd1:cdee1:cli1eli2ed1:d1:eeeeee";
+        let value: BorrowedValue<'_> = crate::from_slice(input)?;
+        assert_eq!(value.into_owned(), crate::from_slice::<Value>(input)?);
+
+        let mut value = BorrowedValue::List(Vec::new());
+        for _ in 0..100_000 {
+            let mut dict = BTreeMap::new();
+            dict.insert(
+                Cow::Borrowed(&b"spam"[..]),
+                BorrowedValue::List(vec![value]),
+            );
+            value = BorrowedValue::Dict(dict);
+        }
+        let mut owned = value.into_owned();
+        for _ in 0..100_000 {
+            owned = mem::replace(&mut owned["spam"][0], Value::List(Vec::new()));
+        }
+        assert_eq!(owned, Value::List(Vec::new()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_value_nested() -> Result<()> {
+        let input = b"d1:alle1:bd1:cdee1:cli1eli2ed1:d1:eeeeee";
+        let value: Value = crate::from_slice(input)?;
+        assert_eq!(
+            BorrowedValue::from(&value),
+            crate::from_slice::<BorrowedValue<'_>>(input)?
+        );
+
+        let mut value = Value::List(Vec::new());
+        for _ in 0..100_000 {
+            let mut dict = BTreeMap::new();
+            dict.insert(ByteString::from(&b"spam"[..]), Value::List(vec![value]));
+            value = Value::Dict(dict);
+        }
+        let mut borrowed = BorrowedValue::from(&value);
+        for _ in 0..100_000 {
+            borrowed = mem::replace(&mut borrowed["spam"][0], BorrowedValue::List(Vec::new()));
+        }
+        assert_eq!(borrowed, BorrowedValue::List(Vec::new()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize() -> Result<()> {
+        let value: BorrowedValue<'_> = crate::from_slice(INPUT)?;
+        assert_eq!(crate::to_vec(&value)?, INPUT);
+        assert_eq!(crate::to_value(&value)?, crate::from_slice::<Value>(INPUT)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_mut() -> Result<()> {
+        let mut value: BorrowedValue<'_> = crate::from_slice(INPUT)?;
+        value["a"]["target"] = BorrowedValue::Int(Number::Unsigned(7));
+        value["t"].as_str_mut().unwrap().make_ascii_uppercase();
+        assert!(!is_borrowed(&value["t"]));
+        assert_eq!(value["t"].as_str(), Some("AA"));
+        assert_eq!(
+            value
+                .get_mut("a")
+                .and_then(|a| a.get_mut("target"))
+                .and_then(|target| target.as_u64()),
+            Some(7)
+        );
+        assert_eq!(
+            crate::to_vec(&value)?,
+            &b"d1:ad2:id20:abcdefghij01234567896:targeti7ee1:q9:find_node1:t2:AA1:y1:qe"[..]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_from_borrowed_value() -> Result<()> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Args<'a> {
+            id: &'a [u8],
+            target: i64,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Query<'a> {
+            #[serde(borrow)]
+            a: Args<'a>,
+            q: &'a str,
+            t: &'a [u8],
+            y: &'a str,
+        }
+
+        let value: BorrowedValue<'_> = crate::from_slice(INPUT)?;
+        let query = Query::deserialize(value)?;
+        assert_eq!(
+            query,
+            Query {
+                a: Args {
+                    id: b"abcdefghij0123456789",
+                    target: -3,
+                },
+                q: "find_node",
+                t: b"aa",
+                y: "q",
+            }
+        );
+
+        let value: BorrowedValue<'_> = crate::from_slice(INPUT)?;
+        let err = u64::deserialize(value["q"].clone()).unwrap_err();
+        match err.kind() {
+            ErrorKind::InvalidType { .. } => {}
+            _ => panic!("wrong error type: {:?}", err),
+        }
+
+        let err = Query::deserialize(BorrowedValue::Dict(
+            vec![(
+                Cow::Borrowed(&b"a"[..]),
+                BorrowedValue::Dict(
+                    vec![
+                        (Cow::Borrowed(&b"id"[..]), BorrowedValue::from("abc")),
+                        (Cow::Borrowed(&b"target"[..]), BorrowedValue::from("x")),
+                    ]
+                    .into_iter()
+                    .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        ))
+        .unwrap_err();
+        assert_eq!(format!("{}", err.path()), "a.target");
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug() -> Result<()> {
+        let value: BorrowedValue<'_> = crate::from_slice(b"d1:ali1e1:\xffee")?;
+        assert_eq!(
+            format!("{:?}", value),
+            "Dict({ByteStr(\"a\"): List([Int(Unsigned(1)), ByteStr([255])])})"
+        );
+        Ok(())
+    }
+}
@@ -1,35 +1,157 @@
-//! Deserializes from a [Value].
+//! Deserializes from a [Value] or a [BorrowedValue].
 
-use super::{Number, Value};
+use super::{visit_big_number, BorrowedValue, Number, Value};
+use crate::de::parse_integer_key;
 use crate::error::Error;
+use crate::raw::RAW_VALUE_TOKEN;
 use crate::ByteString;
-use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::de::{
+    DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, Unexpected, VariantAccess,
+    Visitor,
+};
 use serde::forward_to_deserialize_any;
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::{borrow::Cow, collections::BTreeMap, vec};
-use core::slice;
+use alloc::{borrow::Cow, collections::BTreeMap, vec, vec::Vec};
+use core::{mem, slice};
 #[cfg(feature = "std")]
-use std::{borrow::Cow, collections::BTreeMap, vec};
+use std::{borrow::Cow, collections::BTreeMap, vec, vec::Vec};
 
 impl<'de> serde::Deserializer<'de> for Value {
     type Error = Error;
 
+    #[inline]
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        match self {
-            Value::ByteStr(s) => visitor.visit_byte_buf(s.into_vec()),
+        ValueDeserializer::new(self, false).deserialize_any(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 unit unit_struct
+
+        i8 i16 i32 i64 i128
+        u8 u16 u32 u64 u128
+
+        char str string bytes byte_buf
+
+        seq map
+
+        struct identifier ignored_any
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ValueDeserializer::new(self, false).deserialize_enum(name, variants, visitor)
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == RAW_VALUE_TOKEN {
+            return visitor.visit_byte_buf(crate::to_vec(&self)?);
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Deserializes from an owned [Value] with options which are inherited by
+/// the nested values.
+pub(super) struct ValueDeserializer {
+    value: Value,
+    /// If `bool` values are deserialized from the integers 0 and 1
+    bool_as_int: bool,
+}
+
+impl ValueDeserializer {
+    pub(super) fn new(value: Value, bool_as_int: bool) -> Self {
+        ValueDeserializer { value, bool_as_int }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let bool_as_int = self.bool_as_int;
+        let mut value = self.value;
+        match &mut value {
+            Value::ByteStr(s) => {
+                visitor.visit_byte_buf(mem::replace(s, ByteString::from(Vec::new())).into_vec())
+            }
             Value::Int(n) => match n {
-                Number::Signed(s) => visitor.visit_i64(s),
-                Number::Unsigned(u) => visitor.visit_u64(u),
+                Number::Signed(s) => visitor.visit_i64(*s),
+                Number::Unsigned(u) => visitor.visit_u64(*u),
+                Number::Big(digits) => visit_big_number(digits, visitor),
             },
             Value::List(l) => {
+                let l = mem::replace(l, Vec::new());
                 let len = l.len();
 
                 let mut deserializer = ListDeserializer {
                     iter: l.into_iter(),
+                    index: 0,
+                    bool_as_int,
                 };
                 let seq = visitor.visit_seq(&mut deserializer)?;
                 if deserializer.iter.len() == 0 {
@@ -42,10 +164,12 @@ impl<'de> serde::Deserializer<'de> for Value {
                 }
             }
             Value::Dict(d) => {
+                let d = mem::replace(d, BTreeMap::new());
                 let len = d.len();
                 let mut deserializer = DictDeserializer {
                     iter: d.into_iter(),
                     value: None,
+                    bool_as_int,
                 };
                 let map = visitor.visit_map(&mut deserializer)?;
                 if deserializer.iter.len() == 0 {
@@ -60,17 +184,76 @@ impl<'de> serde::Deserializer<'de> for Value {
         }
     }
 
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if !self.bool_as_int {
+            return self.deserialize_any(visitor);
+        }
+
+        match self.value {
+            Value::Int(Number::Signed(0)) | Value::Int(Number::Unsigned(0)) => {
+                visitor.visit_bool(false)
+            }
+            Value::Int(Number::Signed(1)) | Value::Int(Number::Unsigned(1)) => {
+                visitor.visit_bool(true)
+            }
+            Value::Int(_) => Err(serde::de::Error::invalid_value(
+                unexpected(&self.value),
+                &"the integer 0 or 1",
+            )),
+            _ => Err(serde::de::Error::invalid_type(
+                unexpected(&self.value),
+                &visitor,
+            )),
+        }
+    }
+
     forward_to_deserialize_any! {
-        bool f32 f64 unit unit_struct
+        f32 f64 unit unit_struct
 
-        i8 i16 i32 i64
-        u8 u16 u32 u64
+        i8 i16 i32 i64 i128
+        u8 u16 u32 u64 u128
 
         char str string bytes byte_buf
 
         seq map
 
-        struct enum identifier ignored_any
+        struct identifier ignored_any
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let bool_as_int = self.bool_as_int;
+        let mut value = self.value;
+        match &mut value {
+            Value::ByteStr(s) => visitor.visit_enum(EnumDeserializer::<ValueDeserializer> {
+                variant: Cow::Owned(mem::replace(s, ByteString::from(Vec::new())).into_vec()),
+                value: None,
+            }),
+            Value::Dict(d) => {
+                let mut iter = mem::replace(d, BTreeMap::new()).into_iter();
+                match (iter.next(), iter.next()) {
+                    (Some((variant, value)), None) => visitor.visit_enum(EnumDeserializer {
+                        variant: Cow::Owned(variant.into_vec()),
+                        value: Some(ValueDeserializer::new(value, bool_as_int)),
+                    }),
+                    _ => Err(serde::de::Error::invalid_value(
+                        Unexpected::Map,
+                        &"a dictionary with a single key",
+                    )),
+                }
+            }
+            _ => Err(serde::de::Error::invalid_type(unexpected(&value), &visitor)),
+        }
     }
 
     #[inline]
@@ -84,12 +267,15 @@ impl<'de> serde::Deserializer<'de> for Value {
     #[inline]
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == RAW_VALUE_TOKEN {
+            return visitor.visit_byte_buf(crate::to_vec(&self.value)?);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -120,16 +306,11 @@ impl<'de> serde::Deserializer<'de> for Value {
     }
 }
 
-impl<'de> IntoDeserializer<'de, Error> for Value {
-    type Deserializer = Self;
-
-    fn into_deserializer(self) -> Self::Deserializer {
-        self
-    }
-}
-
 struct ListDeserializer {
     iter: vec::IntoIter<Value>,
+    /// The index of the next element
+    index: usize,
+    bool_as_int: bool,
 }
 
 impl<'de> SeqAccess<'de> for ListDeserializer {
@@ -140,7 +321,13 @@ impl<'de> SeqAccess<'de> for ListDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(ValueDeserializer::new(value, self.bool_as_int))
+                    .map(Some)
+                    .map_err(|err| err.with_path_index(index))
+            }
             None => Ok(None),
         }
     }
@@ -155,7 +342,9 @@ impl<'de> SeqAccess<'de> for ListDeserializer {
 
 struct DictDeserializer {
     iter: <BTreeMap<ByteString, Value> as IntoIterator>::IntoIter,
-    value: Option<Value>,
+    /// The key and the value of the current entry
+    value: Option<(ByteString, Value)>,
+    bool_as_int: bool,
 }
 
 impl<'de> MapAccess<'de> for DictDeserializer {
@@ -166,10 +355,12 @@ impl<'de> MapAccess<'de> for DictDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some((key, value)) => {
-                self.value = Some(value);
+            Some(entry) => {
+                // Replaces the entry if the previous value was not read.
+                self.value = None;
+                let (key, _) = self.value.get_or_insert(entry);
                 let key_de = DictKey {
-                    key: Cow::Owned(key),
+                    key: KeyBytes::Transient(key.as_slice()),
                 };
                 seed.deserialize(key_de).map(Some)
             }
@@ -182,7 +373,9 @@ impl<'de> MapAccess<'de> for DictDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some((key, value)) => seed
+                .deserialize(ValueDeserializer::new(value, self.bool_as_int))
+                .map_err(|err| err.with_path_key(&key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -195,11 +388,59 @@ impl<'de> MapAccess<'de> for DictDeserializer {
     }
 }
 
-struct DictKey<'a> {
-    key: Cow<'a, ByteString>,
+macro_rules! deserialize_integer_key {
+    ($method:ident => $visit:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match parse_integer_key(&self.key) {
+                Some(num) => visitor.$visit(num),
+                None => Err(serde::de::Error::invalid_value(
+                    Unexpected::Bytes(&self.key),
+                    &visitor,
+                )),
+            }
+        }
+    };
+}
+
+/// The bytes of a dictionary key.
+enum KeyBytes<'de, 'a> {
+    /// Bytes which live as long as the deserializer's input
+    Borrowed(&'de [u8]),
+    /// Bytes which only live as long as the key's deserializer
+    Transient(&'a [u8]),
+    /// Bytes which are given to the visitor
+    Owned(Vec<u8>),
 }
 
-impl<'de> serde::Deserializer<'de> for DictKey<'de> {
+impl<'de, 'a> From<Cow<'de, [u8]>> for KeyBytes<'de, 'a> {
+    fn from(key: Cow<'de, [u8]>) -> Self {
+        match key {
+            Cow::Borrowed(bytes) => KeyBytes::Borrowed(bytes),
+            Cow::Owned(bytes) => KeyBytes::Owned(bytes),
+        }
+    }
+}
+
+impl<'de, 'a> core::ops::Deref for KeyBytes<'de, 'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match *self {
+            KeyBytes::Borrowed(bytes) => bytes,
+            KeyBytes::Transient(bytes) => bytes,
+            KeyBytes::Owned(ref bytes) => bytes,
+        }
+    }
+}
+
+struct DictKey<'de, 'a> {
+    key: KeyBytes<'de, 'a>,
+}
+
+impl<'de, 'a> serde::Deserializer<'de> for DictKey<'de, 'a> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -207,8 +448,9 @@ impl<'de> serde::Deserializer<'de> for DictKey<'de> {
         V: Visitor<'de>,
     {
         match self.key {
-            Cow::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
-            Cow::Owned(bytes) => visitor.visit_byte_buf(bytes.into_vec()),
+            KeyBytes::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+            KeyBytes::Transient(bytes) => visitor.visit_bytes(bytes),
+            KeyBytes::Owned(bytes) => visitor.visit_byte_buf(bytes),
         }
     }
 
@@ -232,10 +474,55 @@ impl<'de> serde::Deserializer<'de> for DictKey<'de> {
         visitor.visit_newtype_struct(self)
     }
 
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match core::str::from_utf8(&self.key) {
+            Ok(s) => visitor.visit_str(s),
+            Err(_) => Err(serde::de::Error::invalid_value(
+                Unexpected::Bytes(&self.key),
+                &visitor,
+            )),
+        }
+    }
+
+    deserialize_integer_key!(deserialize_i8 => visit_i8);
+    deserialize_integer_key!(deserialize_i16 => visit_i16);
+    deserialize_integer_key!(deserialize_i32 => visit_i32);
+    deserialize_integer_key!(deserialize_i64 => visit_i64);
+    deserialize_integer_key!(deserialize_i128 => visit_i128);
+    deserialize_integer_key!(deserialize_u8 => visit_u8);
+    deserialize_integer_key!(deserialize_u16 => visit_u16);
+    deserialize_integer_key!(deserialize_u32 => visit_u32);
+    deserialize_integer_key!(deserialize_u64 => visit_u64);
+    deserialize_integer_key!(deserialize_u128 => visit_u128);
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let variant = match self.key {
+            KeyBytes::Borrowed(bytes) => Cow::Borrowed(bytes),
+            KeyBytes::Transient(bytes) => Cow::Owned(bytes.to_vec()),
+            KeyBytes::Owned(bytes) => Cow::Owned(bytes),
+        };
+        visitor.visit_enum(EnumDeserializer::<ValueDeserializer> {
+            variant,
+            value: None,
+        })
+    }
+
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bool f32 f64 str string
         bytes byte_buf unit unit_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        tuple_struct map struct identifier ignored_any
     }
 }
 
@@ -251,11 +538,15 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
             Value::Int(n) => match n {
                 Number::Signed(s) => visitor.visit_i64(*s),
                 Number::Unsigned(u) => visitor.visit_u64(*u),
+                Number::Big(digits) => visit_big_number(digits, visitor),
             },
             Value::List(l) => {
                 let len = l.len();
 
-                let mut deserializer = ListRefDeserializer { iter: l.iter() };
+                let mut deserializer = ListRefDeserializer {
+                    iter: l.iter(),
+                    index: 0,
+                };
 
                 let seq = visitor.visit_seq(&mut deserializer)?;
                 if deserializer.iter.len() == 0 {
@@ -290,14 +581,45 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     forward_to_deserialize_any! {
         bool f32 f64 unit unit_struct
 
-        i8 i16 i32 i64
-        u8 u16 u32 u64
+        i8 i16 i32 i64 i128
+        u8 u16 u32 u64 u128
 
         char str string bytes byte_buf
 
         seq map
 
-        struct enum identifier ignored_any
+        struct identifier ignored_any
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::ByteStr(s) => visitor.visit_enum(EnumDeserializer::<&Value> {
+                variant: Cow::Borrowed(s.as_slice()),
+                value: None,
+            }),
+            Value::Dict(d) => {
+                let mut iter = d.iter();
+                match (iter.next(), iter.next()) {
+                    (Some((variant, value)), None) => visitor.visit_enum(EnumDeserializer {
+                        variant: Cow::Borrowed(variant.as_slice()),
+                        value: Some(value),
+                    }),
+                    _ => Err(serde::de::Error::invalid_value(
+                        Unexpected::Map,
+                        &"a dictionary with a single key",
+                    )),
+                }
+            }
+            _ => Err(serde::de::Error::invalid_type(unexpected(self), &visitor)),
+        }
     }
 
     #[inline]
@@ -311,12 +633,15 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     #[inline]
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == RAW_VALUE_TOKEN {
+            return visitor.visit_byte_buf(crate::to_vec(self)?);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -349,6 +674,8 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
 
 struct ListRefDeserializer<'a> {
     iter: slice::Iter<'a, Value>,
+    /// The index of the next element
+    index: usize,
 }
 
 impl<'a> SeqAccess<'a> for ListRefDeserializer<'a> {
@@ -359,7 +686,13 @@ impl<'a> SeqAccess<'a> for ListRefDeserializer<'a> {
         T: DeserializeSeed<'a>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(value)
+                    .map(Some)
+                    .map_err(|err| err.with_path_index(index))
+            }
             None => Ok(None),
         }
     }
@@ -374,7 +707,8 @@ impl<'a> SeqAccess<'a> for ListRefDeserializer<'a> {
 
 struct DictRefDeserializer<'a> {
     iter: <&'a BTreeMap<ByteString, Value> as IntoIterator>::IntoIter,
-    value: Option<&'a Value>,
+    /// The key and the value of the current entry
+    value: Option<(&'a ByteString, &'a Value)>,
 }
 
 impl<'a> MapAccess<'a> for DictRefDeserializer<'a> {
@@ -386,9 +720,9 @@ impl<'a> MapAccess<'a> for DictRefDeserializer<'a> {
     {
         match self.iter.next() {
             Some((key, value)) => {
-                self.value = Some(value);
+                self.value = Some((key, value));
                 let key_de = DictKey {
-                    key: Cow::Borrowed(key),
+                    key: KeyBytes::Borrowed(key.as_slice()),
                 };
                 seed.deserialize(key_de).map(Some)
             }
@@ -401,7 +735,253 @@ impl<'a> MapAccess<'a> for DictRefDeserializer<'a> {
         T: DeserializeSeed<'a>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some((key, value)) => seed
+                .deserialize(value)
+                .map_err(|err| err.with_path_key(key)),
+            None => Err(serde::de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for BorrowedValue<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut value = self;
+        match &mut value {
+            BorrowedValue::ByteStr(s) => match mem::replace(s, Cow::Borrowed(&[])) {
+                Cow::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Cow::Owned(bytes) => visitor.visit_byte_buf(bytes),
+            },
+            BorrowedValue::Int(n) => match n {
+                Number::Signed(s) => visitor.visit_i64(*s),
+                Number::Unsigned(u) => visitor.visit_u64(*u),
+                Number::Big(digits) => visit_big_number(digits, visitor),
+            },
+            BorrowedValue::List(l) => {
+                let l = mem::replace(l, Vec::new());
+                let len = l.len();
+
+                let mut deserializer = BorrowedListDeserializer {
+                    iter: l.into_iter(),
+                    index: 0,
+                };
+                let seq = visitor.visit_seq(&mut deserializer)?;
+                if deserializer.iter.len() == 0 {
+                    Ok(seq)
+                } else {
+                    Err(serde::de::Error::invalid_length(
+                        len,
+                        &"expected more elements to be consumed in list",
+                    ))
+                }
+            }
+            BorrowedValue::Dict(d) => {
+                let d = mem::replace(d, BTreeMap::new());
+                let len = d.len();
+                let mut deserializer = BorrowedDictDeserializer {
+                    iter: d.into_iter(),
+                    value: None,
+                };
+                let map = visitor.visit_map(&mut deserializer)?;
+                if deserializer.iter.len() == 0 {
+                    Ok(map)
+                } else {
+                    Err(serde::de::Error::invalid_length(
+                        len,
+                        &"expected more elements to be consumed in dict",
+                    ))
+                }
+            }
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 unit unit_struct
+
+        i8 i16 i32 i64 i128
+        u8 u16 u32 u64 u128
+
+        char str string bytes byte_buf
+
+        seq map
+
+        struct identifier ignored_any
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut value = self;
+        match &mut value {
+            BorrowedValue::ByteStr(s) => {
+                visitor.visit_enum(EnumDeserializer::<BorrowedValue<'de>> {
+                    variant: mem::replace(s, Cow::Borrowed(&[])),
+                    value: None,
+                })
+            }
+            BorrowedValue::Dict(d) => {
+                let mut iter = mem::replace(d, BTreeMap::new()).into_iter();
+                match (iter.next(), iter.next()) {
+                    (Some((variant, value)), None) => visitor.visit_enum(EnumDeserializer {
+                        variant,
+                        value: Some(value),
+                    }),
+                    _ => Err(serde::de::Error::invalid_value(
+                        Unexpected::Map,
+                        &"a dictionary with a single key",
+                    )),
+                }
+            }
+            _ => Err(serde::de::Error::invalid_type(
+                unexpected_borrowed(&value),
+                &visitor,
+            )),
+        }
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == RAW_VALUE_TOKEN {
+            return visitor.visit_byte_buf(crate::to_vec(&self)?);
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for BorrowedValue<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+struct BorrowedListDeserializer<'a> {
+    iter: vec::IntoIter<BorrowedValue<'a>>,
+    /// The index of the next element
+    index: usize,
+}
+
+impl<'a> SeqAccess<'a> for BorrowedListDeserializer<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'a>,
+    {
+        match self.iter.next() {
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(value)
+                    .map(Some)
+                    .map_err(|err| err.with_path_index(index))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+struct BorrowedDictDeserializer<'a> {
+    iter: <BTreeMap<Cow<'a, [u8]>, BorrowedValue<'a>> as IntoIterator>::IntoIter,
+    /// The key and the value of the current entry
+    value: Option<(Cow<'a, [u8]>, BorrowedValue<'a>)>,
+}
+
+impl<'a> MapAccess<'a> for BorrowedDictDeserializer<'a> {
+    type Error = Error;
+
+    fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'a>,
+    {
+        match self.iter.next() {
+            Some(entry) => {
+                // Replaces the entry if the previous value was not read.
+                self.value = None;
+                let key = match self.value.get_or_insert(entry).0 {
+                    Cow::Borrowed(key) => KeyBytes::Borrowed(key),
+                    Cow::Owned(ref key) => KeyBytes::Transient(key),
+                };
+                seed.deserialize(DictKey { key }).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'a>,
+    {
+        match self.value.take() {
+            Some((key, value)) => seed
+                .deserialize(value)
+                .map_err(|err| err.with_path_key(&key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -414,13 +994,122 @@ impl<'a> MapAccess<'a> for DictRefDeserializer<'a> {
     }
 }
 
+fn unexpected_borrowed<'a>(value: &'a BorrowedValue<'_>) -> Unexpected<'a> {
+    match value {
+        BorrowedValue::ByteStr(s) => Unexpected::Bytes(s),
+        BorrowedValue::Int(Number::Signed(s)) => Unexpected::Signed(*s),
+        BorrowedValue::Int(Number::Unsigned(u)) => Unexpected::Unsigned(*u),
+        BorrowedValue::Int(Number::Big(_)) => Unexpected::Other("integer"),
+        BorrowedValue::List(_) => Unexpected::Seq,
+        BorrowedValue::Dict(_) => Unexpected::Map,
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::ByteStr(s) => Unexpected::Bytes(s),
+        Value::Int(Number::Signed(s)) => Unexpected::Signed(*s),
+        Value::Int(Number::Unsigned(u)) => Unexpected::Unsigned(*u),
+        Value::Int(Number::Big(_)) => Unexpected::Other("integer"),
+        Value::List(_) => Unexpected::Seq,
+        Value::Dict(_) => Unexpected::Map,
+    }
+}
+
+/// Deserializes an externally tagged enum.
+///
+/// A unit variant does not have a value.
+struct EnumDeserializer<'a, T> {
+    variant: Cow<'a, [u8]>,
+    value: Option<T>,
+}
+
+impl<'de, T> EnumAccess<'de> for EnumDeserializer<'de, T>
+where
+    T: serde::Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+    type Variant = VariantDeserializer<T>;
+
+    fn variant_seed<S>(self, seed: S) -> Result<(S::Value, Self::Variant), Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(DictKey {
+            key: self.variant.into(),
+        })?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer<T> {
+    value: Option<T>,
+}
+
+impl<'de, T> VariantAccess<'de> for VariantDeserializer<T>
+where
+    T: serde::Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            Some(value) => serde::Deserialize::deserialize(value),
+            None => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<S>(self, seed: S) -> Result<S::Value, Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(value) => seed.deserialize(value),
+            None => Err(serde::de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(value) => value.deserialize_seq(visitor),
+            None => Err(serde::de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(value) => value.deserialize_map(visitor),
+            None => Err(serde::de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{error::Result, from_value};
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
-    use alloc::{string::String, vec, vec::Vec};
+    use alloc::{format, string::String, vec, vec::Vec};
     #[cfg(feature = "std")]
     use std::{string::String, vec, vec::Vec};
 
@@ -464,6 +1153,92 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deserialize_bool_as_int() -> Result<()> {
+        use crate::from_value_bool_as_int;
+        use serde_derive::Deserialize;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum E {
+            Flags(Vec<bool>),
+        }
+
+        assert!(from_value::<bool>(Value::Int(Number::Unsigned(1))).is_err());
+        assert!(from_value_bool_as_int::<bool>(Value::Int(Number::Signed(
+            1
+        )))?);
+        assert!(!from_value_bool_as_int::<bool>(Value::Int(
+            Number::Unsigned(0)
+        ))?);
+        assert!(from_value_bool_as_int::<bool>(Value::Int(Number::Unsigned(2))).is_err());
+        assert!(from_value_bool_as_int::<bool>(Value::Int(Number::Signed(-1))).is_err());
+        assert!(from_value_bool_as_int::<bool>(Value::from("1")).is_err());
+
+        let mut m = BTreeMap::new();
+        m.insert(
+            ByteString::from("Flags"),
+            Value::List(vec![
+                Value::Int(Number::Unsigned(1)),
+                Value::Int(Number::Unsigned(0)),
+            ]),
+        );
+        let e: E = from_value_bool_as_int(Value::Dict(m))?;
+        assert_eq!(e, E::Flags(vec![true, false]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_big_integer() -> Result<()> {
+        let big = String::from("340282366920938463463374607431768211455");
+        let v = Value::Int(Number::Big(big.clone()));
+        assert_eq!(from_value::<u128>(v.clone())?, u128::max_value());
+        assert_eq!(
+            <u128 as serde::Deserialize>::deserialize(&v)?,
+            u128::max_value()
+        );
+        assert!(from_value::<u64>(v).is_err());
+
+        let big = String::from("-1000000000000000000000000000000000000000000");
+        let v = Value::Int(Number::Big(big.clone()));
+        assert_eq!(from_value::<Number>(v.clone())?, Number::Big(big.clone()));
+        assert_eq!(<Value as serde::Deserialize>::deserialize(&v)?, v);
+        assert!(from_value::<i128>(v).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_dict_non_string_keys() -> Result<()> {
+        use serde::Deserialize;
+        use serde_derive::Deserialize;
+
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+        enum Key {
+            Spam,
+        }
+
+        let mut m = BTreeMap::new();
+        m.insert(ByteString::from("10"), Value::from("a"));
+        m.insert(ByteString::from("-9"), Value::from("b"));
+        let d = Value::Dict(m);
+
+        let expected = vec![(-9, String::from("b")), (10, String::from("a"))];
+        let b = BTreeMap::<i64, String>::deserialize(&d)?;
+        assert_eq!(b.into_iter().collect::<Vec<_>>(), expected);
+        let b: BTreeMap<i64, String> = from_value(d.clone())?;
+        assert_eq!(b.into_iter().collect::<Vec<_>>(), expected);
+        assert!(from_value::<BTreeMap<u64, String>>(d).is_err());
+
+        let mut m = BTreeMap::new();
+        m.insert(ByteString::from("+1"), Value::from("a"));
+        assert!(from_value::<BTreeMap<i64, String>>(Value::Dict(m)).is_err());
+
+        let mut m = BTreeMap::new();
+        m.insert(ByteString::from("Spam"), Value::from(1));
+        let d: BTreeMap<Key, u32> = from_value(Value::Dict(m))?;
+        assert_eq!(d.into_iter().collect::<Vec<_>>(), vec![(Key::Spam, 1)]);
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_list() -> Result<()> {
         let v = Value::List(vec![
@@ -632,4 +1407,20 @@ mod tests {
         assert_eq!(d, expected);
         Ok(())
     }
+
+    #[test]
+    fn test_deserialize_error_path() -> Result<()> {
+        use crate::test_util::PathInfo;
+        use serde::Deserialize as _;
+
+        let value: Value = crate::from_slice(b"d5:filesld6:lengthi1eed6:lengthi-1eeee")?;
+
+        let err = PathInfo::deserialize(&value).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "files[1].length");
+        assert_eq!(err.path().len(), 3);
+
+        let err = from_value::<PathInfo>(value).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "files[1].length");
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
-//! Indexes into the [Value] type.
+//! Indexes into the [Value] and [BorrowedValue] types.
 
-use super::Value;
+use super::{BorrowedValue, Value};
 use core::ops;
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
@@ -51,15 +51,15 @@ impl Index for str {
 
 impl Index for String {
     fn index<'a>(&self, v: &'a Value) -> Option<&'a Value> {
-        self[..].index(v)
+        Index::index(&self[..], v)
     }
 
     fn index_mut<'a>(&self, v: &'a mut Value) -> Option<&'a mut Value> {
-        self[..].index_mut(v)
+        Index::index_mut(&self[..], v)
     }
 }
 
-impl<'s, T> Index for &'s T
+impl<T> Index for &T
 where
     T: Index + ?Sized,
 {
@@ -91,3 +91,90 @@ where
         self.get_mut(index).expect("invalid index")
     }
 }
+
+/// Indexes into the [BorrowedValue] type.
+pub trait BorrowedIndex {
+    /// If possible, returns a reference to the value using `&self` as an index for the [BorrowedValue] parameter.
+    fn index<'a, 'v>(&self, v: &'v BorrowedValue<'a>) -> Option<&'v BorrowedValue<'a>>;
+
+    /// If possible, returns a mutable reference to the value using `&self` as an index for the [BorrowedValue] parameter.
+    fn index_mut<'a, 'v>(&self, v: &'v mut BorrowedValue<'a>) -> Option<&'v mut BorrowedValue<'a>>;
+}
+
+impl BorrowedIndex for usize {
+    fn index<'a, 'v>(&self, v: &'v BorrowedValue<'a>) -> Option<&'v BorrowedValue<'a>> {
+        match v {
+            BorrowedValue::List(ref l) => l.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_mut<'a, 'v>(&self, v: &'v mut BorrowedValue<'a>) -> Option<&'v mut BorrowedValue<'a>> {
+        match v {
+            BorrowedValue::List(ref mut l) => l.get_mut(*self),
+            _ => None,
+        }
+    }
+}
+
+impl BorrowedIndex for str {
+    fn index<'a, 'v>(&self, v: &'v BorrowedValue<'a>) -> Option<&'v BorrowedValue<'a>> {
+        match v {
+            BorrowedValue::Dict(ref d) => d.get(self.as_bytes()),
+            _ => None,
+        }
+    }
+
+    fn index_mut<'a, 'v>(&self, v: &'v mut BorrowedValue<'a>) -> Option<&'v mut BorrowedValue<'a>> {
+        match v {
+            BorrowedValue::Dict(ref mut d) => d.get_mut(self.as_bytes()),
+            _ => None,
+        }
+    }
+}
+
+impl BorrowedIndex for String {
+    fn index<'a, 'v>(&self, v: &'v BorrowedValue<'a>) -> Option<&'v BorrowedValue<'a>> {
+        BorrowedIndex::index(&self[..], v)
+    }
+
+    fn index_mut<'a, 'v>(&self, v: &'v mut BorrowedValue<'a>) -> Option<&'v mut BorrowedValue<'a>> {
+        BorrowedIndex::index_mut(&self[..], v)
+    }
+}
+
+impl<T> BorrowedIndex for &T
+where
+    T: BorrowedIndex + ?Sized,
+{
+    fn index<'a, 'v>(&self, val: &'v BorrowedValue<'a>) -> Option<&'v BorrowedValue<'a>> {
+        BorrowedIndex::index(*self, val)
+    }
+
+    fn index_mut<'a, 'v>(
+        &self,
+        val: &'v mut BorrowedValue<'a>,
+    ) -> Option<&'v mut BorrowedValue<'a>> {
+        BorrowedIndex::index_mut(*self, val)
+    }
+}
+
+impl<'a, I> ops::Index<I> for BorrowedValue<'a>
+where
+    I: BorrowedIndex,
+{
+    type Output = BorrowedValue<'a>;
+
+    fn index(&self, index: I) -> &BorrowedValue<'a> {
+        self.get(index).expect("invalid index")
+    }
+}
+
+impl<'a, I> ops::IndexMut<I> for BorrowedValue<'a>
+where
+    I: BorrowedIndex,
+{
+    fn index_mut(&mut self, index: I) -> &mut BorrowedValue<'a> {
+        self.get_mut(index).expect("invalid index")
+    }
+}
@@ -1,8 +1,10 @@
 //! Serializes into a [Value].
 
-use super::{Number, Value};
+use super::{Number, Value, NUMBER_TOKEN};
 use crate::{
     error::{Error, ErrorKind, Result},
+    raw::RAW_VALUE_TOKEN,
+    ser::is_none,
     ByteString,
 };
 use serde::{ser, Serialize};
@@ -12,7 +14,11 @@ use alloc::{collections::BTreeMap, vec::Vec};
 #[cfg(feature = "std")]
 use std::{collections::BTreeMap, vec::Vec};
 
-pub(super) struct Serializer;
+#[derive(Clone, Copy, Default)]
+pub(super) struct Serializer {
+    /// If `bool` values are serialized as the integers 0 and 1
+    pub(super) bool_as_int: bool,
+}
 
 impl ser::Serializer for Serializer {
     type Ok = Value;
@@ -21,14 +27,18 @@ impl ser::Serializer for Serializer {
     type SerializeSeq = SerializeList;
     type SerializeTuple = ser::Impossible<Self::Ok, Error>;
     type SerializeTupleStruct = ser::Impossible<Self::Ok, Error>;
-    type SerializeTupleVariant = ser::Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = SerializeTupleVariant;
     type SerializeMap = SerializeDict;
     type SerializeStruct = SerializeDict;
-    type SerializeStructVariant = ser::Impossible<Self::Ok, Error>;
+    type SerializeStructVariant = SerializeStructVariant;
 
     #[inline]
-    fn serialize_bool(self, _value: bool) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok> {
+        if self.bool_as_int {
+            self.serialize_u64(u64::from(value))
+        } else {
+            Err(Error::with_kind(ErrorKind::UnsupportedType))
+        }
     }
 
     #[inline]
@@ -71,6 +81,16 @@ impl ser::Serializer for Serializer {
         Ok(Value::Int(Number::Unsigned(value)))
     }
 
+    #[inline]
+    fn serialize_i128(self, value: i128) -> Result<Self::Ok> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
+    #[inline]
+    fn serialize_u128(self, value: u128) -> Result<Self::Ok> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
     #[inline]
     fn serialize_f32(self, _value: f32) -> Result<Self::Ok> {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
@@ -125,16 +145,28 @@ impl ser::Serializer for Serializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        self.serialize_str(variant)
     }
 
     #[inline]
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == RAW_VALUE_TOKEN {
+            return match value.serialize(self)? {
+                Value::ByteStr(ref bytes) => crate::from_slice(bytes),
+                _ => Err(Error::with_kind(ErrorKind::UnsupportedType)),
+            };
+        }
+        if name == NUMBER_TOKEN {
+            return match value.serialize(self)?.as_str().map(str::parse) {
+                Some(Ok(number)) => Ok(Value::Int(number)),
+                _ => Err(Error::with_kind(ErrorKind::InvalidInteger)),
+            };
+        }
         value.serialize(self)
     }
 
@@ -143,18 +175,21 @@ impl ser::Serializer for Serializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        let mut dict = BTreeMap::new();
+        dict.insert(ByteString::from(variant), value.serialize(self)?);
+        Ok(Value::Dict(dict))
     }
 
     #[inline]
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
         Ok(SerializeList {
+            ser: self,
             list: Vec::with_capacity(len.unwrap_or(0)),
         })
     }
@@ -178,15 +213,20 @@ impl ser::Serializer for Serializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
+        variant: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        Ok(SerializeTupleVariant {
+            ser: self,
+            variant: ByteString::from(variant),
+            list: Vec::with_capacity(len),
+        })
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
         Ok(SerializeDict {
+            ser: self,
             dict: BTreeMap::new(),
             current_key: None,
         })
@@ -202,10 +242,14 @@ impl ser::Serializer for Serializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        Ok(SerializeStructVariant {
+            ser: self,
+            variant: ByteString::from(variant),
+            dict: BTreeMap::new(),
+        })
     }
 
     fn is_human_readable(&self) -> bool {
@@ -214,6 +258,7 @@ impl ser::Serializer for Serializer {
 }
 
 pub(super) struct SerializeList {
+    ser: Serializer,
     list: Vec<Value>,
 }
 
@@ -226,7 +271,11 @@ impl ser::SerializeSeq for SerializeList {
     where
         T: ?Sized + Serialize,
     {
-        self.list.push(super::to_value(value)?);
+        let index = self.list.len();
+        let value = value
+            .serialize(self.ser)
+            .map_err(|err| err.with_path_index(index))?;
+        self.list.push(value);
         Ok(())
     }
 
@@ -237,6 +286,7 @@ impl ser::SerializeSeq for SerializeList {
 }
 
 pub(super) struct SerializeDict {
+    ser: Serializer,
     dict: BTreeMap<ByteString, Value>,
     current_key: Option<ByteString>,
 }
@@ -266,7 +316,12 @@ impl ser::SerializeMap for SerializeDict {
             .current_key
             .take()
             .ok_or_else(|| Error::with_kind(ErrorKind::ValueWithoutKey))?;
-        let value = super::to_value(value)?;
+        if is_none(value) {
+            return Ok(());
+        }
+        let value = value
+            .serialize(self.ser)
+            .map_err(|err| err.with_path_key(&key))?;
         self.dict.insert(key, value);
         Ok(())
     }
@@ -286,8 +341,13 @@ impl ser::SerializeStruct for SerializeDict {
     where
         T: ?Sized + Serialize,
     {
+        if is_none(value) {
+            return Ok(());
+        }
         let key = key.serialize(&mut DictKeySerializer)?;
-        let value = super::to_value(value)?;
+        let value = value
+            .serialize(self.ser)
+            .map_err(|err| err.with_path_key(&key))?;
         self.dict.insert(key, value);
         Ok(())
     }
@@ -298,6 +358,71 @@ impl ser::SerializeStruct for SerializeDict {
     }
 }
 
+pub(super) struct SerializeTupleVariant {
+    ser: Serializer,
+    variant: ByteString,
+    list: Vec<Value>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let index = self.list.len();
+        let value = value
+            .serialize(self.ser)
+            .map_err(|err| err.with_path_index(index))?;
+        self.list.push(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok> {
+        let mut dict = BTreeMap::new();
+        dict.insert(self.variant, Value::List(self.list));
+        Ok(Value::Dict(dict))
+    }
+}
+
+pub(super) struct SerializeStructVariant {
+    ser: Serializer,
+    variant: ByteString,
+    dict: BTreeMap<ByteString, Value>,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if is_none(value) {
+            return Ok(());
+        }
+        let key = key.serialize(&mut DictKeySerializer)?;
+        let value = value
+            .serialize(self.ser)
+            .map_err(|err| err.with_path_key(&key))?;
+        self.dict.insert(key, value);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok> {
+        let mut dict = BTreeMap::new();
+        dict.insert(self.variant, Value::Dict(self.dict));
+        Ok(Value::Dict(dict))
+    }
+}
+
 struct DictKeySerializer;
 
 impl ser::Serializer for &mut DictKeySerializer {
@@ -316,36 +441,44 @@ impl ser::Serializer for &mut DictKeySerializer {
         Err(Error::with_kind(ErrorKind::UnsupportedType))
     }
 
-    fn serialize_i8(self, _value: i8) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok> {
+        self.serialize_i64(i64::from(value))
     }
 
-    fn serialize_i16(self, _value: i16) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok> {
+        self.serialize_i64(i64::from(value))
     }
 
-    fn serialize_i32(self, _value: i32) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok> {
+        self.serialize_i64(i64::from(value))
     }
 
-    fn serialize_i64(self, _value: i64) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok> {
+        self.serialize_str(itoa::Buffer::new().format(value))
     }
 
-    fn serialize_u8(self, _value: u8) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok> {
+        self.serialize_u64(u64::from(value))
     }
 
-    fn serialize_u16(self, _value: u16) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok> {
+        self.serialize_u64(u64::from(value))
     }
 
-    fn serialize_u32(self, _value: u32) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok> {
+        self.serialize_u64(u64::from(value))
     }
 
-    fn serialize_u64(self, _value: u64) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok> {
+        self.serialize_str(itoa::Buffer::new().format(value))
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<Self::Ok> {
+        self.serialize_str(itoa::Buffer::new().format(value))
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<Self::Ok> {
+        self.serialize_str(itoa::Buffer::new().format(value))
     }
 
     fn serialize_f32(self, _value: f32) -> Result<Self::Ok> {
@@ -381,9 +514,9 @@ impl ser::Serializer for &mut DictKeySerializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok> {
-        Err(Error::with_kind(ErrorKind::UnsupportedType))
+        self.serialize_str(variant)
     }
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
@@ -467,7 +600,7 @@ mod tests {
     use crate::to_value;
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
-    use alloc::{string::String, vec};
+    use alloc::{format, string::String, vec};
     #[cfg(feature = "std")]
     use std::{string::String, vec};
 
@@ -488,6 +621,26 @@ mod tests {
         assert_is_unsupported_type!(to_value(&true));
     }
 
+    #[test]
+    fn test_serialize_bool_as_int() {
+        use crate::to_value_bool_as_int;
+
+        assert_eq!(
+            to_value_bool_as_int(&true).unwrap(),
+            Value::Int(Number::Unsigned(1))
+        );
+        assert_eq!(
+            to_value_bool_as_int(&vec![false]).unwrap(),
+            Value::List(vec![Value::Int(Number::Unsigned(0))])
+        );
+
+        let mut expected = BTreeMap::new();
+        expected.insert(ByteString::from("private"), Value::Int(Number::Unsigned(1)));
+        let mut dict = BTreeMap::new();
+        dict.insert("private", true);
+        assert_eq!(to_value_bool_as_int(&dict).unwrap(), Value::Dict(expected));
+    }
+
     #[test]
     fn test_serialize_isize() {
         let value: isize = 2;
@@ -633,28 +786,42 @@ mod tests {
     fn test_serialize_unit_struct() {
         use serde::Serializer;
 
-        assert_is_unsupported_type!(Serializer.serialize_unit_struct("Nothing"));
+        assert_is_unsupported_type!(super::Serializer::default().serialize_unit_struct("Nothing"));
     }
 
     #[test]
     fn test_serialize_unit_variant() {
         use serde::Serializer;
 
-        assert_is_unsupported_type!(Serializer.serialize_unit_variant("Nothing", 0, "Case"));
+        assert_eq!(
+            super::Serializer::default()
+                .serialize_unit_variant("Nothing", 0, "Case")
+                .unwrap(),
+            Value::ByteStr(ByteString::from("Case"))
+        );
     }
 
     #[test]
     fn test_serialize_newtype_struct() {
         use serde::Serializer;
 
-        Serializer.serialize_newtype_struct("Nothing", &2).unwrap();
+        super::Serializer::default()
+            .serialize_newtype_struct("Nothing", &2)
+            .unwrap();
     }
 
     #[test]
     fn test_serialize_newtype_variant() {
         use serde::Serializer;
 
-        assert_is_unsupported_type!(Serializer.serialize_unit_variant("Nothing", 0, "Case"));
+        let mut expected = BTreeMap::new();
+        expected.insert(ByteString::from("Case"), Value::Int(Number::Signed(2)));
+        assert_eq!(
+            super::Serializer::default()
+                .serialize_newtype_variant("Nothing", 0, "Case", &2)
+                .unwrap(),
+            Value::Dict(expected)
+        );
     }
 
     #[test]
@@ -680,38 +847,145 @@ mod tests {
     fn test_serialize_tuple() {
         use serde::Serializer;
 
-        assert_is_unsupported_type!(Serializer.serialize_tuple(0));
+        assert_is_unsupported_type!(super::Serializer::default().serialize_tuple(0));
     }
 
     #[test]
     fn test_serialize_tuple_struct() {
         use serde::Serializer;
 
-        assert_is_unsupported_type!(Serializer.serialize_tuple_struct("Tuple Struct", 2));
+        assert_is_unsupported_type!(
+            super::Serializer::default().serialize_tuple_struct("Tuple Struct", 2)
+        );
     }
 
     #[test]
     fn test_serialize_tuple_variant() {
-        use serde::Serializer;
+        use serde_derive::Serialize;
 
-        assert_is_unsupported_type!(Serializer.serialize_tuple_variant(
-            "Tuple Variant",
-            2,
-            "Case",
-            1
-        ));
+        #[derive(Serialize)]
+        enum E<'a> {
+            Case(i64, &'a str),
+        }
+
+        let mut expected = BTreeMap::new();
+        expected.insert(
+            ByteString::from("Case"),
+            Value::List(vec![
+                Value::Int(Number::Signed(201)),
+                Value::ByteStr(ByteString::from("spam")),
+            ]),
+        );
+        assert_eq!(
+            to_value(&E::Case(201, "spam")).unwrap(),
+            Value::Dict(expected)
+        );
     }
 
     #[test]
     fn test_serialize_struct_variant() {
-        use serde::Serializer;
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        enum E {
+            Case { int: u32, s: String },
+        }
+
+        let value = E::Case {
+            int: 3,
+            s: String::from("spam"),
+        };
+        let mut fields = BTreeMap::new();
+        fields.insert(ByteString::from("int"), Value::Int(Number::Unsigned(3)));
+        fields.insert(
+            ByteString::from("s"),
+            Value::ByteStr(ByteString::from("spam")),
+        );
+        let mut expected = BTreeMap::new();
+        expected.insert(ByteString::from("Case"), Value::Dict(fields));
+        assert_eq!(to_value(&value).unwrap(), Value::Dict(expected));
+    }
+
+    #[test]
+    fn test_serialize_none_entries() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        struct Test {
+            a: Option<u32>,
+            s: Option<String>,
+        }
 
-        assert_is_unsupported_type!(Serializer.serialize_struct_variant(
-            "Struct Variant",
-            2,
-            "Case",
-            1
-        ));
+        #[derive(Serialize)]
+        enum E {
+            Case { a: Option<u32>, b: u32 },
+        }
+
+        let mut expected = BTreeMap::new();
+        expected.insert(
+            ByteString::from("s"),
+            Value::ByteStr(ByteString::from("spam")),
+        );
+        let test = Test {
+            a: None,
+            s: Some(String::from("spam")),
+        };
+        assert_eq!(to_value(&test).unwrap(), Value::Dict(expected.clone()));
+
+        let mut dict = BTreeMap::new();
+        dict.insert("a", None);
+        dict.insert("s", Some("spam"));
+        assert_eq!(to_value(&dict).unwrap(), Value::Dict(expected));
+
+        let mut fields = BTreeMap::new();
+        fields.insert(ByteString::from("b"), Value::Int(Number::Unsigned(1)));
+        let mut expected = BTreeMap::new();
+        expected.insert(ByteString::from("Case"), Value::Dict(fields));
+        assert_eq!(
+            to_value(&E::Case { a: None, b: 1 }).unwrap(),
+            Value::Dict(expected)
+        );
+
+        assert_is_unsupported_type!(to_value(&vec![None::<u32>]));
+    }
+
+    #[test]
+    fn test_serialize_integer_128() {
+        assert_eq!(to_value(&3_u128).unwrap(), Value::Int(Number::Unsigned(3)));
+        assert_eq!(to_value(&-3_i128).unwrap(), Value::Int(Number::Signed(-3)));
+        assert_eq!(
+            to_value(&i128::min_value()).unwrap(),
+            Value::Int(Number::Big(String::from(
+                "-170141183460469231731687303715884105728"
+            )))
+        );
+
+        let big = Number::Big(String::from("1000000000000000000000000000000000000000000"));
+        assert_eq!(to_value(&big).unwrap(), Value::Int(big));
+        let not_canonical =
+            Number::Big(String::from("01000000000000000000000000000000000000000000"));
+        assert!(to_value(&not_canonical).is_err());
+    }
+
+    #[test]
+    fn test_serialize_dict_non_string_keys() {
+        let mut dict = BTreeMap::new();
+        dict.insert(10_u32, 'a');
+        dict.insert(9, 'b');
+        let mut expected = BTreeMap::new();
+        expected.insert(ByteString::from("10"), Value::from("a"));
+        expected.insert(ByteString::from("9"), Value::from("b"));
+        assert_eq!(to_value(&dict).unwrap(), Value::Dict(expected));
+
+        let mut dict = BTreeMap::new();
+        dict.insert('a', -1_i64);
+        let mut expected = BTreeMap::new();
+        expected.insert(ByteString::from("a"), Value::Int(Number::Signed(-1)));
+        assert_eq!(to_value(&dict).unwrap(), Value::Dict(expected));
+
+        let mut dict = BTreeMap::new();
+        dict.insert(true, 1);
+        assert_is_unsupported_type!(to_value(&dict));
     }
 
     #[test]
@@ -740,4 +1014,19 @@ mod tests {
 
         assert_eq!(to_value(&test).unwrap(), Value::Dict(expected));
     }
+
+    #[test]
+    fn test_serialize_error_path() {
+        let err = to_value(&crate::test_util::path_info()).unwrap_err();
+        match err.kind() {
+            ErrorKind::UnsupportedType => {}
+            _ => panic!("wrong error type"),
+        }
+        assert_eq!(format!("{}", err.path()), "files[1].mtime");
+
+        let mut dict = BTreeMap::new();
+        dict.insert(String::from("piece length"), vec![Some(1), None]);
+        let err = to_value(&dict).unwrap_err();
+        assert_eq!(format!("{}", err.path()), "[\"piece length\"][1]");
+    }
 }
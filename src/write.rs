@@ -1,6 +1,5 @@
 //! [Write] trait and helpers to write bytes for the serializer.
 
-#[cfg(feature = "std")]
 use crate::{error::ErrorKind, Error};
 #[cfg(feature = "std")]
 use std::io;
@@ -10,6 +9,9 @@ use alloc::vec::Vec;
 
 use crate::error::Result;
 
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+pub(crate) mod async_io;
+
 /// Trait used by the [`ser::Serializer`][crate::ser::Serializer] to write bytes.
 pub trait Write {
     /// Writes all of the bytes.
@@ -18,9 +20,35 @@ pub trait Write {
     ///
     /// If the bytes could not be written, a Bencode error is returned.
     fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+
+    /// Returns all of the bytes written so far if they can be rewritten.
+    ///
+    /// The serializer writes dictionary entries directly and sorts the
+    /// written entries in place if a key is out of order. If a writer does
+    /// not return the written bytes, each dictionary which is not nested in
+    /// another dictionary is written to a buffer first.
+    ///
+    /// The default implementation returns `None`.
+    #[inline]
+    fn written(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Discards the written bytes after the first `len` bytes.
+    ///
+    /// Only called if [`written()`][Write::written] returns the written
+    /// bytes. The default implementation does nothing.
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        let _ = len;
+    }
 }
 
 /// A wrapper to implement this crate's [Write] trait for [`std::io::Write`] trait implementations.
+///
+/// The written bytes cannot be rewritten, so each dictionary which is not
+/// nested in another dictionary is serialized into a buffer in memory before
+/// it is written. Other values are written directly.
 #[cfg(feature = "std")]
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -55,11 +83,63 @@ where
     }
 }
 
+/// A wrapper to implement this crate's [Write] trait for
+/// [`embedded_io::Write`] trait implementations.
+///
+/// The written bytes cannot be rewritten, so each dictionary which is not
+/// nested in another dictionary is serialized into a buffer in memory before
+/// it is written. Other values are written directly.
+#[cfg(feature = "embedded-io")]
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EmbeddedIoWrite<W>
+where
+    W: embedded_io::Write,
+{
+    writer: W,
+}
+
+#[cfg(feature = "embedded-io")]
+impl<W> EmbeddedIoWrite<W>
+where
+    W: embedded_io::Write,
+{
+    /// Instantiates a new writer.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<W> Write for EmbeddedIoWrite<W>
+where
+    W: embedded_io::Write,
+{
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        use embedded_io::Error as _;
+
+        self.writer
+            .write_all(buf)
+            .map_err(|error| Error::with_kind(ErrorKind::EmbeddedIo(error.kind())))
+    }
+}
+
 impl Write for Vec<u8> {
     fn write_all(&mut self, buf: &[u8]) -> Result<()> {
         self.extend_from_slice(buf);
         Ok(())
     }
+
+    #[inline]
+    fn written(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
 }
 
 impl Write for &mut Vec<u8> {
@@ -67,4 +147,79 @@ impl Write for &mut Vec<u8> {
         self.extend_from_slice(buf);
         Ok(())
     }
+
+    #[inline]
+    fn written(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+}
+
+/// A wrapper to implement this crate's [Write] trait for a fixed size byte
+/// slice.
+///
+/// Nothing is allocated while writing. If the bytes do not fit in the
+/// remaining space, an [`ErrorKind::BufferFull`] error is returned and none of
+/// the bytes are written.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SliceWrite<'a> {
+    slice: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceWrite<'a> {
+    /// Instantiates a new writer which starts at the beginning of the slice.
+    pub fn new(slice: &'a mut [u8]) -> Self {
+        SliceWrite { slice, len: 0 }
+    }
+
+    /// Returns the number of bytes written.
+    #[must_use]
+    #[inline]
+    pub fn bytes_written(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the bytes written.
+    #[must_use]
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.slice[..self.len]
+    }
+
+    /// Returns the number of bytes which can still be written.
+    #[must_use]
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.slice.len() - self.len
+    }
+}
+
+impl Write for SliceWrite<'_> {
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        if buf.len() > self.remaining() {
+            return Err(Error::new(ErrorKind::BufferFull, self.len));
+        }
+        self.slice[self.len..self.len + buf.len()].copy_from_slice(buf);
+        self.len += buf.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn written(&self) -> Option<&[u8]> {
+        Some(self.as_slice())
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+        }
+    }
 }
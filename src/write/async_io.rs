@@ -0,0 +1,77 @@
+//! Writes bytes to async byte sinks.
+
+use crate::error::{Error, ErrorKind, Result};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+
+/// An async sink of bytes.
+pub(crate) trait AsyncSink {
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>;
+}
+
+/// A future which writes all of the bytes to an [`AsyncSink`].
+struct WriteAll<'a, S> {
+    sink: &'a mut S,
+    buf: &'a [u8],
+}
+
+impl<S> Future for WriteAll<'_, S>
+where
+    S: AsyncSink,
+{
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        while !this.buf.is_empty() {
+            match this.sink.poll_write(cx, this.buf) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(len)) => this.buf = &this.buf[len..],
+                Poll::Ready(Err(err)) if err.kind() == io::ErrorKind::Interrupted => {}
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Writes all of the bytes to the sink.
+pub(crate) async fn write_all<S>(sink: &mut S, buf: &[u8]) -> Result<()>
+where
+    S: AsyncSink,
+{
+    WriteAll { sink, buf }
+        .await
+        .map_err(|error| Error::with_kind(ErrorKind::Io(error)))
+}
+
+/// A wrapper for a [`tokio::io::AsyncWrite`] implementation.
+#[cfg(feature = "tokio")]
+pub(crate) struct TokioSink<W>(pub(crate) W);
+
+#[cfg(feature = "tokio")]
+impl<W> AsyncSink for TokioSink<W>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+}
+
+/// A wrapper for a [`futures_io::AsyncWrite`] implementation.
+#[cfg(feature = "futures-io")]
+pub(crate) struct FuturesSink<W>(pub(crate) W);
+
+#[cfg(feature = "futures-io")]
+impl<W> AsyncSink for FuturesSink<W>
+where
+    W: futures_io::AsyncWrite + Unpin,
+{
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+}
@@ -18,6 +18,17 @@ fn test_deserialize_torrent_file_via_type() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_deserialize_torrent_file_strict() -> Result<(), Error> {
+    let torrent_file: TorrentFile = bt_bencode::from_slice_strict(TORRENT_BYTES)?;
+    assert_eq!(torrent_file.announce, "https://torrent.ubuntu.com/announce");
+
+    let decoded_value: Value = bt_bencode::from_slice_strict(TORRENT_BYTES)?;
+    assert_eq!(bt_bencode::to_vec(&decoded_value)?, TORRENT_BYTES);
+
+    Ok(())
+}
+
 #[test]
 fn test_deserialize_torrent_file_via_value() -> Result<(), Error> {
     let decoded_value: Value = bt_bencode::from_slice(TORRENT_BYTES)?;